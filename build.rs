@@ -10,6 +10,7 @@ use std::{
 use strfmt::strfmt;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Error {
     message: String,
 }
@@ -30,7 +31,7 @@ impl From<env::VarError> for Error {
     }
 }
 
-type Matcher = fn(&PathBuf) -> bool;
+type Matcher = fn(&Path) -> bool;
 
 fn generate_tests(destination: &str, module: &str, path: &str, template: &str, matcher: Matcher) -> Result<(), Error> {
     let out_dir = env::var("OUT_DIR")?;
@@ -42,14 +43,9 @@ fn generate_tests(destination: &str, module: &str, path: &str, template: &str, m
     Ok(())
 }
 
-fn generate_tests_module(
-    mut test_file: &mut File,
-    dir: &PathBuf,
-    template: &str,
-    matcher: Matcher,
-) -> Result<(), Error> {
+fn generate_tests_module(test_file: &mut File, dir: &Path, template: &str, matcher: Matcher) -> Result<(), Error> {
     let module_name = normalize_file_stem(dir)?;
-    start_module(&mut test_file, &module_name)?;
+    start_module(test_file, &module_name)?;
 
     for entry in read_dir(dir)? {
         let entry = entry?;
@@ -64,13 +60,13 @@ fn generate_tests_module(
         }
     }
 
-    end_module(&mut test_file)?;
+    end_module(test_file)?;
     Ok(())
 }
 
-fn generate_test(test_file: &mut File, path: &PathBuf, template: &str) -> Result<(), Error> {
+fn generate_test(test_file: &mut File, path: &Path, template: &str) -> Result<(), Error> {
     let mut vars = HashMap::new();
-    vars.insert("name".to_string(), format!("{}", normalize_file_stem(&path)?));
+    vars.insert("name".to_string(), normalize_file_stem(path)?);
     vars.insert(
         "path".to_string(),
         path.to_str().expect("unable to format path as a string").to_string(),
@@ -91,6 +87,7 @@ mod {name} {{
     use jellyschema::schema::Schema;
     use jellyschema::error::Error;
     use jellyschema::generator::generate_json_ui_schema;
+    use jellyschema::validator::path::PathFormat;
     use pretty_assertions::assert_eq;
     use serde_json;
     use serde_yaml;
@@ -115,7 +112,7 @@ fn end_module(test_file: &mut File) -> Result<(), Error> {
     Ok(())
 }
 
-fn normalize_file_stem(path: &PathBuf) -> Result<String, Error> {
+fn normalize_file_stem(path: &Path) -> Result<String, Error> {
     let result = path
         .file_stem()
         .ok_or(Error {
@@ -126,14 +123,14 @@ fn normalize_file_stem(path: &PathBuf) -> Result<String, Error> {
     Ok(result.to_string())
 }
 
-fn validator_tests_matcher(path: &PathBuf) -> bool {
+fn validator_tests_matcher(path: &Path) -> bool {
     match path.extension() {
         Some(ext) => ext == "yaml",
         _ => false,
     }
 }
 
-fn generator_tests_matcher(path: &PathBuf) -> bool {
+fn generator_tests_matcher(path: &Path) -> bool {
     match path.file_name() {
         Some(name) => name == "input-schema.yaml",
         _ => false,
@@ -0,0 +1,20 @@
+const jels = require('jellyschema');
+
+test('includes documents', () => {
+    schema = new jels.JellySchema(
+        'include: ntp.yml\nproperties:\n  - hostname:\n      type: hostname\n',
+        {
+            'ntp.yml': 'properties:\n  - ntp:\n      type: stringlist\n'
+        }
+    );
+    expect(schema.jsonAndUiSchema().jsonSchema['$$order']).toEqual(['ntp', 'hostname']);
+});
+
+test('error points at the included document', () => {
+    expect.assertions(1);
+    try {
+        new jels.JellySchema('include: ntp.yml', {'ntp.yml': 'properties:\n  - ntp:\n      type: foo\n'});
+    } catch (e) {
+        expect(e.file).toEqual('ntp.yml');
+    }
+});
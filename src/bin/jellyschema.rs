@@ -0,0 +1,574 @@
+//! `jellyschema` command line tool
+//!
+//! Built with the `cli` feature only (`cargo install jellyschema --features cli`).
+//! Data files can be in the JSON or YAML format, `-` reads the data from the standard
+//! input.
+//!
+//! Exit codes:
+//!
+//! * `0` - success, data or schema are valid
+//! * `1` - data or schema are not valid
+//! * `2` - invalid arguments, unreadable file, invalid schema, ...
+use std::{
+    env, fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use serde_json::{json, Value};
+
+use jellyschema::{
+    error::Error,
+    evaluator::evaluate_formulas,
+    filler::fill_default_values,
+    generator::{generate_json_ui_schema, generate_json_ui_schema_with_data},
+    lint::lint,
+    mapping::render,
+    schema::Schema,
+    validator::{path::PathFormat, validate},
+};
+
+const USAGE: &str = "Usage: jellyschema <command> [options]
+
+Commands:
+    generate <schema> [--data <data>]         Generate JSON Schema & UI Schema
+             [--locale <l>]
+    validate <schema> <data> [--format <f>]   Validate data against the schema
+             [--pointers]
+    fill <schema> <data> [--optional]         Fill default values
+    lint <schema> [--format <f>]              Check schema consistency
+    render <schema> <data> <directory>        Render mapping targets into the directory
+
+Options:
+    --data <data>      Data used to hide inactive properties in the UI Schema
+    --format <f>       Output format, human (default) or json
+    --locale <l>       Annotations locale (de, pt-BR, ...), English by default
+    --optional         Fill default values of optional properties as well
+    --pointers         Render data & schema paths as JSON Pointers (/networks/0/ssid)
+    -h, --help         Print this help";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Human,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Generate {
+        schema: PathBuf,
+        data: Option<PathBuf>,
+        locale: Option<String>,
+    },
+    Validate {
+        schema: PathBuf,
+        data: PathBuf,
+        format: Format,
+        path_format: PathFormat,
+    },
+    Fill {
+        schema: PathBuf,
+        data: PathBuf,
+        optional: bool,
+    },
+    Lint {
+        schema: PathBuf,
+        format: Format,
+    },
+    Render {
+        schema: PathBuf,
+        data: PathBuf,
+        directory: PathBuf,
+    },
+    Help,
+}
+
+// Command line arguments split into positional arguments & options
+struct Arguments {
+    positional: Vec<String>,
+    data: Option<String>,
+    format: Option<String>,
+    locale: Option<String>,
+    optional: bool,
+    pointers: bool,
+}
+
+impl Arguments {
+    fn parse(args: &[String]) -> Result<Arguments, String> {
+        let mut result = Arguments {
+            positional: vec![],
+            data: None,
+            format: None,
+            locale: None,
+            optional: false,
+            pointers: false,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--data" | "--format" | "--locale" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("option '{}' requires a value", arg))?
+                        .clone();
+                    match arg.as_str() {
+                        "--data" => result.data = Some(value),
+                        "--format" => result.format = Some(value),
+                        _ => result.locale = Some(value),
+                    };
+                }
+                "--optional" => result.optional = true,
+                "--pointers" => result.pointers = true,
+                x if x.starts_with("--") => return Err(format!("unknown option '{}'", x)),
+                _ => result.positional.push(arg.clone()),
+            };
+        }
+
+        Ok(result)
+    }
+
+    fn positional(&self, count: usize) -> Result<Vec<PathBuf>, String> {
+        if self.positional.len() != count {
+            return Err(format!(
+                "expected {} argument(s), found {}",
+                count,
+                self.positional.len()
+            ));
+        }
+        Ok(self.positional.iter().map(PathBuf::from).collect())
+    }
+
+    fn format(&self) -> Result<Format, String> {
+        match self.format.as_deref() {
+            None | Some("human") => Ok(Format::Human),
+            Some("json") => Ok(Format::Json),
+            Some(x) => Err(format!("unknown format '{}'", x)),
+        }
+    }
+
+    // Fails if an option not supported by the command was used
+    fn check_options(&self, command: &str, supported: &[&str]) -> Result<(), String> {
+        let used = [
+            ("--data", self.data.is_some()),
+            ("--format", self.format.is_some()),
+            ("--locale", self.locale.is_some()),
+            ("--optional", self.optional),
+            ("--pointers", self.pointers),
+        ];
+
+        match used.iter().find(|(option, used)| *used && !supported.contains(option)) {
+            Some((option, _)) => Err(format!(
+                "command '{}' does not support the '{}' option",
+                command, option
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    let (command, args) = match args.split_first() {
+        Some(x) => x,
+        None => return Err("missing command".to_string()),
+    };
+
+    if command == "-h" || command == "--help" || command == "help" || args.iter().any(|x| x == "-h" || x == "--help") {
+        return Ok(Command::Help);
+    }
+
+    let args = Arguments::parse(args)?;
+
+    let command = match command.as_str() {
+        "generate" => {
+            args.check_options(command, &["--data", "--locale"])?;
+            let mut positional = args.positional(1)?;
+            Command::Generate {
+                schema: positional.remove(0),
+                data: args.data.as_ref().map(PathBuf::from),
+                locale: args.locale.clone(),
+            }
+        }
+        "validate" => {
+            args.check_options(command, &["--format", "--pointers"])?;
+            let mut positional = args.positional(2)?;
+            Command::Validate {
+                schema: positional.remove(0),
+                data: positional.remove(0),
+                format: args.format()?,
+                path_format: if args.pointers {
+                    PathFormat::Pointer
+                } else {
+                    PathFormat::Dotted
+                },
+            }
+        }
+        "fill" => {
+            args.check_options(command, &["--optional"])?;
+            let mut positional = args.positional(2)?;
+            Command::Fill {
+                schema: positional.remove(0),
+                data: positional.remove(0),
+                optional: args.optional,
+            }
+        }
+        "lint" => {
+            args.check_options(command, &["--format"])?;
+            let mut positional = args.positional(1)?;
+            Command::Lint {
+                schema: positional.remove(0),
+                format: args.format()?,
+            }
+        }
+        "render" => {
+            args.check_options(command, &[])?;
+            let mut positional = args.positional(3)?;
+            Command::Render {
+                schema: positional.remove(0),
+                data: positional.remove(0),
+                directory: positional.remove(0),
+            }
+        }
+        x => return Err(format!("unknown command '{}'", x)),
+    };
+
+    Ok(command)
+}
+
+fn file_error<E>(path: &Path, error: E) -> Error
+where
+    E: fmt::Display,
+{
+    Error::message(format!("{}: {}", path.display(), error))
+}
+
+fn read_schema(path: &Path) -> Result<Schema, Error> {
+    let (schema, unknown) = Schema::parse_file(path, None)?;
+    for keyword in unknown {
+        eprintln!("warning: {}", keyword);
+    }
+    Ok(schema)
+}
+
+// Reads JSON or YAML data, `-` reads the standard input
+fn read_data(path: &Path) -> Result<Value, Error> {
+    let source = if path == Path::new("-") {
+        let mut source = String::new();
+        io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| file_error(path, e))?;
+        source
+    } else {
+        fs::read_to_string(path).map_err(|e| file_error(path, e))?
+    };
+
+    serde_yaml::from_str(&source).map_err(|e| file_error(path, e))
+}
+
+fn write_json(output: &mut dyn Write, value: &Value) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| Error::message(e.to_string()))?;
+    writeln!(output, "{}", json).map_err(|e| Error::message(e.to_string()))
+}
+
+fn write_line(output: &mut dyn Write, line: String) -> Result<(), Error> {
+    writeln!(output, "{}", line).map_err(|e| Error::message(e.to_string()))
+}
+
+fn path_message(path: &str, message: &str) -> String {
+    if path.is_empty() {
+        message.to_string()
+    } else {
+        format!("'{}': {}", path, message)
+    }
+}
+
+// Returns `Ok(false)` if the data or schema are not valid
+fn run(command: Command, output: &mut dyn Write) -> Result<bool, Error> {
+    match command {
+        Command::Help => write_line(output, USAGE.to_string())?,
+        Command::Generate { schema, data, locale } => {
+            let schema = read_schema(&schema)?;
+            let locale = locale.as_deref();
+            let (json_schema, ui_schema) = match data {
+                Some(data) => generate_json_ui_schema_with_data(&schema, &read_data(&data)?, locale),
+                None => generate_json_ui_schema(&schema, locale),
+            };
+            write_json(output, &json!({ "jsonSchema": json_schema, "uiSchema": ui_schema }))?;
+        }
+        Command::Validate {
+            schema,
+            data,
+            format,
+            path_format,
+        } => {
+            let schema = read_schema(&schema)?;
+            let state = validate(&schema, &read_data(&data)?).with_path_format(path_format);
+
+            match format {
+                Format::Json => write_json(output, &json!({ "valid": state.is_valid(), "errors": state.errors() }))?,
+                Format::Human => {
+                    for error in state.errors() {
+                        write_line(output, path_message(&error.data_path(), error.message()))?;
+                    }
+                }
+            };
+
+            return Ok(state.is_valid());
+        }
+        Command::Fill { schema, data, optional } => {
+            let schema = read_schema(&schema)?;
+            let mut data = read_data(&data)?;
+            fill_default_values(&schema, &mut data, optional);
+            write_json(output, &data)?;
+        }
+        Command::Lint { schema, format } => {
+            let schema = read_schema(&schema)?;
+            let errors = lint(&schema);
+
+            match format {
+                Format::Json => write_json(output, &json!({ "valid": errors.is_empty(), "errors": errors }))?,
+                Format::Human => {
+                    for error in errors.iter() {
+                        write_line(output, path_message(error.schema_path(), error.message()))?;
+                    }
+                }
+            };
+
+            return Ok(errors.is_empty());
+        }
+        Command::Render {
+            schema,
+            data,
+            directory,
+        } => {
+            let schema = read_schema(&schema)?;
+            let mut data = read_data(&data)?;
+
+            fill_default_values(&schema, &mut data, false);
+            evaluate_formulas(&schema, &mut data)?;
+
+            let state = validate(&schema, &data);
+            if !state.is_valid() {
+                for error in state.errors() {
+                    write_line(output, path_message(&error.data_path(), error.message()))?;
+                }
+                return Ok(false);
+            }
+
+            // Files are stored as `<directory>/<partition>/<path>`
+            for (file, content) in render(&schema, &data)?.iter() {
+                let mut path = directory.join(file.location().partition().to_string());
+                path.push(file.path().trim_start_matches('/'));
+
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| file_error(parent, e))?;
+                }
+                fs::write(&path, content).map_err(|e| file_error(&path, e))?;
+                write_line(output, path.display().to_string())?;
+            }
+        }
+    };
+
+    Ok(true)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = match parse_command(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    match run(command, &mut output) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+    version: 2
+    mapping:
+      targets:
+        config-json:
+          type: file
+          format: json
+          location:
+            partition: resin-boot
+            path: /config.json
+    properties:
+      - hostname:
+          type: hostname
+          default: balena
+          mapping:
+            target: config-json
+            path: hostname
+      - port:
+          type: port?
+          mapping:
+            target: config-json
+            path: port
+    "#;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    // Creates an empty temporary directory with the schema file
+    fn directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("jellyschema-cli-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("schema.yml"), SCHEMA).unwrap();
+        directory
+    }
+
+    fn run_command(command: Command) -> (bool, String) {
+        let mut output = vec![];
+        let result = run(command, &mut output).unwrap();
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            parse_command(&args("validate schema.yml data.json --format json")),
+            Ok(Command::Validate {
+                schema: "schema.yml".into(),
+                data: "data.json".into(),
+                format: Format::Json,
+                path_format: PathFormat::Dotted,
+            })
+        );
+        assert_eq!(
+            parse_command(&args("fill --optional schema.yml -")),
+            Ok(Command::Fill {
+                schema: "schema.yml".into(),
+                data: "-".into(),
+                optional: true,
+            })
+        );
+        assert_eq!(
+            parse_command(&args("generate schema.yml --data data.yml --locale de")),
+            Ok(Command::Generate {
+                schema: "schema.yml".into(),
+                data: Some("data.yml".into()),
+                locale: Some("de".to_string()),
+            })
+        );
+        assert_eq!(parse_command(&args("lint schema.yml --help")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn parse_invalid_commands() {
+        assert_eq!(parse_command(&[]), Err("missing command".to_string()));
+        assert_eq!(
+            parse_command(&args("check schema.yml")),
+            Err("unknown command 'check'".to_string())
+        );
+        assert_eq!(
+            parse_command(&args("validate schema.yml")),
+            Err("expected 2 argument(s), found 1".to_string())
+        );
+        assert_eq!(
+            parse_command(&args("lint schema.yml --format xml")),
+            Err("unknown format 'xml'".to_string())
+        );
+        assert_eq!(
+            parse_command(&args("lint schema.yml --optional")),
+            Err("command 'lint' does not support the '--optional' option".to_string())
+        );
+        assert_eq!(
+            parse_command(&args("validate schema.yml --format")),
+            Err("option '--format' requires a value".to_string())
+        );
+    }
+
+    #[test]
+    fn validate_data() {
+        let directory = directory("validate");
+        fs::write(directory.join("data.yml"), "hostname: balena\nport: 80000\n").unwrap();
+
+        let command = |format, path_format| Command::Validate {
+            schema: directory.join("schema.yml"),
+            data: directory.join("data.yml"),
+            format,
+            path_format,
+        };
+
+        let (valid, output) = run_command(command(Format::Human, PathFormat::Dotted));
+        assert!(!valid);
+        assert!(output.starts_with("'port': "));
+
+        let (_, output) = run_command(command(Format::Human, PathFormat::Pointer));
+        assert!(output.starts_with("'/port': "));
+
+        let (valid, output) = run_command(command(Format::Json, PathFormat::Dotted));
+        let output: Value = serde_json::from_str(&output).unwrap();
+        assert!(!valid);
+        assert_eq!(output["valid"], json!(false));
+        assert_eq!(output["errors"][0]["dataPath"], json!("port"));
+    }
+
+    #[test]
+    fn fill_data() {
+        let directory = directory("fill");
+        fs::write(directory.join("data.json"), "{}").unwrap();
+
+        let (_, output) = run_command(Command::Fill {
+            schema: directory.join("schema.yml"),
+            data: directory.join("data.json"),
+            optional: false,
+        });
+        assert_eq!(
+            serde_json::from_str::<Value>(&output).unwrap(),
+            json!({"hostname": "balena"})
+        );
+    }
+
+    #[test]
+    fn render_data() {
+        let directory = directory("render");
+        fs::write(directory.join("data.json"), r#"{"port": 8080}"#).unwrap();
+
+        let (valid, _) = run_command(Command::Render {
+            schema: directory.join("schema.yml"),
+            data: directory.join("data.json"),
+            directory: directory.join("output"),
+        });
+        assert!(valid);
+
+        let content = fs::read_to_string(directory.join("output/resin-boot/config.json")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&content).unwrap(),
+            json!({"hostname": "balena", "port": 8080})
+        );
+    }
+
+    #[test]
+    fn invalid_schema_file() {
+        let result = run(
+            Command::Lint {
+                schema: "does-not-exist.yml".into(),
+                format: Format::Human,
+            },
+            &mut vec![],
+        );
+        assert!(result.is_err());
+    }
+}
@@ -1,10 +1,20 @@
 use std::{error, fmt};
 
 use serde;
+use serde_derive::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Error {
+    #[serde(rename = "message")]
     msg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(rename = "schemaPath", skip_serializing_if = "Option::is_none")]
+    schema_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
 }
 
 impl Error {
@@ -12,7 +22,91 @@ impl Error {
     where
         S: Into<String>,
     {
-        Error { msg: msg.into() }
+        Error {
+            msg: msg.into(),
+            line: None,
+            column: None,
+            schema_path: None,
+            file: None,
+        }
+    }
+
+    /// Creates an error with the message prefixed with the path (`'path': message`)
+    ///
+    /// The message is not prefixed if the path is empty.
+    pub(crate) fn at_path<P, S>(path: P, message: S) -> Error
+    where
+        P: fmt::Display,
+        S: AsRef<str>,
+    {
+        let path = path.to_string();
+
+        if path.is_empty() {
+            Error::message(message.as_ref())
+        } else {
+            Error::message(format!("'{}': {}", path, message.as_ref()))
+        }
+    }
+
+    /// Same as [`at_path`](#method.at_path), the path is attached as the logical schema path
+    pub(crate) fn at_schema_path<P, S>(path: P, message: S) -> Error
+    where
+        P: fmt::Display,
+        S: AsRef<str>,
+    {
+        let path = path.to_string();
+        Error::at_path(&path, message).with_schema_path(path)
+    }
+
+    /// Attaches the schema source position (1-based line & column)
+    pub fn with_position(self, line: usize, column: usize) -> Error {
+        Error {
+            line: Some(line),
+            column: Some(column),
+            ..self
+        }
+    }
+
+    /// Attaches the logical schema path (`properties[3].network.items.properties[1]`)
+    pub fn with_schema_path<S>(self, schema_path: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error {
+            schema_path: Some(schema_path.into()),
+            ..self
+        }
+    }
+
+    /// Attaches the name of the schema document (see [`IncludeResolver`](../schema/trait.IncludeResolver.html))
+    pub fn with_file<S>(self, file: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error {
+            file: Some(file.into()),
+            ..self
+        }
+    }
+
+    /// Schema source line (1-based)
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Schema source column (1-based)
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Logical schema path
+    pub fn schema_path(&self) -> Option<&str> {
+        self.schema_path.as_deref()
+    }
+
+    /// Schema document name, `None` for the root document
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
     }
 }
 
@@ -20,12 +114,15 @@ impl error::Error for Error {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.msg)
+        match &self.file {
+            Some(file) => write!(f, "{}: {}", file, self.msg),
+            None => write!(f, "{}", self.msg),
+        }
     }
 }
 
 impl<T: serde::de::Error + 'static> From<T> for Error {
     fn from(e: T) -> Error {
-        Error { msg: format!("{}", e) }
+        Error::message(format!("{}", e))
     }
 }
@@ -0,0 +1,160 @@
+//! Formula dependencies
+//!
+//! A formula depends on another one if it refers to its value, to any value
+//! nested in it or to any of its parents.
+use balena_temen::ast::{Expression, ExpressionValue, Identifier, IdentifierValue};
+
+use crate::error::Error;
+
+fn collect_value_identifiers<'a>(value: &'a ExpressionValue, result: &mut Vec<&'a Identifier>) {
+    match value {
+        ExpressionValue::Identifier(identifier) => result.push(identifier),
+        ExpressionValue::Math(math) => {
+            collect_identifiers(&math.lhs, result);
+            collect_identifiers(&math.rhs, result);
+        }
+        ExpressionValue::Logical(logical) => {
+            collect_identifiers(&logical.lhs, result);
+            collect_identifiers(&logical.rhs, result);
+        }
+        ExpressionValue::FunctionCall(call) => {
+            for arg in &call.args {
+                collect_identifiers(arg, result);
+            }
+        }
+        ExpressionValue::StringConcat(concat) => {
+            for value in &concat.values {
+                collect_value_identifiers(value, result);
+            }
+        }
+        ExpressionValue::Ternary(ternary) => {
+            collect_identifiers(&ternary.condition, result);
+            collect_identifiers(&ternary.truthy, result);
+            collect_identifiers(&ternary.falsy, result);
+        }
+        ExpressionValue::Integer(_)
+        | ExpressionValue::Float(_)
+        | ExpressionValue::Boolean(_)
+        | ExpressionValue::String(_) => {}
+    };
+}
+
+fn collect_identifiers<'a>(expression: &'a Expression, result: &mut Vec<&'a Identifier>) {
+    collect_value_identifiers(&expression.value, result);
+
+    for filter in &expression.filters {
+        for arg in &filter.args {
+            collect_identifiers(arg, result);
+        }
+    }
+}
+
+// Collects the longest statically known prefix of a canonical identifier
+//
+// `networks[boss.id].ssid` leads to two dependencies - `networks` & `boss.id`, because
+// we do not know which network is going to be used before the evaluation.
+fn collect_static_prefixes(identifier: &Identifier, result: &mut Vec<Identifier>) {
+    let mut prefix = vec![];
+
+    for value in &identifier.values {
+        match value {
+            IdentifierValue::Name(_) => prefix.push(value.clone()),
+            IdentifierValue::Index(index) if *index >= 0 => prefix.push(value.clone()),
+            IdentifierValue::Identifier(nested) => {
+                collect_static_prefixes(nested, result);
+                break;
+            }
+            _ => break,
+        };
+    }
+
+    result.push(Identifier::new(prefix));
+}
+
+/// Returns list of canonical identifiers the expression depends on
+///
+/// # Arguments
+///
+/// * `expression` - Parsed formula
+/// * `position` - Formula position (relative identifiers are resolved against it)
+pub fn dependencies(expression: &Expression, position: &Identifier) -> Result<Vec<Identifier>, Error> {
+    let mut identifiers = vec![];
+    collect_identifiers(expression, &mut identifiers);
+
+    let mut result = vec![];
+
+    for identifier in identifiers {
+        let canonical = identifier
+            .canonicalize(position)
+            .map_err(|e| Error::message(format!("unable to resolve identifier: {}", e)))?;
+        collect_static_prefixes(&canonical, &mut result);
+    }
+
+    Ok(result)
+}
+
+/// Checks if two identifiers refer to overlapping values
+///
+/// Values are overlapping if they're equal or one of them is nested in the other one.
+pub fn overlaps(lhs: &Identifier, rhs: &Identifier) -> bool {
+    lhs.values.starts_with(&rhs.values) || rhs.values.starts_with(&lhs.values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deps(expression: &str, position: &str) -> Vec<Identifier> {
+        let position = if position.is_empty() {
+            Identifier::default()
+        } else {
+            position.parse().unwrap()
+        };
+        dependencies(&expression.parse().unwrap(), &position).unwrap()
+    }
+
+    fn identifier(s: &str) -> Identifier {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn absolute_identifiers() {
+        assert_eq!(
+            deps("host ~ `.` ~ domain", ""),
+            vec![identifier("host"), identifier("domain")]
+        );
+    }
+
+    #[test]
+    fn relative_identifiers() {
+        assert_eq!(
+            deps("super.ssid | SLUGIFY", "network[0].id"),
+            vec![identifier("network[0].ssid")]
+        );
+        assert_eq!(deps("this.id", "network[1]"), vec![identifier("network[1].id")]);
+    }
+
+    #[test]
+    fn nested_expressions() {
+        assert_eq!(
+            deps("(a > 1 ? MAX(b, c) : d) | TRIM", ""),
+            vec![identifier("a"), identifier("b"), identifier("c"), identifier("d")]
+        );
+    }
+
+    #[test]
+    fn indirect_identifiers() {
+        assert_eq!(
+            deps("networks[boss.id].ssid", ""),
+            vec![identifier("boss.id"), identifier("networks")]
+        );
+    }
+
+    #[test]
+    fn overlapping_identifiers() {
+        assert!(overlaps(&identifier("a.b"), &identifier("a.b")));
+        assert!(overlaps(&identifier("a"), &identifier("a.b")));
+        assert!(overlaps(&identifier("a.b.c"), &identifier("a")));
+        assert!(!overlaps(&identifier("a.b"), &identifier("a.c")));
+    }
+}
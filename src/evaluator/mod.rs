@@ -0,0 +1,454 @@
+//! A module containing formula evaluator
+//!
+//! Values of properties with the `formula` keyword are computed with the [balena-temen]
+//! expression engine. Formulas can refer to any other value via an absolute identifier
+//! (`network.ssid`) or relative to the property position (`this.id`, `super.ssid`).
+//! A formula referring to a computed value is evaluated after the referred one, circular
+//! dependencies are reported as an error.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::evaluator::evaluate_formulas;
+//! use jellyschema::schema::Schema;
+//! use serde_json::json;
+//!
+//! let schema: Schema = r#"
+//!   properties:
+//!     - fqdn:
+//!         type: hostname
+//!         formula: host ~ `.` ~ domain
+//!     - domain:
+//!         type: string
+//!     - host:
+//!         type: string
+//! "#.parse().unwrap();
+//!
+//! let mut data = json!({"host": "device", "domain": "balena.io"});
+//! evaluate_formulas(&schema, &mut data).unwrap();
+//!
+//! assert_eq!(data["fqdn"], json!("device.balena.io"));
+//! ```
+//!
+//! [balena-temen]: https://github.com/balena-io-modules/balena-temen
+use std::convert::TryFrom;
+
+use balena_temen::{
+    ast::{Expression, Identifier, IdentifierValue},
+    Context, Engine,
+};
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    schema::Schema,
+    validator::path::{Component, PathBuf},
+};
+
+mod dependencies;
+
+struct Formula<'a> {
+    position: Identifier,
+    expression: &'a str,
+    dependencies: Vec<Identifier>,
+}
+
+impl<'a> Formula<'a> {
+    fn new(position: Identifier, expression: &'a str) -> Result<Formula<'a>, Error> {
+        let parsed: Expression = expression.parse().map_err(|e| {
+            Error::at_path(
+                position_to_string(&position),
+                format!("invalid formula '{}': {}", expression, e),
+            )
+        })?;
+        let dependencies = dependencies::dependencies(&parsed, &position)
+            .map_err(|e| Error::at_path(position_to_string(&position), e.to_string()))?;
+
+        Ok(Formula {
+            position,
+            expression,
+            dependencies,
+        })
+    }
+
+    fn depends_on(&self, other: &Formula) -> bool {
+        self.dependencies
+            .iter()
+            .any(|dependency| dependencies::overlaps(dependency, &other.position))
+    }
+}
+
+fn position_to_string(position: &Identifier) -> String {
+    let mut result = String::new();
+
+    for value in &position.values {
+        match value {
+            IdentifierValue::Name(name) => {
+                if !result.is_empty() {
+                    result.push('.');
+                }
+                result.push_str(name);
+            }
+            IdentifierValue::Index(index) => result.push_str(&format!("[{}]", index)),
+            _ => {}
+        };
+    }
+
+    result
+}
+
+// Formulas are collected only if the parent value exists, we do not create missing
+// objects or array items
+fn collect_formulas<'a>(
+    schema: &'a Schema,
+    data: &Value,
+    position: &Identifier,
+    formulas: &mut Vec<Formula<'a>>,
+) -> Result<(), Error> {
+    if let Some(expression) = schema.formula() {
+        // Computed value, nested formulas are not evaluated
+        formulas.push(Formula::new(position.clone(), expression)?);
+        return Ok(());
+    }
+
+    match data {
+        Value::Object(object) => {
+            for property in schema.properties() {
+                let value = object.get(property.name()).unwrap_or(&Value::Null);
+                collect_formulas(
+                    property.schema(),
+                    value,
+                    &position.clone().name(property.name()),
+                    formulas,
+                )?;
+            }
+        }
+        Value::Array(array) => {
+            if let [items_schema] = schema.items() {
+                for (idx, item) in array.iter().enumerate() {
+                    collect_formulas(items_schema, item, &position.clone().index(idx as isize), formulas)?;
+                }
+            }
+        }
+        _ => {}
+    };
+
+    Ok(())
+}
+
+// Depth first topological sort, returns formula indexes in the evaluation order
+fn evaluation_order(formulas: &[Formula]) -> Result<Vec<usize>, Error> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        None,
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        idx: usize,
+        formulas: &[Formula],
+        marks: &mut Vec<Mark>,
+        stack: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        match marks[idx] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let start = stack.iter().position(|x| *x == idx).expect("invalid formula stack");
+                let cycle: Vec<String> = stack[start..]
+                    .iter()
+                    .chain(Some(&idx))
+                    .map(|x| position_to_string(&formulas[*x].position))
+                    .collect();
+                return Err(Error::message(format!(
+                    "circular formula dependency: {}",
+                    cycle.join(" -> ")
+                )));
+            }
+            Mark::None => {}
+        };
+
+        marks[idx] = Mark::Visiting;
+        stack.push(idx);
+
+        for dependency in 0..formulas.len() {
+            if dependency != idx && formulas[idx].depends_on(&formulas[dependency]) {
+                visit(dependency, formulas, marks, stack, order)?;
+            }
+        }
+
+        stack.pop();
+        marks[idx] = Mark::Done;
+        order.push(idx);
+        Ok(())
+    }
+
+    let mut marks = vec![Mark::None; formulas.len()];
+    let mut stack = vec![];
+    let mut order = vec![];
+
+    for idx in 0..formulas.len() {
+        visit(idx, formulas, &mut marks, &mut stack, &mut order)?;
+    }
+
+    Ok(order)
+}
+
+fn set_value(data: &mut Value, position: &Identifier, value: Value) -> Result<(), Error> {
+    let (last, parents) = match position.values.split_last() {
+        Some(x) => x,
+        None => {
+            *data = value;
+            return Ok(());
+        }
+    };
+
+    let mut current = data;
+
+    for component in parents {
+        current = match component {
+            IdentifierValue::Name(name) => current.get_mut(name),
+            IdentifierValue::Index(idx) => current.get_mut(*idx as usize),
+            _ => None,
+        }
+        .ok_or_else(|| Error::at_path(position_to_string(position), "parent value does not exist"))?;
+    }
+
+    match (current, last) {
+        (Value::Object(object), IdentifierValue::Name(name)) => {
+            object.insert(name.clone(), value);
+        }
+        (Value::Array(array), IdentifierValue::Index(idx)) if (*idx as usize) < array.len() => {
+            array[*idx as usize] = value;
+        }
+        _ => {
+            return Err(Error::at_path(
+                position_to_string(position),
+                "parent value does not exist",
+            ))
+        }
+    };
+
+    Ok(())
+}
+
+/// Evaluates all formulas and stores computed values in the data
+///
+/// Computed values replace existing ones. Formulas of properties without a parent value
+/// (missing object, array item, ...) are not evaluated.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `data` - Configuration data
+pub fn evaluate_formulas(schema: &Schema, data: &mut Value) -> Result<(), Error> {
+    let mut formulas = vec![];
+    collect_formulas(schema, data, &Identifier::default(), &mut formulas)?;
+
+    let engine = Engine::default();
+    let mut context = Context::default();
+
+    for idx in evaluation_order(&formulas)? {
+        let formula = &formulas[idx];
+        let value = engine
+            .eval(formula.expression, &formula.position, data, &mut context)
+            .map_err(|e| {
+                Error::at_path(
+                    position_to_string(&formula.position),
+                    format!("unable to evaluate formula '{}': {}", formula.expression, e),
+                )
+            })?;
+        set_value(data, &formula.position, value)?;
+    }
+
+    Ok(())
+}
+
+// Converts the data path to the canonical identifier
+fn path_to_identifier(path: &PathBuf) -> Identifier {
+    path.components()
+        .iter()
+        .fold(Identifier::default(), |identifier, component| match component {
+            Component::Property(name) => identifier.name(name.as_str()),
+            Component::Index(idx) => identifier.index(*idx as isize),
+        })
+}
+
+// Returns the value the canonical identifier points to
+fn lookup<'a>(data: &'a Value, identifier: &Identifier) -> Option<&'a Value> {
+    identifier
+        .values
+        .iter()
+        .try_fold(data, |value, component| match component {
+            IdentifierValue::Name(name) => value.get(name.as_str()),
+            IdentifierValue::Index(index) => value.get(usize::try_from(*index).ok()?),
+            _ => None,
+        })
+}
+
+/// Evaluates a single expression at the given data position
+pub(crate) fn evaluate_expression(expression: &str, position: &PathBuf, data: &Value) -> Result<Value, Error> {
+    let position = path_to_identifier(position);
+
+    Engine::default()
+        .eval(expression, &position, data, &mut Context::default())
+        .map_err(|e| {
+            Error::at_path(
+                position_to_string(&position),
+                format!("unable to evaluate '{}': {}", expression, e),
+            )
+        })
+}
+
+/// Evaluates the `when` condition of the schema at the given data position
+///
+/// Schema without a condition is always active. Condition referring to a missing value
+/// or which doesn't evaluate to `true` makes the schema inactive. An error is returned
+/// if the condition is not a valid expression or if it can't be evaluated.
+pub(crate) fn is_active(schema: &Schema, position: &PathBuf, data: &Value) -> Result<bool, Error> {
+    let condition = match schema.when() {
+        Some(x) => x,
+        None => return Ok(true),
+    };
+
+    let parsed = condition
+        .parse::<Expression>()
+        .map_err(|e| Error::message(format!("invalid expression '{}': {}", condition, e)))?;
+
+    let identifier = path_to_identifier(position);
+    let dependencies = dependencies::dependencies(&parsed, &identifier)
+        .map_err(|e| Error::at_path(position_to_string(&identifier), e.to_string()))?;
+
+    if dependencies.iter().any(|x| lookup(data, x).is_none()) {
+        return Ok(false);
+    }
+
+    Ok(evaluate_expression(condition, position, data)? == Value::Bool(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn evaluate(schema: &str, mut data: Value) -> Result<Value, Error> {
+        let schema: Schema = schema.parse().unwrap();
+        evaluate_formulas(&schema, &mut data)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn absolute_identifiers() {
+        let schema = r#"
+        properties:
+          - host:
+              type: string
+          - domain:
+              type: string
+          - fqdn:
+              type: hostname
+              formula: host ~ `.` ~ domain
+        "#;
+        let data = evaluate(schema, json!({"host": "device", "domain": "balena.io"})).unwrap();
+        assert_eq!(data["fqdn"], json!("device.balena.io"));
+    }
+
+    #[test]
+    fn overwrite_existing_value() {
+        let schema = r#"
+        properties:
+          - port:
+              type: port
+              formula: 1000 + 80
+        "#;
+        assert_eq!(evaluate(schema, json!({"port": 22})).unwrap(), json!({"port": 1080}));
+    }
+
+    #[test]
+    fn relative_identifiers_in_array_items() {
+        let schema = r#"
+        properties:
+          - networks:
+              type: array
+              items:
+                type: object
+                properties:
+                  - ssid:
+                      type: string
+                  - id:
+                      type: string
+                      formula: super.ssid | SLUGIFY
+                  - name:
+                      type: string
+                      formula: super.id ~ `-wifi`
+        "#;
+        let data = evaluate(schema, json!({"networks": [{"ssid": "My Home"}, {"ssid": "Office"}]})).unwrap();
+        assert_eq!(
+            data,
+            json!({"networks": [
+                {"ssid": "My Home", "id": "my-home", "name": "my-home-wifi"},
+                {"ssid": "Office", "id": "office", "name": "office-wifi"}
+            ]})
+        );
+    }
+
+    #[test]
+    fn evaluate_dependencies_first() {
+        let schema = r#"
+        properties:
+          - c:
+              type: integer
+              formula: b * 2
+          - b:
+              type: integer
+              formula: a + 1
+          - a:
+              type: integer
+        "#;
+        assert_eq!(
+            evaluate(schema, json!({"a": 1})).unwrap(),
+            json!({"a": 1, "b": 2, "c": 4})
+        );
+    }
+
+    #[test]
+    fn skip_formulas_without_parent() {
+        let schema = r#"
+        properties:
+          - network:
+              type: object?
+              properties:
+                - id:
+                    type: string
+                    formula: "`foo`"
+        "#;
+        assert_eq!(evaluate(schema, json!({})).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn fail_on_circular_dependency() {
+        let schema = r#"
+        properties:
+          - a:
+              type: integer
+              formula: b + 1
+          - b:
+              type: integer
+              formula: a + 1
+        "#;
+        let error = evaluate(schema, json!({})).unwrap_err();
+        assert!(error.to_string().contains("circular formula dependency: a -> b -> a"));
+    }
+
+    #[test]
+    fn fail_on_invalid_formula() {
+        let schema = r#"
+        properties:
+          - a:
+              type: integer
+              formula: 1 +
+        "#;
+        assert!(evaluate(schema, json!({})).is_err());
+    }
+}
@@ -1,33 +1,46 @@
 //! A module containing default values filler.
 use serde_json::{json, Value};
 
-use crate::schema::{PrimitiveType, Schema};
+use crate::{
+    evaluator,
+    schema::{PrimitiveType, Schema},
+    validator::path::PathBuf,
+};
 
 // Recursively check if the object is empty
 //
 // If the child object is empty, parent object is considered as empty.
 fn is_empty_object(value: &Value) -> bool {
     match value {
-        Value::Object(map) => map.values().all(|x| is_empty_object(x)),
+        Value::Object(map) => map.values().all(is_empty_object),
         _ => false,
     }
 }
 
-fn fill_object_defaults(schema: &Schema, data: &mut Value, include_optional: bool) {
+fn fill_object_defaults(schema: &Schema, data: &mut Value, path: &PathBuf, root: &Value, include_optional: bool) {
     if data.is_null() {
-        std::mem::replace(data, json!({}));
+        *data = json!({});
     }
 
     if let Some(data) = data.as_object_mut() {
         for property in schema.properties() {
             let name = property.name();
 
-            if let Some(mut value) = data.get_mut(name) {
-                fill_defaults(property.schema(), &mut value, include_optional);
+            let mut property_path = path.clone();
+            property_path.push_property(name);
+
+            // Do not fill inactive properties (`when` condition), conditions which can't be
+            // evaluated are reported by the validator
+            if !evaluator::is_active(property.schema(), &property_path, root).unwrap_or(false) {
+                continue;
+            }
+
+            if let Some(value) = data.get_mut(name) {
+                fill_defaults(property.schema(), value, &property_path, root, include_optional);
             } else {
                 // Fill defaults, but if the resulting object is empty, do not include it
                 let mut value = Value::Null;
-                fill_defaults(property.schema(), &mut value, include_optional);
+                fill_defaults(property.schema(), &mut value, &property_path, root, include_optional);
                 if !value.is_null() && !is_empty_object(&value) {
                     data.insert(name.to_string(), value);
                 }
@@ -36,14 +49,26 @@ fn fill_object_defaults(schema: &Schema, data: &mut Value, include_optional: boo
     }
 }
 
-fn fill_array_defaults(schema: &Schema, data: &mut Value, include_optional: bool) {
-    if data.is_array() && schema.items().len() == 1 {
-        // What we should do in case of multiple schemas? Partial object match?
-        let schema = schema.items().first().unwrap();
+fn fill_array_defaults(schema: &Schema, data: &mut Value, path: &PathBuf, root: &Value, include_optional: bool) {
+    let items = match data.as_array_mut() {
+        Some(x) => x,
+        None => return,
+    };
 
-        for mut item in data.as_array_mut().unwrap() {
-            fill_defaults(&schema, &mut item, include_optional);
-        }
+    for (idx, item) in items.iter_mut().enumerate() {
+        // Multiple items schemas are supported only if the item schema can be selected
+        // by the discriminator
+        let items_schema = match schema.items() {
+            [items_schema] => items_schema,
+            _ => match schema.discriminated_items(item) {
+                Some((_, items_schema)) => items_schema,
+                None => continue,
+            },
+        };
+
+        let mut item_path = path.clone();
+        item_path.push_index(idx);
+        fill_defaults(items_schema, item, &item_path, root, include_optional);
     }
 }
 
@@ -52,15 +77,16 @@ fn fill_primitive_defaults(schema: &Schema, data: &mut Value, include_optional:
 
     if let Some(default_value) = schema.r#default() {
         if data.is_null() && (include_optional || required) {
-            std::mem::replace(data, default_value.clone());
+            *data = default_value.clone();
         }
     }
 }
 
-fn fill_defaults(schema: &Schema, data: &mut Value, include_optional: bool) {
+// `root` is the data snapshot used to evaluate `when` conditions
+fn fill_defaults(schema: &Schema, data: &mut Value, path: &PathBuf, root: &Value, include_optional: bool) {
     match (schema.r#type().primitive_type(), schema.r#type().is_required()) {
-        (PrimitiveType::Object, _) => fill_object_defaults(schema, data, include_optional),
-        (PrimitiveType::Array, _) => fill_array_defaults(schema, data, include_optional),
+        (PrimitiveType::Object, _) => fill_object_defaults(schema, data, path, root, include_optional),
+        (PrimitiveType::Array, _) => fill_array_defaults(schema, data, path, root, include_optional),
         _ => fill_primitive_defaults(schema, data, include_optional),
     };
 }
@@ -72,15 +98,26 @@ fn fill_defaults(schema: &Schema, data: &mut Value, include_optional: bool) {
 /// * `schema` - JellySchema
 /// * `data` - JSON value to start with
 /// * `include_optional` - if `false` only required properties are filled
+///
+/// Properties with the `when` condition are filled only if they're active. Filled
+/// values can activate other properties, defaults are filled until nothing changes.
 pub fn fill_default_values(schema: &Schema, data: &mut Value, include_optional: bool) {
-    fill_defaults(schema, data, include_optional);
+    // Values are only added, never removed, it stops once all active properties are filled
+    loop {
+        let snapshot = data.clone();
+        fill_defaults(schema, data, &PathBuf::new(), &snapshot, include_optional);
+        if *data == snapshot {
+            break;
+        }
+    }
+
     if data.is_null() {
         match schema.r#type().primitive_type() {
             PrimitiveType::Object => {
-                std::mem::replace(data, json!({}));
+                *data = json!({});
             }
             PrimitiveType::Array => {
-                std::mem::replace(data, json!([]));
+                *data = json!([]);
             }
             _ => {}
         }
@@ -214,6 +251,73 @@ mod tests {
         assert_eq!(fill_required(schema, input), result);
     }
 
+    #[test]
+    fn ignore_inactive_properties() {
+        let schema = r##"
+            properties:
+                - enabled:
+                    type: boolean
+                    default: false
+                - server:
+                    type: string
+                    default: proxy.local
+                    when: super.enabled
+        "##;
+        assert_eq!(fill_required(schema, json!({})), json!({"enabled": false}));
+        assert_eq!(
+            fill_required(schema, json!({"enabled": true})),
+            json!({"enabled": true, "server": "proxy.local"})
+        );
+    }
+
+    #[test]
+    fn fill_properties_activated_by_defaults() {
+        let schema = r##"
+            properties:
+                - server:
+                    type: string
+                    default: proxy.local
+                    when: super.enabled
+                - enabled:
+                    type: boolean
+                    default: true
+        "##;
+        assert_eq!(
+            fill_required(schema, json!({})),
+            json!({"enabled": true, "server": "proxy.local"})
+        );
+    }
+
+    #[test]
+    fn fill_discriminated_array_items() {
+        let schema = r##"
+            type: array
+            discriminator: type
+            items:
+                - properties:
+                    - type:
+                        type: string
+                        const: wifi
+                    - hidden:
+                        type: boolean
+                        default: false
+                - properties:
+                    - type:
+                        type: string
+                        const: ethernet
+                    - dhcp:
+                        type: boolean
+                        default: true
+        "##;
+        let input = json!([{"type": "ethernet"}, {"type": "wifi"}, {"type": "unknown"}]);
+        let result = json!([
+            {"type": "ethernet", "dhcp": true},
+            {"type": "wifi", "hidden": false},
+            {"type": "unknown"}
+        ]);
+        assert_eq!(fill_required(schema, input), result);
+    }
+
     #[test]
     fn object_emptiness() {
         assert!(!is_empty_object(&json!("foo")));
@@ -1,23 +1,142 @@
 //! A module containing output generator for the JSON Schema & UI Object
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 
 use crate::{
+    evaluator,
     generator::serialization::{JsonSchema, UiSchema},
-    schema::Schema,
+    locale::DEFAULT_LOCALE,
+    schema::{PrimitiveType, Schema},
+    validator::path::PathBuf,
 };
 
 mod serialization;
 
-fn generate_json_schema(schema: &Schema) -> Value {
-    let json_schema: JsonSchema = JsonSchema::with_default_schema_url(schema);
+fn generate_json_schema(schema: &Schema, locale: &str) -> Value {
+    let json_schema: JsonSchema = JsonSchema::with_default_schema_url(schema, locale);
     serde_json::to_value(json_schema).expect("Internal error: inconsistent schema: json schema")
 }
 
-fn generate_ui_schema(schema: &Schema) -> Value {
-    let ui_schema: UiSchema = UiSchema::new(schema);
+fn generate_ui_schema(schema: &Schema, locale: &str) -> Value {
+    let ui_schema: UiSchema = UiSchema::new(schema, locale);
     serde_json::to_value(ui_schema).expect("Internal error: inconsistent schema: ui schema")
 }
 
-pub fn generate_json_ui_schema(schema: &Schema) -> (Value, Value) {
-    (generate_json_schema(schema), generate_ui_schema(schema))
+/// Generates JSON Schema & UI Schema
+///
+/// Localized annotations (`title`, `description`, `help`, ...) are emitted in the given
+/// locale, see [`Annotation::get`](../schema/struct.Annotation.html#method.get).
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `locale` - Annotations locale (`de`, `pt-BR`, ...), English if `None`
+pub fn generate_json_ui_schema(schema: &Schema, locale: Option<&str>) -> (Value, Value) {
+    let locale = locale.unwrap_or(DEFAULT_LOCALE);
+    (generate_json_schema(schema, locale), generate_ui_schema(schema, locale))
+}
+
+// Hides properties which are not active (`when` condition) for the given data
+fn hide_inactive_properties(schema: &Schema, ui_schema: &mut Map<String, Value>, path: &PathBuf, data: &Value) {
+    for property in schema.properties() {
+        let mut property_path = path.clone();
+        property_path.push_property(property.name());
+
+        let mut property_ui_schema = match ui_schema.remove(property.name()) {
+            Some(Value::Object(x)) => x,
+            _ => Map::new(),
+        };
+
+        // Conditions which can't be evaluated are reported by the validator
+        if evaluator::is_active(property.schema(), &property_path, data).unwrap_or(false) {
+            if let PrimitiveType::Object = property.schema().r#type().primitive_type() {
+                hide_inactive_properties(property.schema(), &mut property_ui_schema, &property_path, data);
+            }
+        } else {
+            property_ui_schema.insert("ui:widget".to_string(), json!("hidden"));
+        }
+
+        if !property_ui_schema.is_empty() {
+            ui_schema.insert(property.name().to_string(), Value::Object(property_ui_schema));
+        }
+    }
+}
+
+/// Generates JSON Schema & UI Schema with inactive properties hidden
+///
+/// Same as [`generate_json_ui_schema`], but properties with the `when` condition which
+/// are not active for the given data have the hidden widget.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `data` - Configuration data
+/// * `locale` - Annotations locale (`de`, `pt-BR`, ...), English if `None`
+pub fn generate_json_ui_schema_with_data(schema: &Schema, data: &Value, locale: Option<&str>) -> (Value, Value) {
+    let locale = locale.unwrap_or(DEFAULT_LOCALE);
+    let mut ui_schema = generate_ui_schema(schema, locale);
+
+    if let Some(map) = ui_schema.as_object_mut() {
+        hide_inactive_properties(schema, map, &PathBuf::new(), data);
+    }
+
+    (generate_json_schema(schema, locale), ui_schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hide_inactive_properties_for_data() {
+        let schema: Schema = r#"
+            properties:
+              - proxy:
+                  properties:
+                    - enabled:
+                        type: boolean
+                    - server:
+                        type: hostname
+                        placeholder: proxy.local
+                        when: super.enabled
+        "#
+        .parse()
+        .unwrap();
+
+        let (_, ui_schema) = generate_json_ui_schema_with_data(&schema, &json!({"proxy": {"enabled": false}}), None);
+        assert_eq!(
+            ui_schema["proxy"]["server"],
+            json!({"ui:placeholder": "proxy.local", "ui:widget": "hidden"})
+        );
+
+        let (_, ui_schema) = generate_json_ui_schema_with_data(&schema, &json!({"proxy": {"enabled": true}}), None);
+        assert_eq!(ui_schema["proxy"]["server"], json!({"ui:placeholder": "proxy.local"}));
+    }
+
+    #[test]
+    fn localized_annotations() {
+        let schema: Schema = include_str!("../../tests/generator/valid/annotations/localized/input-schema.yaml")
+            .parse()
+            .unwrap();
+
+        let (json_schema, ui_schema) = generate_json_ui_schema(&schema, Some("de-AT"));
+        assert_eq!(json_schema["title"], json!("Netzwerk"));
+        assert_eq!(json_schema["description"], json!("Netzwerkkonfiguration"));
+        assert_eq!(json_schema["properties"]["ssid"]["title"], json!("Netzwerk-SSID"));
+        assert_eq!(
+            json_schema["properties"]["mode"]["oneOf"][0],
+            json!({"title": "Kabelgebunden", "enum": ["ethernet"]})
+        );
+        assert_eq!(json_schema["properties"]["mode"]["title"], json!("Mode"));
+        assert_eq!(
+            ui_schema["ssid"],
+            json!({
+                "ui:help": "Name des drahtlosen Netzwerks",
+                "ui:warning": "Groß- und Kleinschreibung beachten",
+                "ui:placeholder": "Mein Netzwerk"
+            })
+        );
+
+        let (_, ui_schema) = generate_json_ui_schema_with_data(&schema, &json!({}), Some("fr"));
+        assert_eq!(ui_schema["ssid"]["ui:help"], json!("Name of the wireless network"));
+    }
 }
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::string::ToString;
 
+use balena_temen::ast::{Expression, ExpressionValue, IdentifierValue, LogicalOperator};
 use serde::ser::{Error, SerializeMap};
 use serde::{Serialize, Serializer};
 use serde_json::{json, Value};
@@ -15,6 +16,7 @@ const SCHEMA_URL: &str = "http://json-schema.org/draft-04/schema#";
 pub struct JsonSchema<'a> {
     schema_url: Option<&'static str>,
     schema: &'a Schema,
+    locale: &'a str,
 }
 
 impl<'a> Serialize for JsonSchema<'a> {
@@ -28,27 +30,83 @@ impl<'a> Serialize for JsonSchema<'a> {
             map.serialize_entry("$schema", url)?;
         }
 
-        serialize_as_json_schema(&self.schema, &mut map)?;
+        serialize_as_json_schema(self.schema, self.locale, &mut map)?;
         map.end()
     }
 }
 
 impl<'a> JsonSchema<'a> {
-    pub fn new(schema: &'a Schema) -> Self {
+    pub fn new(schema: &'a Schema, locale: &'a str) -> Self {
         JsonSchema {
             schema,
             schema_url: None,
+            locale,
         }
     }
 
-    pub fn with_default_schema_url(schema: &'a Schema) -> Self {
+    pub fn with_default_schema_url(schema: &'a Schema, locale: &'a str) -> Self {
         JsonSchema {
             schema,
             schema_url: Some(SCHEMA_URL),
+            locale,
         }
     }
 }
 
+// Checks if the schema should be serialized as the `$ref` (reference without overrides)
+fn is_bare_reference(schema: &Schema) -> bool {
+    schema.r#ref().is_some() && !schema.overrides_reference()
+}
+
+// Collects names of definitions referenced via `$ref` in the serialized schema
+fn collect_references<'a>(schema: &'a Schema, definitions: &'a BTreeMap<String, Schema>, names: &mut Vec<&'a str>) {
+    if is_bare_reference(schema) {
+        let name = schema
+            .r#ref()
+            .and_then(|x| x.rsplit('/').next())
+            .and_then(|x| definitions.get_key_value(x));
+
+        if let Some((name, definition)) = name {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+                collect_references(definition, definitions, names);
+            }
+        }
+        return;
+    }
+
+    for property in schema.properties() {
+        collect_references(property.schema(), definitions, names);
+    }
+
+    for items in schema.items() {
+        collect_references(items, definitions, names);
+    }
+
+    if let Some(values) = schema.values() {
+        collect_references(values, definitions, names);
+    }
+}
+
+fn serialize_definitions<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
+where
+    E: Error,
+    S: SerializeMap<Ok = O, Error = E>,
+{
+    let mut names = vec![];
+    collect_references(schema, schema.definitions(), &mut names);
+
+    if !names.is_empty() {
+        let definitions: BTreeMap<&str, JsonSchema> = names
+            .into_iter()
+            .map(|name| (name, JsonSchema::new(&schema.definitions()[name], locale)))
+            .collect();
+        map.serialize_entry("definitions", &definitions)?;
+    }
+
+    Ok(())
+}
+
 fn serialize_type<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
 where
     E: Error,
@@ -99,23 +157,23 @@ where
     Ok(())
 }
 
-fn serialize_annotations<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+fn serialize_annotations<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
 where
     E: Error,
     S: SerializeMap<Ok = O, Error = E>,
 {
-    if let Some(title) = schema.title() {
+    if let Some(title) = schema.title_in(locale) {
         map.serialize_entry("title", title)?;
     }
 
-    if let Some(description) = schema.description() {
+    if let Some(description) = schema.description_in(locale) {
         map.serialize_entry("description", description)?;
     }
 
     Ok(())
 }
 
-fn serialize_array_keywords<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+fn serialize_array_keywords<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
 where
     E: Error,
     S: SerializeMap<Ok = O, Error = E>,
@@ -137,17 +195,152 @@ where
     let items_count = schema.items().len();
     match items_count {
         0 => {}
-        1 => map.serialize_entry("items", &JsonSchema::new(schema.items().first().unwrap()))?,
+        1 => map.serialize_entry("items", &JsonSchema::new(schema.items().first().unwrap(), locale))?,
         _ => {
-            let json_schemas: Vec<JsonSchema> = schema.items().iter().map(JsonSchema::new).collect();
+            let json_schemas: Vec<JsonSchema> = schema.items().iter().map(|x| JsonSchema::new(x, locale)).collect();
             map.serialize_entry("items", &json!({ "oneOf": json_schemas }))?;
         }
     };
 
+    if let Some(discriminator) = schema.discriminator() {
+        map.serialize_entry("$$discriminator", discriminator)?;
+    }
+
+    Ok(())
+}
+
+// Sibling property comparison (`super.name == value`), `true` if equal is expected
+struct Condition {
+    property: String,
+    value: Value,
+    equal: bool,
+}
+
+fn sibling_name(expression: &Expression) -> Option<&str> {
+    match &expression.value {
+        ExpressionValue::Identifier(identifier) if expression.filters.is_empty() => {
+            match identifier.values.as_slice() {
+                [IdentifierValue::Super, IdentifierValue::Name(name)] => Some(name),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn literal(expression: &Expression) -> Option<Value> {
+    if expression.negated || !expression.filters.is_empty() {
+        return None;
+    }
+
+    match &expression.value {
+        ExpressionValue::Boolean(x) => Some(json!(x)),
+        ExpressionValue::Integer(x) => Some(json!(x)),
+        ExpressionValue::Float(x) => Some(json!(x)),
+        ExpressionValue::String(x) => Some(json!(x)),
+        _ => None,
+    }
+}
+
+// Parses the `when` condition if it can be expressed in the JSON Schema
+//
+// Supported conditions are `super.name`, `!super.name`, `super.name == value` and
+// `super.name != value` where value is a literal.
+fn parse_condition(expression: &Expression) -> Option<Condition> {
+    if let Some(property) = sibling_name(expression) {
+        return Some(Condition {
+            property: property.to_string(),
+            value: Value::Bool(true),
+            equal: !expression.negated,
+        });
+    }
+
+    let logical = match &expression.value {
+        ExpressionValue::Logical(x) if !expression.negated && expression.filters.is_empty() => x,
+        _ => return None,
+    };
+
+    let equal = match logical.operator {
+        LogicalOperator::Equal => true,
+        LogicalOperator::NotEqual => false,
+        _ => return None,
+    };
+
+    let (property, value) = match (sibling_name(&logical.lhs), sibling_name(&logical.rhs)) {
+        (Some(property), None) if !logical.lhs.negated => (property, literal(&logical.rhs)?),
+        (None, Some(property)) if !logical.rhs.negated => (property, literal(&logical.lhs)?),
+        _ => return None,
+    };
+
+    Some(Condition {
+        property: property.to_string(),
+        value,
+        equal,
+    })
+}
+
+// Conditional properties are not listed in the `required` keyword, required ones
+// are required via the `dependencies` keyword if the condition can be expressed
+fn serialize_conditions<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+where
+    E: Error,
+    S: SerializeMap<Ok = O, Error = E>,
+{
+    let mut dependencies = BTreeMap::<String, Vec<Value>>::new();
+
+    for property in schema.properties() {
+        let expression = match property.schema().when().map(str::parse::<Expression>) {
+            Some(Ok(x)) if property.schema().r#type().is_required() => x,
+            _ => continue,
+        };
+
+        let condition = match parse_condition(&expression) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        // Value of the property the condition depends on is either matching
+        // and the conditional property is required or it is not matching
+        let matching = json!({ "enum": [condition.value] });
+        let not_matching = json!({ "not": matching });
+        let (active, inactive) = if condition.equal {
+            (matching, not_matching)
+        } else {
+            (not_matching, matching)
+        };
+
+        dependencies.entry(condition.property.clone()).or_default().push(json!({
+            "oneOf": [
+                {
+                    "properties": { &condition.property: active },
+                    "required": [property.name()]
+                },
+                {
+                    "properties": { &condition.property: inactive }
+                }
+            ]
+        }));
+    }
+
+    if !dependencies.is_empty() {
+        let dependencies: BTreeMap<String, Value> = dependencies
+            .into_iter()
+            .map(|(name, mut schemas)| {
+                let schema = if schemas.len() == 1 {
+                    schemas.remove(0)
+                } else {
+                    json!({ "allOf": schemas })
+                };
+                (name, schema)
+            })
+            .collect();
+        map.serialize_entry("dependencies", &dependencies)?;
+    }
+
     Ok(())
 }
 
-fn serialize_object_keywords<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+fn serialize_object_keywords<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
 where
     E: Error,
     S: SerializeMap<Ok = O, Error = E>,
@@ -163,12 +356,12 @@ where
         let mut properties = HashMap::<&str, JsonSchema>::new();
 
         for property in schema.properties() {
-            if property.schema().r#type().is_required() {
+            if property.schema().r#type().is_required() && property.schema().when().is_none() {
                 required.push(property.name());
             }
             order.push(property.name());
 
-            properties.insert(property.name(), JsonSchema::new(property.schema()));
+            properties.insert(property.name(), JsonSchema::new(property.schema(), locale));
         }
 
         if !required.is_empty() {
@@ -182,12 +375,14 @@ where
         if !properties.is_empty() {
             map.serialize_entry("properties", &properties)?;
         }
+
+        serialize_conditions(schema, map)?;
     }
 
     match (schema.keys(), schema.values()) {
         (Some(keys), Some(values)) if keys.pattern().is_some() => map.serialize_entry(
             "patternProperties",
-            &json!({ keys.pattern().unwrap().to_string(): JsonSchema::new(values) }),
+            &json!({ keys.pattern().unwrap().to_string(): JsonSchema::new(values, locale) }),
         )?,
         _ => {}
     };
@@ -249,14 +444,21 @@ where
     Ok(())
 }
 
-fn serialize_as_json_schema<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+fn serialize_as_json_schema<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
 where
     E: Error,
     S: SerializeMap<Ok = O, Error = E>,
 {
-    serialize_annotations(schema, map)?;
-    serialize_array_keywords(schema, map)?;
-    serialize_object_keywords(schema, map)?;
+    // Definitions are serialized only if they're referenced, overridden references are inlined
+    if is_bare_reference(schema) {
+        map.serialize_entry("$ref", &schema.r#ref())?;
+        return Ok(());
+    }
+
+    serialize_definitions(schema, locale, map)?;
+    serialize_annotations(schema, locale, map)?;
+    serialize_array_keywords(schema, locale, map)?;
+    serialize_object_keywords(schema, locale, map)?;
     serialize_number_keywords(schema, map)?;
     serialize_string_keywords(schema, map)?;
 
@@ -278,6 +480,10 @@ where
         map.serialize_entry("$$formula", formula)?;
     }
 
+    if let Some(when) = schema.when() {
+        map.serialize_entry("$$when", when)?;
+    }
+
     if schema.read_only() {
         map.serialize_entry("readOnly", &true)?;
     }
@@ -289,7 +495,7 @@ where
     let values: Vec<Value> = schema
         .r#enum()
         .iter()
-        .map(|x| json!({ "title": x.title(), "enum": [ x.value() ]}))
+        .map(|x| json!({ "title": x.title_in(locale), "enum": [ x.value() ]}))
         .collect();
     if !values.is_empty() {
         map.serialize_entry("oneOf", &values)?;
@@ -8,6 +8,7 @@ use crate::schema::{PrimitiveType, Schema};
 
 pub struct UiSchema<'a> {
     schema: &'a Schema,
+    locale: &'a str,
 }
 
 impl<'a> Serialize for UiSchema<'a> {
@@ -16,14 +17,14 @@ impl<'a> Serialize for UiSchema<'a> {
         S: Serializer,
     {
         let mut map = serializer.serialize_map(None)?;
-        serialize_as_ui_schema(&self.schema, &mut map)?;
+        serialize_as_ui_schema(self.schema, self.locale, &mut map)?;
         map.end()
     }
 }
 
 impl<'a> UiSchema<'a> {
-    pub fn new(schema: &'a Schema) -> Self {
-        UiSchema { schema }
+    pub fn new(schema: &'a Schema, locale: &'a str) -> Self {
+        UiSchema { schema, locale }
     }
 }
 
@@ -65,16 +66,16 @@ fn serialize_ui_options(schema: &Schema, map: &mut Map<String, Value>) {
     }
 }
 
-fn serialize_annotations(schema: &Schema, map: &mut Map<String, Value>) {
-    if let Some(help) = schema.help() {
+fn serialize_annotations(schema: &Schema, locale: &str, map: &mut Map<String, Value>) {
+    if let Some(help) = schema.help_in(locale) {
         map.insert("ui:help".to_string(), Value::String(help.to_string()));
     }
 
-    if let Some(warning) = schema.warning() {
+    if let Some(warning) = schema.warning_in(locale) {
         map.insert("ui:warning".to_string(), Value::String(warning.to_string()));
     }
 
-    if let Some(placeholder) = schema.placeholder() {
+    if let Some(placeholder) = schema.placeholder_in(locale) {
         map.insert("ui:placeholder".to_string(), Value::String(placeholder.to_string()));
     }
 
@@ -87,7 +88,7 @@ fn serialize_annotations(schema: &Schema, map: &mut Map<String, Value>) {
     }
 }
 
-fn serialize_properties(schema: &Schema, map: &mut Map<String, Value>) {
+fn serialize_properties(schema: &Schema, locale: &str, map: &mut Map<String, Value>) {
     let mut order = vec![];
     let mut properties = Map::<String, Value>::new();
 
@@ -95,7 +96,7 @@ fn serialize_properties(schema: &Schema, map: &mut Map<String, Value>) {
         order.push(property.name());
 
         let mut property_map = Map::<String, Value>::new();
-        serialize_ui_schema_into_map(property.schema(), &mut property_map);
+        serialize_ui_schema_into_map(property.schema(), locale, &mut property_map);
 
         if !property_map.is_empty() {
             properties.insert(property.name().to_string(), Value::Object(property_map));
@@ -104,7 +105,7 @@ fn serialize_properties(schema: &Schema, map: &mut Map<String, Value>) {
 
     map.extend(properties);
 
-    if let Some(title) = schema.keys().and_then(crate::schema::Schema::title) {
+    if let Some(title) = schema.keys().and_then(|x| x.title_in(locale)) {
         map.insert("ui:keys".to_string(), json!({ "ui:title": title }));
     }
 
@@ -113,43 +114,82 @@ fn serialize_properties(schema: &Schema, map: &mut Map<String, Value>) {
     }
 }
 
-fn serialize_array_items(schema: &Schema, map: &mut Map<String, Value>) {
+// UI schemas of the discriminated items are keyed by the discriminator value, there's
+// no way to express multiple items UI schemas without it
+fn serialize_discriminated_items(schema: &Schema, locale: &str, map: &mut Map<String, Value>) {
+    let discriminator = match schema.discriminator() {
+        Some(x) => x,
+        None => return,
+    };
+
+    let mut variants: Map<String, Value> = Map::new();
+
+    for items in schema.items() {
+        let value = items
+            .properties()
+            .iter()
+            .find(|x| x.name() == discriminator)
+            .and_then(|x| x.schema().r#const());
+
+        let key = match value {
+            Some(Value::String(x)) => x.clone(),
+            Some(x) => x.to_string(),
+            None => continue,
+        };
+
+        let mut result: Map<String, Value> = Map::new();
+        serialize_ui_schema_into_map(items, locale, &mut result);
+
+        if !result.is_empty() {
+            variants.insert(key, Value::Object(result));
+        }
+    }
+
+    let mut result: Map<String, Value> = Map::new();
+    result.insert("ui:discriminator".to_string(), json!(discriminator));
+    if !variants.is_empty() {
+        result.insert("ui:variants".to_string(), Value::Object(variants));
+    }
+    map.insert("items".to_string(), Value::Object(result));
+}
+
+fn serialize_array_items(schema: &Schema, locale: &str, map: &mut Map<String, Value>) {
     if schema.items().is_empty() {
         return;
     }
 
     if schema.items().len() > 1 {
-        // FIXME How it should look like?
+        serialize_discriminated_items(schema, locale, map);
         return;
     }
 
     let mut result: Map<String, Value> = Map::new();
-    serialize_ui_schema_into_map(schema.items().first().unwrap(), &mut result);
+    serialize_ui_schema_into_map(schema.items().first().unwrap(), locale, &mut result);
 
     if !result.is_empty() {
         map.insert("items".to_string(), json!(result));
     }
 }
 
-fn serialize_ui_schema_into_map(schema: &Schema, map: &mut Map<String, Value>) {
-    serialize_annotations(schema, map);
-    serialize_properties(schema, map);
+fn serialize_ui_schema_into_map(schema: &Schema, locale: &str, map: &mut Map<String, Value>) {
+    serialize_annotations(schema, locale, map);
+    serialize_properties(schema, locale, map);
     serialize_widget(schema, map);
     serialize_ui_options(schema, map);
-    serialize_array_items(schema, map);
+    serialize_array_items(schema, locale, map);
 
     if schema.read_only() {
         map.insert("ui:readonly".to_string(), json!(true));
     }
 }
 
-fn serialize_as_ui_schema<O, E, S>(schema: &Schema, map: &mut S) -> Result<(), E>
+fn serialize_as_ui_schema<O, E, S>(schema: &Schema, locale: &str, map: &mut S) -> Result<(), E>
 where
     E: Error,
     S: SerializeMap<Ok = O, Error = E>,
 {
     let mut result: Map<String, Value> = Map::new();
-    serialize_ui_schema_into_map(schema, &mut result);
+    serialize_ui_schema_into_map(schema, locale, &mut result);
 
     for (k, v) in result.iter() {
         map.serialize_entry(k, v)?;
@@ -4,6 +4,11 @@
 //!
 //! * transform configuration DSL into the JSON Schema & UI Object Schema with custom extensions
 //! * parse configuration DSL
+//! * check configuration DSL consistency
+//! * evaluate formulas
+//! * render configuration data into target files & read them back
+//! * migrate configuration data between schema revisions
+//! * localize validation messages & schema annotations
 //!
 //! # Versioning
 //!
@@ -20,7 +25,6 @@
 //! ```
 //! use jellyschema::generator::generate_json_ui_schema;
 //! use jellyschema::schema::Schema;
-//! use serde_yaml;
 //!
 //! let dsl = r#"
 //!   version: 1
@@ -30,16 +34,20 @@
 //!         help: You should type your name here
 //! "#;
 //!
-//! let input_schema: Schema = serde_yaml::from_str(dsl).unwrap();
+//! let input_schema: Schema = dsl.parse().unwrap();
 //!
-//! let (json_schema, ui_object) = generate_json_ui_schema(&input_schema);
+//! let (json_schema, ui_object) = generate_json_ui_schema(&input_schema, None);
 //! ```
 //!
 //! [balena]: https://www.balena.io
 //! [Semantic Versioning]: https://semver.org/
-pub(crate) mod deref;
 pub mod error;
+pub mod evaluator;
 pub mod filler;
+pub mod lint;
+pub mod locale;
+pub mod mapping;
+pub mod migration;
 pub mod schema;
 pub mod validator;
 
@@ -0,0 +1,52 @@
+use serde_derive::Serialize;
+use std::fmt;
+
+/// Schema consistency issue
+#[derive(Debug, Serialize)]
+pub struct LintError {
+    keyword: String,
+    #[serde(rename = "schemaPath")]
+    schema_path: String,
+    message: String,
+}
+
+impl LintError {
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl LintError {
+    pub fn new<S1, S2, S3>(keyword: S1, schema_path: S2, message: S3) -> LintError
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        LintError {
+            keyword: keyword.into(),
+            schema_path: schema_path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "schema path: '{}', keyword: '{}', message: '{}'",
+            self.schema_path, self.keyword, self.message
+        )
+    }
+}
+
+impl std::error::Error for LintError {}
@@ -0,0 +1,566 @@
+//! A module containing schema linter
+//!
+//! Deserialization catches syntax errors only. Linter walks the whole schema and
+//! checks its semantic consistency - values of the `default`, `const` & `enum`
+//! keywords must be valid, keywords must be applicable to the property type,
+//! ranges must not be empty, etc.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::lint::lint;
+//! use jellyschema::schema::Schema;
+//!
+//! let schema: Schema = r#"
+//!   properties:
+//!     - port:
+//!         type: port
+//!         default: 80000
+//! "#.parse().unwrap();
+//!
+//! let errors = lint(&schema);
+//!
+//! assert_eq!(errors.len(), 1);
+//! assert_eq!(errors[0].schema_path(), "properties[0].port.default");
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use balena_temen::ast::{Identifier, IdentifierValue};
+use serde_json::{Number, Value};
+
+use crate::{
+    schema::{
+        mapping::{Target, TargetFormat},
+        PrimitiveType, Schema, UniqueItems,
+    },
+    validator::{path::PathBuf, validate},
+};
+
+pub use self::error::LintError;
+
+mod error;
+
+struct Linter {
+    errors: Vec<LintError>,
+}
+
+/// Mapping targets visible to the schema
+#[derive(Clone, Default)]
+struct Targets {
+    // Declared targets formats
+    declared: HashMap<String, TargetFormat>,
+    // Format of the target selected by the schema or inherited from its parents
+    selected: Option<TargetFormat>,
+}
+
+fn is_string_based(primitive_type: &PrimitiveType) -> bool {
+    !matches!(
+        primitive_type,
+        PrimitiveType::Object
+            | PrimitiveType::Boolean
+            | PrimitiveType::Integer
+            | PrimitiveType::Number
+            | PrimitiveType::Port
+            | PrimitiveType::Array
+            | PrimitiveType::StringList
+            | PrimitiveType::File
+    )
+}
+
+fn is_array_based(primitive_type: &PrimitiveType) -> bool {
+    matches!(primitive_type, PrimitiveType::Array | PrimitiveType::StringList)
+}
+
+fn is_greater(lhs: &Number, rhs: &Number) -> bool {
+    match (lhs.as_f64(), rhs.as_f64()) {
+        (Some(lhs), Some(rhs)) => lhs > rhs,
+        _ => false,
+    }
+}
+
+// Checks if the dotted path (`wifi.ssid`) names an existing property
+fn path_exists(schema: &Schema, path: &[IdentifierValue]) -> bool {
+    let (first, remaining) = match path.split_first() {
+        Some(x) => x,
+        None => return true,
+    };
+
+    match first {
+        IdentifierValue::Name(name) => schema
+            .properties()
+            .iter()
+            .filter(|property| property.name() == name)
+            .any(|property| path_exists(property.schema(), remaining)),
+        IdentifierValue::Index(_) => schema.items().iter().any(|items| path_exists(items, remaining)),
+        _ => false,
+    }
+}
+
+impl Linter {
+    fn error<S1, S2>(&mut self, path: &PathBuf, keyword: S1, message: S2)
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let keyword = keyword.into();
+        let mut schema_path = path.clone();
+        schema_path.push_property(keyword.clone());
+        self.errors
+            .push(LintError::new(keyword, schema_path.to_string(), message));
+    }
+
+    fn lint_value(&mut self, schema: &Schema, path: &PathBuf, keyword: &str, value: &Value) {
+        let state = validate(schema, value);
+
+        if let Some(error) = state.errors().first() {
+            self.error(
+                path,
+                keyword,
+                format!("value {} is not valid: {}", value, error.message()),
+            );
+        }
+    }
+
+    fn lint_values(&mut self, schema: &Schema, path: &PathBuf) {
+        if let Some(value) = schema.r#default() {
+            self.lint_value(schema, path, "default", value);
+        }
+
+        if let Some(value) = schema.r#const() {
+            self.lint_value(schema, path, "const", value);
+        }
+
+        for entry in schema.r#enum() {
+            self.lint_value(schema, path, "enum", entry.value());
+        }
+    }
+
+    fn lint_keywords(&mut self, schema: &Schema, path: &PathBuf) {
+        let primitive_type = schema.r#type().primitive_type();
+
+        if let (Some(min), Some(max)) = (schema.min(), schema.max()) {
+            if is_greater(min, max) {
+                self.error(path, "min", format!("min ({}) is greater than max ({})", min, max));
+            }
+        }
+
+        if let (Some(min), Some(max)) = (schema.min_length(), schema.max_length()) {
+            if min > max {
+                self.error(
+                    path,
+                    "minLength",
+                    format!("minLength ({}) is greater than maxLength ({})", min, max),
+                );
+            }
+        }
+
+        if let (Some(min), Some(max)) = (schema.min_items(), schema.max_items()) {
+            if min > max {
+                self.error(
+                    path,
+                    "minItems",
+                    format!("minItems ({}) is greater than maxItems ({})", min, max),
+                );
+            }
+        }
+
+        if schema.pattern().is_some() && !is_string_based(primitive_type) {
+            self.error(
+                path,
+                "pattern",
+                format!("not applicable to the '{}' type", primitive_type.as_ref()),
+            );
+        }
+
+        if !schema.items().is_empty() && !is_array_based(primitive_type) {
+            self.error(
+                path,
+                "items",
+                format!("not applicable to the '{}' type", primitive_type.as_ref()),
+            );
+        }
+    }
+
+    fn lint_properties(&mut self, schema: &Schema, path: &PathBuf) {
+        let mut names = HashSet::new();
+
+        for (idx, property) in schema.properties().iter().enumerate() {
+            if !names.insert(property.name()) {
+                let mut path = path.clone();
+                path.push_property("properties");
+                path.push_index(idx);
+                self.error(
+                    &path,
+                    property.name(),
+                    format!("duplicate property name '{}'", property.name()),
+                );
+            }
+        }
+    }
+
+    fn lint_unique_items(&mut self, schema: &Schema, path: &PathBuf) {
+        if schema.unique_items() == &UniqueItems::Boolean(false) {
+            return;
+        }
+
+        let primitive_type = schema.r#type().primitive_type();
+
+        if !is_array_based(primitive_type) {
+            self.error(
+                path,
+                "uniqueItems",
+                format!("not applicable to the '{}' type", primitive_type.as_ref()),
+            );
+            return;
+        }
+
+        let paths = match schema.unique_items().paths() {
+            Some(x) => x,
+            None => return,
+        };
+
+        for unique_path in paths {
+            let exists = match unique_path.parse::<Identifier>() {
+                Ok(identifier) => schema
+                    .items()
+                    .iter()
+                    .any(|items| path_exists(items, &identifier.values)),
+                Err(_) => false,
+            };
+
+            if !exists {
+                self.error(
+                    path,
+                    "uniqueItems",
+                    format!("path '{}' does not name an existing property", unique_path),
+                );
+            }
+        }
+    }
+
+    fn lint_mapping(&mut self, schema: &Schema, path: &PathBuf, targets: &Targets) -> Targets {
+        let mapping = match schema.mapping() {
+            Some(x) => x,
+            None => return targets.clone(),
+        };
+
+        let mut targets = targets.clone();
+        targets.declared.extend(
+            mapping
+                .targets()
+                .iter()
+                .map(|(name, target)| (name.clone(), *target.format())),
+        );
+
+        let mut mapping_path = path.clone();
+        mapping_path.push_property("mapping");
+
+        match mapping.target() {
+            Some(Target::Reference(name)) => match targets.declared.get(name) {
+                Some(format) => targets.selected = Some(*format),
+                None => {
+                    self.error(&mapping_path, "target", format!("undeclared target '{}'", name));
+                    targets.selected = None;
+                }
+            },
+            Some(Target::Raw(target)) => targets.selected = Some(*target.format()),
+            None => {}
+        };
+
+        // The whole value is the binary target document, it must be a file
+        let primitive_type = schema.r#type().primitive_type();
+        if (mapping.target().is_some() || mapping.path().is_some())
+            && targets.selected.is_some_and(TargetFormat::is_binary)
+            && primitive_type != &PrimitiveType::File
+        {
+            let keyword = if mapping.target().is_some() { "target" } else { "path" };
+            self.error(
+                &mapping_path,
+                keyword,
+                format!("binary target requires a 'file' property, got '{}'", primitive_type),
+            );
+        }
+
+        targets
+    }
+
+    fn lint_schema(&mut self, schema: &Schema, path: &PathBuf, targets: &Targets) {
+        self.lint_values(schema, path);
+        self.lint_keywords(schema, path);
+        self.lint_properties(schema, path);
+        self.lint_unique_items(schema, path);
+        let targets = self.lint_mapping(schema, path, targets);
+
+        for (idx, property) in schema.properties().iter().enumerate() {
+            let mut nested_path = path.clone();
+            nested_path.push_property("properties");
+            nested_path.push_index(idx);
+            nested_path.push_property(property.name());
+            self.lint_schema(property.schema(), &nested_path, &targets);
+        }
+
+        for (idx, items) in schema.items().iter().enumerate() {
+            let mut nested_path = path.clone();
+            nested_path.push_property("items");
+            nested_path.push_index(idx);
+            self.lint_schema(items, &nested_path, &targets);
+        }
+
+        for (keyword, nested) in [("keys", schema.keys()), ("values", schema.values())].iter() {
+            if let Some(nested) = nested {
+                let mut nested_path = path.clone();
+                nested_path.push_property(*keyword);
+                self.lint_schema(nested, &nested_path, &targets);
+            }
+        }
+    }
+}
+
+/// Checks the schema semantic consistency
+///
+/// Returns an empty vector if no issue was found.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+pub fn lint(schema: &Schema) -> Vec<LintError> {
+    let mut linter = Linter { errors: vec![] };
+    linter.lint_schema(schema, &PathBuf::new(), &Targets::default());
+    linter.errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_str(schema: &str) -> Vec<(String, String)> {
+        let schema: Schema = schema.parse().unwrap();
+        lint(&schema)
+            .into_iter()
+            .map(|e| (e.schema_path().to_string(), e.keyword().to_string()))
+            .collect()
+    }
+
+    fn paths(errors: &[(&str, &str)]) -> Vec<(String, String)> {
+        errors
+            .iter()
+            .map(|(path, keyword)| (path.to_string(), keyword.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_schema() {
+        let schema = r#"
+        mapping:
+          targets:
+            config-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /config.json
+        properties:
+          - networks:
+              type: array
+              uniqueItems:
+                - ssid
+              items:
+                properties:
+                  - ssid:
+                      type: string
+                      minLength: 1
+                      maxLength: 32
+              mapping:
+                target: config-json
+                path: networks
+          - port:
+              type: port
+              default: 80
+              enum:
+                - 80
+                - 8080
+        "#;
+        assert!(lint_str(schema).is_empty());
+    }
+
+    #[test]
+    fn invalid_values() {
+        let schema = r#"
+        properties:
+          - port:
+              type: port
+              default: foo
+          - name:
+              type: string
+              const: 10
+          - mode:
+              type: string
+              enum:
+                - foo
+                - 10
+        "#;
+        assert_eq!(
+            lint_str(schema),
+            paths(&[
+                ("properties[0].port.default", "default"),
+                ("properties[1].name.const", "const"),
+                ("properties[2].mode.enum", "enum"),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_ranges() {
+        let schema = r#"
+        properties:
+          - count:
+              type: integer
+              min: 10
+              max: 5
+          - name:
+              type: string
+              minLength: 10
+              maxLength: 5
+          - list:
+              type: array
+              minItems: 10
+              maxItems: 5
+        "#;
+        assert_eq!(
+            lint_str(schema),
+            paths(&[
+                ("properties[0].count.min", "min"),
+                ("properties[1].name.minLength", "minLength"),
+                ("properties[2].list.minItems", "minItems"),
+            ])
+        );
+    }
+
+    #[test]
+    fn inapplicable_keywords() {
+        let schema = r#"
+        properties:
+          - count:
+              type: integer
+              pattern: "^[0-9]+$"
+          - name:
+              type: string
+              items:
+                type: string
+          - network:
+              type: object
+              uniqueItems: true
+        "#;
+        assert_eq!(
+            lint_str(schema),
+            paths(&[
+                ("properties[0].count.pattern", "pattern"),
+                ("properties[1].name.items", "items"),
+                ("properties[2].network.uniqueItems", "uniqueItems"),
+            ])
+        );
+    }
+
+    #[test]
+    fn undeclared_targets() {
+        let schema = r#"
+        properties:
+          - advanced:
+              mapping:
+                targets:
+                  config-json:
+                    type: file
+                    format: json
+                    location:
+                      partition: resin-boot
+                      path: /config.json
+              properties:
+                - hostname:
+                    type: hostname
+                    mapping:
+                      target: config-json
+                      path: hostname
+          - country:
+              type: string
+              mapping:
+                target: config-json
+                path: country
+        "#;
+        assert_eq!(
+            lint_str(schema),
+            paths(&[("properties[1].country.mapping.target", "target")])
+        );
+    }
+
+    #[test]
+    fn binary_target_property_type() {
+        let schema = r#"
+        mapping:
+          targets:
+            splash:
+              type: file
+              format: binary
+              location:
+                partition: resin-boot
+                path: /splash/resin-logo.png
+        properties:
+          - logo:
+              type: file
+              mapping:
+                target: splash
+          - name:
+              type: string
+              mapping:
+                target: splash
+        "#;
+        assert_eq!(
+            lint_str(schema),
+            paths(&[("properties[1].name.mapping.target", "target")])
+        );
+    }
+
+    #[test]
+    fn duplicate_property_names() {
+        let schema = r#"
+        properties:
+          - name:
+              type: string
+          - name:
+              type: integer
+        "#;
+        assert_eq!(lint_str(schema), paths(&[("properties[1].name", "name")]));
+    }
+
+    #[test]
+    fn unique_items_paths() {
+        let schema = r#"
+        properties:
+          - networks:
+              type: array
+              uniqueItems:
+                - ssid
+                - wifi.psk
+                - wifi.foo
+                - id
+              items:
+                properties:
+                  - ssid:
+                      type: string
+                  - wifi:
+                      properties:
+                        - psk:
+                            type: string
+        "#;
+        let errors: Vec<_> = {
+            let schema: Schema = schema.parse().unwrap();
+            lint(&schema).into_iter().map(|e| e.message().to_string()).collect()
+        };
+        assert_eq!(
+            errors,
+            vec![
+                "path 'wifi.foo' does not name an existing property",
+                "path 'id' does not name an existing property"
+            ]
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! A module containing localized message catalogs
+//!
+//! A message catalog is a flat YAML / JSON mapping of message keys to message templates.
+//! Validation messages are keyed by the validation error code (see
+//! [`ErrorCode`](../validator/enum.ErrorCode.html)) and the `{name}` placeholders are
+//! replaced with the error parameters (`{limit}`, `{actual}`, `{expected}`,
+//! `{expectedType}`, `{pattern}`, `{property}`).
+//!
+//! English catalog is built in, other catalogs are loaded at runtime. Messages are looked
+//! up in the requested locale (`pt-BR`), then in its language (`pt`) and then in English.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::locale::Catalogs;
+//! use jellyschema::schema::Schema;
+//! use jellyschema::validator::validate;
+//! use serde_json::json;
+//!
+//! let schema: Schema = r#"
+//!   version: 1
+//!   type: string
+//!   maxLength: 4
+//! "#.parse().unwrap();
+//!
+//! let mut catalogs = Catalogs::new();
+//! catalogs
+//!     .load("de", "max-length: \"höchstens '{limit}' Zeichen erwartet\"")
+//!     .unwrap();
+//!
+//! let state = validate(&schema, &json!("balena"));
+//! let error = &state.errors()[0];
+//!
+//! assert_eq!(catalogs.format(error, "de-AT"), "höchstens '4' Zeichen erwartet");
+//! assert_eq!(catalogs.format(error, "en"), "expected up to '4' characters");
+//! ```
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{error::Error, validator::ValidationError};
+
+/// Default locale with the built-in catalog
+pub const DEFAULT_LOCALE: &str = "en";
+
+const ENGLISH: &str = include_str!("en.yaml");
+
+/// Message catalog of a single locale
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn new() -> Catalog {
+        Catalog::default()
+    }
+
+    /// Parses the catalog source
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - YAML or JSON mapping of message keys to message templates
+    pub fn parse(source: &str) -> Result<Catalog, Error> {
+        let messages: Option<HashMap<String, String>> = serde_yaml::from_str(source)?;
+        Ok(Catalog {
+            messages: messages.unwrap_or_default(),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn insert<K, M>(&mut self, key: K, message: M) -> Option<String>
+    where
+        K: Into<String>,
+        M: Into<String>,
+    {
+        self.messages.insert(key.into(), message.into())
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    // Adds all messages from the other catalog, existing messages are replaced
+    fn merge(&mut self, other: Catalog) {
+        self.messages.extend(other.messages);
+    }
+}
+
+/// Message catalogs of all locales
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catalogs {
+    catalogs: HashMap<String, Catalog>,
+}
+
+impl Default for Catalogs {
+    fn default() -> Catalogs {
+        let english = Catalog::parse(ENGLISH).expect("invalid built-in English catalog");
+
+        let mut catalogs = HashMap::new();
+        catalogs.insert(DEFAULT_LOCALE.to_string(), english);
+        Catalogs { catalogs }
+    }
+}
+
+// Normalizes the locale, `pt_br` -> `pt-BR`
+pub(crate) fn normalize(locale: &str) -> String {
+    let mut parts = locale.split(['-', '_']);
+    let mut result = parts.next().unwrap_or_default().to_lowercase();
+
+    for part in parts {
+        result.push('-');
+        if part.len() == 2 {
+            result.push_str(&part.to_uppercase());
+        } else {
+            result.push_str(part);
+        }
+    }
+
+    result
+}
+
+// Locales used for the message lookup, `pt-BR` -> [`pt-BR`, `pt`, `en`]
+pub(crate) fn fallbacks(locale: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut locale = normalize(locale);
+
+    loop {
+        result.push(locale.clone());
+        match locale.rfind('-') {
+            Some(idx) => locale.truncate(idx),
+            None => break,
+        };
+    }
+
+    if !result.iter().any(|x| x == DEFAULT_LOCALE) {
+        result.push(DEFAULT_LOCALE.to_string());
+    }
+
+    result
+}
+
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+// Replaces the `{name}` placeholders, unknown placeholders are kept
+fn format_template(template: &str, error: &ValidationError) -> String {
+    let params = error.params();
+    let mut result = String::with_capacity(template.len());
+    let mut remaining = template;
+
+    while let Some(start) = remaining.find('{') {
+        result.push_str(&remaining[..start]);
+        remaining = &remaining[start..];
+
+        let end = match remaining.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let value = match &remaining[1..end] {
+            "limit" => params.limit().map(render),
+            "actual" => params.actual().map(render),
+            "expected" => params.expected().map(render),
+            "expectedType" => params.expected_type().map(str::to_string),
+            "pattern" => params.pattern().map(str::to_string),
+            "property" => params.property().map(str::to_string),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&remaining[..=end]),
+        };
+        remaining = &remaining[end + 1..];
+    }
+
+    result.push_str(remaining);
+    result
+}
+
+impl Catalogs {
+    /// Creates catalogs with the built-in English catalog
+    pub fn new() -> Catalogs {
+        Catalogs::default()
+    }
+
+    /// Adds the locale catalog
+    ///
+    /// Messages are merged into the existing locale catalog, existing messages are
+    /// replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    /// * `catalog` - Message catalog
+    pub fn insert(&mut self, locale: &str, catalog: Catalog) {
+        self.catalogs.entry(normalize(locale)).or_default().merge(catalog);
+    }
+
+    /// Parses and adds the locale catalog
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    /// * `source` - YAML or JSON mapping of message keys to message templates
+    pub fn load(&mut self, locale: &str, source: &str) -> Result<(), Error> {
+        let catalog = Catalog::parse(source).map_err(|e| Error::message(format!("catalog '{}': {}", locale, e)))?;
+        self.insert(locale, catalog);
+        Ok(())
+    }
+
+    pub fn get(&self, locale: &str) -> Option<&Catalog> {
+        self.catalogs.get(&normalize(locale))
+    }
+
+    /// Looks up the message template, falls back to the language & English
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Requested locale
+    /// * `key` - Message key
+    pub fn message(&self, locale: &str, key: &str) -> Option<&str> {
+        fallbacks(locale)
+            .iter()
+            .filter_map(|x| self.catalogs.get(x))
+            .find_map(|x| x.get(key))
+    }
+
+    /// Formats the validation error message in the requested locale
+    ///
+    /// The error message is returned if there's no message for the error code in any
+    /// catalog.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Validation error
+    /// * `locale` - Requested locale
+    pub fn format(&self, error: &ValidationError, locale: &str) -> String {
+        match self.message(locale, error.code().as_str()) {
+            Some(template) => format_template(template, error),
+            None => error.message().to_string(),
+        }
+    }
+
+    /// Returns the validation error with the message in the requested locale
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Validation error
+    /// * `locale` - Requested locale
+    pub fn localize(&self, error: &ValidationError, locale: &str) -> ValidationError {
+        error.clone().with_message(self.format(error, locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        schema::Schema,
+        validator::{path::PathBuf, validate, ErrorCode},
+    };
+
+    const ERROR_CODES: &[ErrorCode] = &[
+        ErrorCode::InvalidData,
+        ErrorCode::Required,
+        ErrorCode::Type,
+        ErrorCode::Format,
+        ErrorCode::Const,
+        ErrorCode::Enum,
+        ErrorCode::EnumAmbiguous,
+        ErrorCode::Min,
+        ErrorCode::ExclusiveMin,
+        ErrorCode::Max,
+        ErrorCode::ExclusiveMax,
+        ErrorCode::MultipleOf,
+        ErrorCode::MinLength,
+        ErrorCode::MaxLength,
+        ErrorCode::Pattern,
+        ErrorCode::MinItems,
+        ErrorCode::MaxItems,
+        ErrorCode::UniqueItems,
+        ErrorCode::ItemsAmbiguous,
+        ErrorCode::Discriminator,
+        ErrorCode::AdditionalProperties,
+        ErrorCode::When,
+    ];
+
+    fn first_error(schema: &str, data: Value) -> ValidationError {
+        let schema: Schema = schema.parse().unwrap();
+        validate(&schema, &data).errors()[0].clone()
+    }
+
+    #[test]
+    fn english_catalog_is_complete() {
+        let catalogs = Catalogs::new();
+        for code in ERROR_CODES {
+            assert!(catalogs.message("en", code.as_str()).is_some(), "{}", code);
+        }
+        assert_eq!(catalogs.get("en").unwrap().len(), ERROR_CODES.len());
+    }
+
+    #[test]
+    fn format_english_messages() {
+        let catalogs = Catalogs::new();
+
+        let error = first_error("type: integer\nmin: 10\n", json!(5));
+        assert_eq!(catalogs.format(&error, "en"), "expected '>= 10'");
+
+        let error = first_error("properties:\n  - host:\n      type: hostname\n", json!({}));
+        assert_eq!(catalogs.format(&error, "en-US"), "'hostname' is not an optional type");
+
+        let error = first_error("type: string\npattern: ^[a-z]+$\n", json!("1"));
+        assert_eq!(catalogs.format(&error, "en"), "does not match '^[a-z]+$'");
+    }
+
+    #[test]
+    fn fallback_to_language_and_english() {
+        let mut catalogs = Catalogs::new();
+        catalogs
+            .load("pt", "min: \"esperado '>= {limit}'\"\nmax: \"esperado '<= {limit}'\"")
+            .unwrap();
+        catalogs.load("pt_br", "max: \"esperado no máximo {limit}\"").unwrap();
+
+        let min = first_error("type: integer\nmin: 10\n", json!(5));
+        let max = first_error("type: integer\nmax: 10\n", json!(15));
+        let items = first_error("type: array\nminItems: 2\n", json!([]));
+
+        assert_eq!(catalogs.format(&min, "pt-BR"), "esperado '>= 10'");
+        assert_eq!(catalogs.format(&max, "pt-BR"), "esperado no máximo 10");
+        assert_eq!(catalogs.format(&max, "pt"), "esperado '<= 10'");
+        assert_eq!(catalogs.format(&items, "pt-BR"), "should contain at least '2' items");
+        assert_eq!(catalogs.format(&items, "fr"), "should contain at least '2' items");
+    }
+
+    #[test]
+    fn localize_error() {
+        let mut catalogs = Catalogs::new();
+        catalogs.load("de", r#"{"const": "'{expected}' erwartet"}"#).unwrap();
+
+        let error = first_error("type: string\nconst: foo\n", json!("bar"));
+        let localized = catalogs.localize(&error, "de");
+        assert_eq!(localized.message(), "'foo' erwartet");
+        assert_eq!(localized.code(), ErrorCode::Const);
+    }
+
+    #[test]
+    fn keep_unknown_placeholders() {
+        let mut catalogs = Catalogs::new();
+        catalogs.load("de", "enum: \"{actual} ist {unknown} {\"").unwrap();
+
+        let error = ValidationError::new(ErrorCode::Enum, PathBuf::new(), PathBuf::new(), "").with_actual(1);
+        assert_eq!(catalogs.format(&error, "de"), "1 ist {unknown} {");
+    }
+
+    #[test]
+    fn invalid_catalog() {
+        let mut catalogs = Catalogs::new();
+        let error = catalogs.load("de", "- foo").unwrap_err();
+        assert!(error.to_string().starts_with("catalog 'de': "));
+    }
+
+    #[test]
+    fn normalize_locales() {
+        assert_eq!(fallbacks("pt_br"), vec!["pt-BR", "pt", "en"]);
+        assert_eq!(fallbacks("EN-us"), vec!["en-US", "en"]);
+        assert_eq!(fallbacks("zh-Hant-TW"), vec!["zh-Hant-TW", "zh-Hant", "zh", "en"]);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{btree_map, BTreeMap};
+use std::fmt;
+
+use regex::Regex;
+
+use crate::schema::mapping::{RawTarget, TargetLocation, TargetType};
+
+const DEFAULT_GLOB: &str = "*";
+
+// Translates a file name glob pattern (`*`, `?`, `[...]`) into the regular expression
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                pattern.push('[');
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => break,
+                        '!' if pattern.ends_with('[') => pattern.push('^'),
+                        '\\' | '[' | '^' => {
+                            pattern.push('\\');
+                            pattern.push(c);
+                        }
+                        c => pattern.push(c),
+                    };
+                }
+                pattern.push(']');
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        };
+    }
+
+    pattern.push('$');
+    Regex::new(&pattern).ok()
+}
+
+/// Target file identification
+///
+/// A target file is identified by the target location and by an optional file name.
+/// File name is present for `fileset` targets only, where the location path is a
+/// directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TargetFile {
+    location: TargetLocation,
+    filename: Option<String>,
+}
+
+impl TargetFile {
+    pub fn new(location: TargetLocation, filename: Option<String>) -> TargetFile {
+        TargetFile { location, filename }
+    }
+
+    pub fn location(&self) -> &TargetLocation {
+        &self.location
+    }
+
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Checks if the file belongs to the target
+    ///
+    /// `file` target has just one file - the target location. `fileset` target location
+    /// path is a directory and all files in it matching the target glob (`*` if not
+    /// specified) belong to the target.
+    pub fn belongs_to(&self, target: &RawTarget) -> bool {
+        match (target.type_(), self.filename()) {
+            (TargetType::File, None) => &self.location == target.location(),
+            (TargetType::FileSet, Some(filename)) => {
+                self.location.partition() == target.location().partition()
+                    && self.location.path().trim_end_matches('/') == target.location().path().trim_end_matches('/')
+                    && glob_regex(target.glob().unwrap_or(DEFAULT_GLOB))
+                        .map(|regex| regex.is_match(filename))
+                        .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns full file path (location path & file name) on the partition
+    pub fn path(&self) -> String {
+        match self.filename() {
+            Some(filename) => format!("{}/{}", self.location.path().trim_end_matches('/'), filename),
+            None => self.location.path().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TargetFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.location.partition(), self.path())
+    }
+}
+
+/// In-memory set of target files
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TargetFiles {
+    files: BTreeMap<TargetFile, Vec<u8>>,
+}
+
+impl TargetFiles {
+    pub fn new() -> TargetFiles {
+        TargetFiles { files: BTreeMap::new() }
+    }
+
+    /// Inserts a file, returns previous content if the file was already present
+    pub fn insert(&mut self, file: TargetFile, content: Vec<u8>) -> Option<Vec<u8>> {
+        self.files.insert(file, content)
+    }
+
+    pub fn get(&self, file: &TargetFile) -> Option<&[u8]> {
+        self.files.get(file).map(Vec::as_slice)
+    }
+
+    /// Finds a file by the full path, regardless of the partition
+    pub fn find(&self, path: &str) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|(file, _)| file.path() == path)
+            .map(|(_, content)| content.as_slice())
+    }
+
+    pub fn contains(&self, file: &TargetFile) -> bool {
+        self.files.contains_key(file)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, TargetFile, Vec<u8>> {
+        self.files.iter()
+    }
+}
+
+impl IntoIterator for TargetFiles {
+    type Item = (TargetFile, Vec<u8>);
+    type IntoIter = btree_map::IntoIter<TargetFile, Vec<u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a TargetFiles {
+    type Item = (&'a TargetFile, &'a Vec<u8>);
+    type IntoIter = btree_map::Iter<'a, TargetFile, Vec<u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::mapping::LocationPartition;
+
+    fn location(path: &str) -> TargetLocation {
+        TargetLocation::new(LocationPartition::Label("resin-boot".to_string()), path)
+    }
+
+    #[test]
+    fn file_path() {
+        let file = TargetFile::new(location("/config.json"), None);
+        assert_eq!(file.path(), "/config.json");
+    }
+
+    #[test]
+    fn file_set_path() {
+        let file = TargetFile::new(location("/system-connections/"), Some("balena-wifi".to_string()));
+        assert_eq!(file.path(), "/system-connections/balena-wifi");
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let matches = |glob: &str, name: &str| glob_regex(glob).unwrap().is_match(name);
+
+        assert!(matches("*", "balena-wifi"));
+        assert!(matches("*.conf", "redsocks.conf"));
+        assert!(!matches("*.conf", "redsocks.conf.bak"));
+        assert!(matches("wifi-?", "wifi-1"));
+        assert!(!matches("wifi-?", "wifi-10"));
+        assert!(matches("wifi-[0-9]", "wifi-5"));
+        assert!(!matches("wifi-[!0-9]", "wifi-5"));
+        assert!(!matches("*", "foo/bar"));
+    }
+
+    #[test]
+    fn belongs_to_target() {
+        let target: RawTarget = serde_yaml::from_str(
+            r#"
+            type: fileset
+            format: ini
+            glob: "*.nmconnection"
+            location:
+              partition: resin-boot
+              path: /system-connections/
+            "#,
+        )
+        .unwrap();
+
+        let file = |path: &str, name: &str| TargetFile::new(location(path), Some(name.to_string()));
+
+        assert!(file("/system-connections", "home.nmconnection").belongs_to(&target));
+        assert!(!file("/system-connections", "home").belongs_to(&target));
+        assert!(!file("/other", "home.nmconnection").belongs_to(&target));
+        assert!(!TargetFile::new(location("/system-connections"), None).belongs_to(&target));
+    }
+
+    #[test]
+    fn display() {
+        let file = TargetFile::new(location("/config.json"), None);
+        assert_eq!(file.to_string(), "resin-boot:/config.json");
+    }
+
+    #[test]
+    fn find_by_path() {
+        let mut files = TargetFiles::new();
+        files.insert(TargetFile::new(location("/config.json"), None), b"{}".to_vec());
+        assert_eq!(files.find("/config.json"), Some(&b"{}"[..]));
+        assert!(files.find("/foo.json").is_none());
+    }
+}
@@ -0,0 +1,110 @@
+//! Binary format
+//!
+//! Target document is a `file` value - base64 encoded `data:` URL
+//! (`data:image/png;name=logo.png;base64,iVBOR...`). The file is written as
+//! decoded raw bytes. The `data:` URL is reconstructed when read back, the name
+//! is the target file name and the media type is derived from its extension.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    schema::{PrimitiveType, Schema},
+};
+
+fn media_type(name: &str) -> &'static str {
+    let extension = name.rsplit('.').next().unwrap_or_default().to_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "txt" => "text/plain",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Checks that the value is bound to a `file` property
+pub fn prepare(schema: &Schema, value: Value) -> Result<Value, Error> {
+    match schema.r#type().primitive_type() {
+        PrimitiveType::File => Ok(value),
+        primitive_type => Err(Error::message(format!(
+            "binary target requires a 'file' property, got '{}'",
+            primitive_type
+        ))),
+    }
+}
+
+pub fn serialize(document: &Value) -> Result<Vec<u8>, Error> {
+    let url = match document {
+        Value::Null => return Ok(vec![]),
+        Value::String(s) => s,
+        _ => return Err(Error::message("binary document must be a file")),
+    };
+
+    let data = url
+        .strip_prefix("data:")
+        .and_then(|x| x.find(";base64,").map(|idx| &x[idx + 8..]))
+        .ok_or_else(|| Error::message("binary document must be a base64 encoded data URL"))?;
+
+    STANDARD
+        .decode(data)
+        .map_err(|e| Error::message(format!("unable to decode file data: {}", e)))
+}
+
+/// Deserializes file content into the `data:` URL
+///
+/// # Arguments
+///
+/// * `content` - File content
+/// * `path` - File path, used for the file name & media type
+pub fn deserialize(content: &[u8], path: &str) -> Result<Value, Error> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    Ok(Value::String(format!(
+        "data:{};name={};base64,{}",
+        media_type(name),
+        name,
+        STANDARD.encode(content)
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reject_non_file_property() {
+        let schema: Schema = "type: string".parse().unwrap();
+        assert_eq!(
+            prepare(&schema, json!("foo")).unwrap_err().to_string(),
+            "binary target requires a 'file' property, got 'string'"
+        );
+    }
+
+    #[test]
+    fn decode_data_url() {
+        assert_eq!(
+            serialize(&json!("data:text/plain;name=test.txt;base64,aGVsbG8=")).unwrap(),
+            b"hello".to_vec()
+        );
+    }
+
+    #[test]
+    fn encode_data_url() {
+        assert_eq!(
+            deserialize(b"hello", "/splash/resin-logo.png").unwrap(),
+            json!("data:image/png;name=resin-logo.png;base64,aGVsbG8=")
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_document() {
+        assert!(serialize(&json!("aGVsbG8=")).is_err());
+        assert!(serialize(&json!("data:text/plain;name=test.txt;base64,***")).is_err());
+        assert!(serialize(&json!(10)).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Untyped values coercion
+//!
+//! Some formats (INI, redsocks, ...) do not preserve value types, all values are
+//! strings. These strings are coerced to the schema types when read back.
+use serde_json::Value;
+
+use crate::schema::{PrimitiveType, Schema};
+
+/// Format specific values syntax
+pub struct Syntax {
+    /// Boolean literals
+    pub booleans: &'static [(&'static str, bool)],
+    /// List items separator, `None` if lists are not supported
+    pub list_separator: Option<char>,
+}
+
+/// Coerces strings to the schema types
+///
+/// Values which can't be coerced are left untouched and they're reported by the validator.
+pub fn coerce(syntax: &Syntax, schema: &Schema, value: Value) -> Value {
+    let string = match value {
+        Value::String(s) => s,
+        Value::Object(mut object) => {
+            for property in schema.properties() {
+                if let Some(value) = object.remove(property.name()) {
+                    object.insert(property.name().to_string(), coerce(syntax, property.schema(), value));
+                }
+            }
+            return Value::Object(object);
+        }
+        value => return value,
+    };
+
+    match schema.r#type().primitive_type() {
+        PrimitiveType::Boolean => match syntax.booleans.iter().find(|(literal, _)| *literal == string) {
+            Some((_, b)) => Value::Bool(*b),
+            None => Value::String(string),
+        },
+        PrimitiveType::Integer | PrimitiveType::Port => match string.parse::<i64>() {
+            Ok(n) => Value::from(n),
+            _ => Value::String(string),
+        },
+        PrimitiveType::Number => match (string.parse::<i64>(), string.parse::<f64>()) {
+            (Ok(n), _) => Value::from(n),
+            (_, Ok(n)) => Value::from(n),
+            _ => Value::String(string),
+        },
+        PrimitiveType::Array | PrimitiveType::StringList if syntax.list_separator.is_some() => {
+            let separator = syntax.list_separator.expect("invalid guard");
+            let items = string.split(separator).filter(|x| !x.is_empty());
+
+            match schema.items() {
+                [items_schema] => Value::Array(
+                    items
+                        .map(|x| coerce(syntax, items_schema, Value::String(x.to_string())))
+                        .collect(),
+                ),
+                _ => Value::Array(items.map(|x| Value::String(x.to_string())).collect()),
+            }
+        }
+        _ => Value::String(string),
+    }
+}
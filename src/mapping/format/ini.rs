@@ -0,0 +1,354 @@
+//! INI format (NetworkManager keyfile flavour)
+//!
+//! Target document is an object where nested objects are sections and other values
+//! are section keys. Section names can contain dashes (`[wifi-security]`), booleans
+//! are written as `true` / `false` and arrays as `;` separated lists (`dns=8.8.8.8;1.1.1.1;`).
+//!
+//! String values are escaped the way NetworkManager keyfiles expect (`\n`, `\t`, `\r`,
+//! `\\` and `\s` for leading & trailing spaces), keys & section names which would change
+//! the file structure are rejected.
+//!
+//! INI files do not carry type information. All values are parsed as strings and they're
+//! coerced to the schema types when read back.
+use std::fmt::Write;
+
+use serde_json::{Map, Value};
+
+use crate::{error::Error, mapping::format::coerce::Syntax};
+
+const LIST_SEPARATOR: char = ';';
+
+/// INI values syntax
+pub const SYNTAX: Syntax = Syntax {
+    booleans: &[("true", true), ("false", false)],
+    list_separator: Some(LIST_SEPARATOR),
+};
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let leading = value.len() - value.trim_start_matches(' ').len();
+    let trailing = value.len() - value.trim_end_matches(' ').len();
+
+    for (idx, c) in value.char_indices() {
+        match c {
+            ' ' if idx < leading || idx >= value.len() - trailing => result.push_str("\\s"),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            c => result.push(c),
+        };
+    }
+
+    result
+}
+
+fn unescape(value: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(c) => return Err(format!("invalid escape sequence '\\{}'", c)),
+            None => return Err("incomplete escape sequence".to_string()),
+        };
+    }
+
+    Ok(result)
+}
+
+fn check_key(key: &str) -> Result<(), Error> {
+    if key.is_empty() || key.contains(['=', '[', '\n', '\r']) {
+        return Err(Error::message(format!("invalid key '{}'", key.escape_default())));
+    }
+    Ok(())
+}
+
+fn check_section(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains([']', '\n', '\r']) {
+        return Err(Error::message(format!(
+            "invalid section name '{}'",
+            name.escape_default()
+        )));
+    }
+    Ok(())
+}
+
+fn serialize_value(key: &str, value: &Value) -> Result<Option<String>, Error> {
+    let result = match value {
+        Value::Null => return Ok(None),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => escape(s),
+        Value::Array(array) => {
+            let mut result = String::new();
+            for item in array {
+                match serialize_value(key, item)? {
+                    Some(item) if item.contains(LIST_SEPARATOR) => {
+                        return Err(Error::message(format!(
+                            "key '{}': list item '{}' contains '{}'",
+                            key, item, LIST_SEPARATOR
+                        )));
+                    }
+                    Some(item) => {
+                        result.push_str(&item);
+                        result.push(LIST_SEPARATOR);
+                    }
+                    None => {}
+                };
+            }
+            result
+        }
+        Value::Object(_) => {
+            return Err(Error::message(format!(
+                "key '{}': nested objects are not supported",
+                key
+            )));
+        }
+    };
+
+    Ok(Some(result))
+}
+
+pub fn serialize(document: &Value) -> Result<Vec<u8>, Error> {
+    let document = match document {
+        Value::Object(object) => object,
+        Value::Null => return Ok(vec![]),
+        _ => return Err(Error::message("INI document must be an object")),
+    };
+
+    let mut output = String::new();
+
+    // Keys without a section must be placed before the first section
+    for (key, value) in document.iter().filter(|(_, v)| !v.is_object()) {
+        check_key(key)?;
+        if let Some(value) = serialize_value(key, value)? {
+            writeln!(output, "{}={}", key, value).expect("unable to write to string");
+        }
+    }
+
+    for (name, section) in document.iter().filter_map(|(k, v)| v.as_object().map(|v| (k, v))) {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        check_section(name)?;
+        writeln!(output, "[{}]", name).expect("unable to write to string");
+
+        for (key, value) in section {
+            let value = check_key(key)
+                .and_then(|_| serialize_value(key, value))
+                .map_err(|e| Error::message(format!("section '{}': {}", name, e)))?;
+            if let Some(value) = value {
+                writeln!(output, "{}={}", key, value).expect("unable to write to string");
+            }
+        }
+    }
+
+    Ok(output.into_bytes())
+}
+
+pub fn deserialize(content: &[u8]) -> Result<Value, Error> {
+    let content = std::str::from_utf8(content).map_err(|e| Error::message(e.to_string()))?;
+
+    let mut document = Map::new();
+    let mut section: Option<String> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        // Escaped trailing spaces (`\s`) are kept, raw whitespace around `=` is ignored
+        let line = line.trim_start();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let line = line.trim_end();
+            let name = line
+                .strip_prefix('[')
+                .and_then(|x| x.strip_suffix(']'))
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| Error::message(format!("line {}: invalid section header '{}'", idx + 1, line)))?;
+            document.entry(name).or_insert_with(|| Value::Object(Map::new()));
+            section = Some(name.to_string());
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+            None => {
+                return Err(Error::message(format!(
+                    "line {}: expected key=value, got '{}'",
+                    idx + 1,
+                    line
+                )))
+            }
+        };
+
+        if key.is_empty() {
+            return Err(Error::message(format!("line {}: empty key", idx + 1)));
+        }
+
+        let keys = match &section {
+            Some(name) => document
+                .get_mut(name)
+                .and_then(Value::as_object_mut)
+                .expect("invalid section"),
+            None => &mut document,
+        };
+        let value = unescape(value).map_err(|e| Error::message(format!("line {}: {}", idx + 1, e)))?;
+        keys.insert(key.to_string(), Value::String(value));
+    }
+
+    Ok(Value::Object(document))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mapping::format::coerce::coerce, schema::Schema};
+    use serde_json::json;
+
+    fn serialize_str(document: Value) -> String {
+        String::from_utf8(serialize(&document).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn serialize_sections() {
+        let document = json!({
+            "connection": {"id": "balena-wifi", "type": "wifi"},
+            "wifi": {"hidden": true, "ssid": "Home"},
+            "wifi-security": {"psk": "secret"},
+            "ipv4": {"dns": ["8.8.8.8", "1.1.1.1"], "route-metric": 100}
+        });
+
+        assert_eq!(
+            serialize_str(document),
+            "[connection]\nid=balena-wifi\ntype=wifi\n\n\
+             [ipv4]\ndns=8.8.8.8;1.1.1.1;\nroute-metric=100\n\n\
+             [wifi]\nhidden=true\nssid=Home\n\n\
+             [wifi-security]\npsk=secret\n"
+        );
+    }
+
+    #[test]
+    fn serialize_keys_without_section_first() {
+        let document = json!({"wifi": {"ssid": "Home"}, "version": 1});
+        assert_eq!(serialize_str(document), "version=1\n\n[wifi]\nssid=Home\n");
+    }
+
+    #[test]
+    fn fail_on_nested_objects() {
+        assert!(serialize(&json!({"wifi": {"security": {"psk": "secret"}}})).is_err());
+        assert!(serialize(&json!(["foo"])).is_err());
+    }
+
+    #[test]
+    fn fail_on_separator_in_list_item() {
+        assert!(serialize(&json!({"ipv4": {"dns": ["8.8.8.8;1.1.1.1"]}})).is_err());
+    }
+
+    #[test]
+    fn escape_values() {
+        let document = json!({"wifi": {"ssid": "home\n[connection]\nautoconnect=false"}});
+        assert_eq!(
+            serialize_str(document),
+            "[wifi]\nssid=home\\n[connection]\\nautoconnect=false\n"
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_keys_and_sections() {
+        assert!(serialize(&json!({"wifi": {"ssid=foo": "Home"}})).is_err());
+        assert!(serialize(&json!({"wifi": {"[ssid": "Home"}})).is_err());
+        assert!(serialize(&json!({"wifi": {"ssid\nfoo": "Home"}})).is_err());
+        assert!(serialize(&json!({"ssid\n[wifi]": "Home"})).is_err());
+        assert!(serialize(&json!({"wifi]": {"ssid": "Home"}})).is_err());
+        assert!(serialize(&json!({"wifi\n": {"ssid": "Home"}})).is_err());
+    }
+
+    #[test]
+    fn round_trip_whitespace_and_backslashes() {
+        let document = json!({"wifi": {"ssid": "  Home \\ Net\t1 ", "psk": "a\\nb\r\n"}});
+        let content = serialize(&document).unwrap();
+        assert_eq!(
+            String::from_utf8(content.clone()).unwrap(),
+            "[wifi]\npsk=a\\\\nb\\r\\n\nssid=\\s\\sHome \\\\ Net\\t1\\s\n"
+        );
+        assert_eq!(deserialize(&content).unwrap(), document);
+    }
+
+    #[test]
+    fn fail_on_invalid_escape_sequences() {
+        assert!(deserialize(b"[wifi]\nssid=Home\\x\n").is_err());
+        assert!(deserialize(b"[wifi]\nssid=Home\\\n").is_err());
+    }
+
+    #[test]
+    fn deserialize_sections() {
+        let content = "# comment\n[wifi]\nssid = Home\nhidden=true\n\n; comment\n[wifi-security]\npsk=a=b\n";
+        assert_eq!(
+            deserialize(content.as_bytes()).unwrap(),
+            json!({"wifi": {"ssid": "Home", "hidden": "true"}, "wifi-security": {"psk": "a=b"}})
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_lines() {
+        assert!(deserialize(b"[wifi\nssid=Home\n").is_err());
+        assert!(deserialize(b"[wifi]\nssid\n").is_err());
+        assert!(deserialize(b"[wifi]\n=Home\n").is_err());
+    }
+
+    #[test]
+    fn coerce_to_schema_types() {
+        let schema: Schema = r#"
+        properties:
+          - hidden:
+              type: boolean
+          - metric:
+              type: integer
+          - ssid:
+              type: string
+          - dns:
+              type: array
+              items:
+                type: string
+          - ports:
+              type: array
+              items:
+                type: port
+        "#
+        .parse()
+        .unwrap();
+
+        let value = json!({
+            "hidden": "false",
+            "metric": "100",
+            "ssid": "1234",
+            "dns": "8.8.8.8;1.1.1.1;",
+            "ports": "80;443"
+        });
+
+        assert_eq!(
+            coerce(&SYNTAX, &schema, value),
+            json!({
+                "hidden": false,
+                "metric": 100,
+                "ssid": "1234",
+                "dns": ["8.8.8.8", "1.1.1.1"],
+                "ports": [80, 443]
+            })
+        );
+    }
+}
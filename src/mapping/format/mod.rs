@@ -0,0 +1,61 @@
+//! Target file formats
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    mapping::files::TargetFile,
+    schema::{mapping::TargetFormat, Schema},
+};
+
+mod binary;
+mod coerce;
+mod ini;
+mod json;
+mod redsocks;
+mod text;
+
+/// Serializes target document into the target file content
+pub fn serialize(format: TargetFormat, document: &Value) -> Result<Vec<u8>, Error> {
+    match format {
+        TargetFormat::Json => json::serialize(document),
+        TargetFormat::Ini => ini::serialize(document),
+        TargetFormat::Redsocks => redsocks::serialize(document),
+        TargetFormat::Text => text::serialize(document),
+        TargetFormat::Binary => binary::serialize(document),
+    }
+}
+
+/// Deserializes target file content into the target document
+pub fn deserialize(format: TargetFormat, file: &TargetFile, content: &[u8]) -> Result<Value, Error> {
+    match format {
+        TargetFormat::Json => json::deserialize(content),
+        TargetFormat::Ini => ini::deserialize(content),
+        TargetFormat::Redsocks => redsocks::deserialize(content),
+        TargetFormat::Text => text::deserialize(content),
+        TargetFormat::Binary => binary::deserialize(content, &file.path()),
+    }
+}
+
+/// Prepares a value to be placed into the target document
+///
+/// Some formats (text, ...) have their own representation of schema types, the binary
+/// format accepts `file` values only.
+pub fn prepare(format: TargetFormat, schema: &Schema, value: Value) -> Result<Value, Error> {
+    match format {
+        TargetFormat::Text => text::prepare(schema, value),
+        TargetFormat::Binary => binary::prepare(schema, value),
+        _ => Ok(value),
+    }
+}
+
+/// Coerces a value read from the target document to the schema types
+///
+/// Some formats (INI, ...) do not preserve value types.
+pub fn coerce(format: TargetFormat, schema: &Schema, value: Value) -> Value {
+    match format {
+        TargetFormat::Ini => coerce::coerce(&ini::SYNTAX, schema, value),
+        TargetFormat::Redsocks => coerce::coerce(&redsocks::SYNTAX, schema, value),
+        TargetFormat::Text => text::coerce(schema, value),
+        _ => value,
+    }
+}
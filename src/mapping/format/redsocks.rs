@@ -0,0 +1,361 @@
+//! Redsocks configuration format
+//!
+//! Target document is an object where every key is a block (`base { ... }`,
+//! `redsocks { ... }`) and the block object contains `key = value;` pairs.
+//! Repeated blocks (several `redsocks { ... }` for example) are represented
+//! as an array of objects.
+//!
+//! ```text
+//! base {
+//!     log = stderr;
+//!     redirector = iptables;
+//! }
+//!
+//! redsocks {
+//!     type = socks5;
+//!     ip = 127.0.0.1;
+//!     port = 1080;
+//! }
+//! ```
+use std::{fmt::Write, iter::Peekable, str::CharIndices};
+
+use serde_json::{Map, Value};
+
+use crate::{error::Error, mapping::format::coerce::Syntax};
+
+/// Redsocks values syntax
+pub const SYNTAX: Syntax = Syntax {
+    booleans: &[("on", true), ("off", false), ("true", true), ("false", false)],
+    list_separator: None,
+};
+
+fn is_bare_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "._-:/".contains(c)
+}
+
+fn serialize_value(key: &str, value: &Value) -> Result<Option<String>, Error> {
+    let result = match value {
+        Value::Null => return Ok(None),
+        Value::Bool(true) => "on".to_string(),
+        Value::Bool(false) => "off".to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) if !s.is_empty() && s.chars().all(is_bare_char) => s.clone(),
+        Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        Value::Array(_) | Value::Object(_) => {
+            return Err(Error::message(format!(
+                "key '{}': only scalar values are supported",
+                key
+            )));
+        }
+    };
+
+    Ok(Some(result))
+}
+
+fn serialize_block(output: &mut String, name: &str, block: &Map<String, Value>) -> Result<(), Error> {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+
+    writeln!(output, "{} {{", name).expect("unable to write to string");
+
+    for (key, value) in block {
+        let value = serialize_value(key, value).map_err(|e| Error::message(format!("block '{}': {}", name, e)))?;
+        if let Some(value) = value {
+            writeln!(output, "    {} = {};", key, value).expect("unable to write to string");
+        }
+    }
+
+    writeln!(output, "}}").expect("unable to write to string");
+    Ok(())
+}
+
+pub fn serialize(document: &Value) -> Result<Vec<u8>, Error> {
+    let document = match document {
+        Value::Object(object) => object,
+        Value::Null => return Ok(vec![]),
+        _ => return Err(Error::message("redsocks document must be an object")),
+    };
+
+    let mut output = String::new();
+
+    for (name, block) in document {
+        match block {
+            Value::Object(block) => serialize_block(&mut output, name, block)?,
+            Value::Array(blocks) if blocks.iter().all(Value::is_object) => {
+                for block in blocks {
+                    serialize_block(&mut output, name, block.as_object().expect("invalid guard"))?;
+                }
+            }
+            _ => return Err(Error::message(format!("'{}' is not a block", name))),
+        };
+    }
+
+    Ok(output.into_bytes())
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    OpenBrace,
+    CloseBrace,
+    Equal,
+    Semicolon,
+}
+
+struct Tokenizer<'a> {
+    content: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(content: &'a str) -> Tokenizer<'a> {
+        Tokenizer {
+            content,
+            chars: content.char_indices().peekable(),
+            line: 1,
+        }
+    }
+
+    fn error<S: AsRef<str>>(&self, message: S) -> Error {
+        Error::message(format!("line {}: {}", self.line, message.as_ref()))
+    }
+
+    fn next_char(&mut self) -> Option<(usize, char)> {
+        let next = self.chars.next();
+        if let Some((_, '\n')) = next {
+            self.line += 1;
+        }
+        next
+    }
+
+    fn skip_line(&mut self) {
+        while let Some((_, c)) = self.next_char() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), Error> {
+        let mut previous = None;
+        while let Some((_, c)) = self.next_char() {
+            if previous == Some('*') && c == '/' {
+                return Ok(());
+            }
+            previous = Some(c);
+        }
+        Err(self.error("unterminated comment"))
+    }
+
+    fn quoted_string(&mut self) -> Result<Token, Error> {
+        let mut result = String::new();
+        loop {
+            match self.next_char() {
+                Some((_, '"')) => return Ok(Token::Word(result)),
+                Some((_, '\\')) => match self.next_char() {
+                    Some((_, c)) => result.push(c),
+                    None => break,
+                },
+                Some((_, c)) => result.push(c),
+                None => break,
+            };
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        while let Some(&(start, c)) = self.chars.peek() {
+            let token = match c {
+                c if c.is_whitespace() => {
+                    self.next_char();
+                    continue;
+                }
+                '#' => {
+                    self.skip_line();
+                    continue;
+                }
+                '/' if self.content[start..].starts_with("//") => {
+                    self.skip_line();
+                    continue;
+                }
+                '/' if self.content[start..].starts_with("/*") => {
+                    self.next_char();
+                    self.next_char();
+                    self.skip_block_comment()?;
+                    continue;
+                }
+                '{' => Token::OpenBrace,
+                '}' => Token::CloseBrace,
+                '=' => Token::Equal,
+                ';' => Token::Semicolon,
+                '"' => {
+                    self.next_char();
+                    return self.quoted_string().map(Some);
+                }
+                c if is_bare_char(c) => {
+                    let mut end = start;
+                    while let Some(&(idx, c)) = self.chars.peek() {
+                        if !is_bare_char(c) {
+                            break;
+                        }
+                        end = idx + c.len_utf8();
+                        self.next_char();
+                    }
+                    return Ok(Some(Token::Word(self.content[start..end].to_string())));
+                }
+                c => return Err(self.error(format!("unexpected character '{}'", c))),
+            };
+            self.next_char();
+            return Ok(Some(token));
+        }
+        Ok(None)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Error> {
+        match self.next_token()? {
+            Some(ref token) if *token == expected => Ok(()),
+            Some(token) => Err(self.error(format!("expected {:?}, got {:?}", expected, token))),
+            None => Err(self.error(format!("expected {:?}, got end of file", expected))),
+        }
+    }
+}
+
+fn deserialize_block(tokenizer: &mut Tokenizer) -> Result<Map<String, Value>, Error> {
+    let mut block = Map::new();
+
+    loop {
+        let key = match tokenizer.next_token()? {
+            Some(Token::CloseBrace) => return Ok(block),
+            Some(Token::Word(key)) => key,
+            Some(token) => return Err(tokenizer.error(format!("expected key, got {:?}", token))),
+            None => return Err(tokenizer.error("unterminated block")),
+        };
+
+        tokenizer.expect(Token::Equal)?;
+
+        let value = match tokenizer.next_token()? {
+            Some(Token::Word(value)) => value,
+            Some(token) => return Err(tokenizer.error(format!("expected value, got {:?}", token))),
+            None => return Err(tokenizer.error("unterminated block")),
+        };
+
+        tokenizer.expect(Token::Semicolon)?;
+        block.insert(key, Value::String(value));
+    }
+}
+
+pub fn deserialize(content: &[u8]) -> Result<Value, Error> {
+    let content = std::str::from_utf8(content).map_err(|e| Error::message(e.to_string()))?;
+    let mut tokenizer = Tokenizer::new(content);
+    let mut document = Map::new();
+
+    while let Some(token) = tokenizer.next_token()? {
+        let name = match token {
+            Token::Word(name) => name,
+            token => return Err(tokenizer.error(format!("expected block name, got {:?}", token))),
+        };
+
+        tokenizer.expect(Token::OpenBrace)?;
+        let block = Value::Object(deserialize_block(&mut tokenizer)?);
+
+        match document.get_mut(&name) {
+            Some(Value::Array(blocks)) => blocks.push(block),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, block]);
+            }
+            None => {
+                document.insert(name, block);
+            }
+        };
+    }
+
+    Ok(Value::Object(document))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONTENT: &str = "base {
+    log = stderr;
+    log_debug = off;
+    redirector = iptables;
+}
+
+redsocks {
+    ip = 10.0.0.1;
+    local_ip = 127.0.0.1;
+    login = \"john doe\";
+    port = 1080;
+    type = socks5;
+}
+";
+
+    #[test]
+    fn serialize_blocks() {
+        let document = json!({
+            "base": {"log": "stderr", "log_debug": false, "redirector": "iptables"},
+            "redsocks": {
+                "type": "socks5",
+                "ip": "10.0.0.1",
+                "port": 1080,
+                "local_ip": "127.0.0.1",
+                "login": "john doe"
+            }
+        });
+        assert_eq!(String::from_utf8(serialize(&document).unwrap()).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn serialize_repeated_blocks() {
+        let document = json!({"redsocks": [{"port": 1080}, {"port": 1081}]});
+        assert_eq!(
+            String::from_utf8(serialize(&document).unwrap()).unwrap(),
+            "redsocks {\n    port = 1080;\n}\n\nredsocks {\n    port = 1081;\n}\n"
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_document() {
+        assert!(serialize(&json!({"log": "stderr"})).is_err());
+        assert!(serialize(&json!({"base": {"log": ["stderr"]}})).is_err());
+    }
+
+    #[test]
+    fn deserialize_blocks() {
+        assert_eq!(
+            deserialize(CONTENT.as_bytes()).unwrap(),
+            json!({
+                "base": {"log": "stderr", "log_debug": "off", "redirector": "iptables"},
+                "redsocks": {
+                    "type": "socks5",
+                    "ip": "10.0.0.1",
+                    "port": "1080",
+                    "local_ip": "127.0.0.1",
+                    "login": "john doe"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_comments_and_repeated_blocks() {
+        let content = "// comment\nredsocks { port = 1080; } # comment\n/* multi\nline */ redsocks { port = 1081; }";
+        assert_eq!(
+            deserialize(content.as_bytes()).unwrap(),
+            json!({"redsocks": [{"port": "1080"}, {"port": "1081"}]})
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_content() {
+        assert!(deserialize(b"base { log = stderr }").is_err());
+        assert!(deserialize(b"base { log = stderr;").is_err());
+        assert!(deserialize(b"base log = stderr; }").is_err());
+        assert!(deserialize(b"base { log = \"stderr; }").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Plain text format
+//!
+//! Target document is a string written as it is. `stringlist` (and `array`) values are
+//! joined with the schema `separator` (new line by default) before they're written and
+//! split when read back.
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    mapping::format::coerce::{self, Syntax},
+    schema::{PrimitiveType, Schema},
+};
+
+const DEFAULT_SEPARATOR: &str = "\n";
+
+// Separator is usually written as `\n` in the YAML plain scalar, which is not an escape
+// sequence there
+fn separator(schema: &Schema) -> String {
+    schema
+        .separator()
+        .map(|x| x.replace("\\n", "\n").replace("\\t", "\t"))
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| DEFAULT_SEPARATOR.to_string())
+}
+
+fn is_list(schema: &Schema) -> bool {
+    matches!(
+        schema.r#type().primitive_type(),
+        PrimitiveType::StringList | PrimitiveType::Array
+    )
+}
+
+fn item_to_string(value: &Value) -> Result<String, Error> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(Error::message("only scalar values can be written to a text file")),
+    }
+}
+
+/// Joins list items with the schema separator
+pub fn prepare(schema: &Schema, value: Value) -> Result<Value, Error> {
+    match value {
+        Value::Array(items) if is_list(schema) => {
+            let separator = separator(schema);
+            let mut result = items
+                .iter()
+                .map(item_to_string)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(&separator);
+
+            if !result.is_empty() && separator.ends_with('\n') {
+                result.push_str(&separator);
+            }
+
+            Ok(Value::String(result))
+        }
+        Value::Null => Ok(value),
+        value => Ok(Value::String(item_to_string(&value)?)),
+    }
+}
+
+/// Splits the text into list items with the schema separator
+pub fn coerce(schema: &Schema, value: Value) -> Value {
+    match value {
+        Value::String(s) if is_list(schema) => {
+            let separator = separator(schema);
+            let syntax = Syntax {
+                booleans: &[("true", true), ("false", false)],
+                list_separator: None,
+            };
+            let items = s.split(separator.as_str()).map(str::trim).filter(|x| !x.is_empty());
+
+            match schema.items() {
+                [items_schema] => Value::Array(
+                    items
+                        .map(|x| coerce::coerce(&syntax, items_schema, Value::String(x.to_string())))
+                        .collect(),
+                ),
+                _ => Value::Array(items.map(|x| Value::String(x.to_string())).collect()),
+            }
+        }
+        value => value,
+    }
+}
+
+pub fn serialize(document: &Value) -> Result<Vec<u8>, Error> {
+    match document {
+        Value::Null => Ok(vec![]),
+        Value::String(s) => Ok(s.as_bytes().to_vec()),
+        _ => Err(Error::message("text document must be a string")),
+    }
+}
+
+pub fn deserialize(content: &[u8]) -> Result<Value, Error> {
+    let content = std::str::from_utf8(content).map_err(|e| Error::message(e.to_string()))?;
+    Ok(Value::String(content.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(separator: Option<&str>) -> Schema {
+        let separator = separator.map(|x| format!("separator: \"{}\"", x)).unwrap_or_default();
+        format!(
+            r#"
+            type: stringlist
+            {}
+            items:
+              type: string
+            "#,
+            separator
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn join_with_default_separator() {
+        assert_eq!(
+            prepare(&schema(None), json!(["10.0.0.0/8", "192.168.0.1"])).unwrap(),
+            json!("10.0.0.0/8\n192.168.0.1\n")
+        );
+    }
+
+    #[test]
+    fn join_with_custom_separator() {
+        assert_eq!(prepare(&schema(Some(" ")), json!(["a", "b"])).unwrap(), json!("a b"));
+    }
+
+    #[test]
+    fn unescape_separator() {
+        // `\\n` in the double quoted YAML string is a backslash followed by `n`
+        assert_eq!(
+            prepare(&schema(Some("\\\\n")), json!(["a", "b"])).unwrap(),
+            json!("a\nb\n")
+        );
+    }
+
+    #[test]
+    fn split_with_separator() {
+        assert_eq!(coerce(&schema(None), json!("a\n\nb\n")), json!(["a", "b"]));
+        assert_eq!(coerce(&schema(Some(" ")), json!("a b")), json!(["a", "b"]));
+    }
+
+    #[test]
+    fn fail_on_non_scalar_items() {
+        assert!(prepare(&schema(None), json!([["a"]])).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! A module containing configuration data mapping
+//!
+//! Configuration data are mapped into target files via the `mapping` keyword:
+//!
+//! * `mapping.targets` declares named targets (files) visible to the whole subtree,
+//! * `mapping.target` selects a target for the property and all its descendants,
+//!   descendants can select another one,
+//! * `mapping.path` places the property value at the given (dotted) path in the
+//!   target document, the value is placed at the document root if the property
+//!   selects the target itself and has no path,
+//! * `mapping.template` is a base document the property values are merged into,
+//! * `mapping.filename` is a file name (or a `formula` evaluated at the property
+//!   position) for the `fileset` targets.
+//!
+//! `fileset` target location path is a directory. An array property whose items select
+//! a `fileset` target is rendered into one file per item. Every file in the directory
+//! matching the target `glob` is read back as one array item (items are sorted by
+//! the file name).
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::mapping::render;
+//! use jellyschema::schema::Schema;
+//! use serde_json::json;
+//!
+//! let schema: Schema = r#"
+//!   mapping:
+//!     targets:
+//!       config-json:
+//!         type: file
+//!         format: json
+//!         location:
+//!           partition: resin-boot
+//!           path: /config.json
+//!   properties:
+//!     - hostname:
+//!         type: hostname
+//!         mapping:
+//!           target: config-json
+//!           path: hostname
+//! "#.parse().unwrap();
+//!
+//! let files = render(&schema, &json!({"hostname": "balena"})).unwrap();
+//! let config_json = files.find("/config.json").unwrap();
+//!
+//! assert_eq!(
+//!     serde_json::from_slice::<serde_json::Value>(config_json).unwrap(),
+//!     json!({"hostname": "balena"})
+//! );
+//! ```
+use crate::{
+    error::Error,
+    schema::{
+        mapping::{RawTarget, Target, TargetType},
+        Schema,
+    },
+    validator::path::PathBuf,
+};
+
+pub use self::{
+    files::{TargetFile, TargetFiles},
+    read::read,
+    render::render,
+};
+
+mod files;
+mod format;
+mod read;
+mod render;
+mod scope;
+mod value;
+
+// Identifies a file the data belong to, `filename` is required for `fileset` targets
+fn target_file(target: &RawTarget, filename: Option<&str>, path: &PathBuf) -> Result<TargetFile, Error> {
+    match (target.type_(), filename) {
+        (TargetType::File, _) => Ok(TargetFile::new(target.location().clone(), None)),
+        (TargetType::FileSet, Some(filename)) => {
+            Ok(TargetFile::new(target.location().clone(), Some(filename.to_string())))
+        }
+        (TargetType::FileSet, None) => Err(Error::at_path(path, "fileset target without a file name")),
+    }
+}
+
+fn collect_targets<'a>(schema: &'a Schema, targets: &mut Vec<&'a RawTarget>) {
+    if let Some(mapping) = schema.mapping() {
+        let mut declared: Vec<_> = mapping.targets().iter().collect();
+        declared.sort_by_key(|(name, _)| name.as_str());
+
+        for (_, target) in declared {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        if let Some(Target::Raw(target)) = mapping.target() {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+
+    for property in schema.properties() {
+        collect_targets(property.schema(), targets);
+    }
+
+    for items in schema.items() {
+        collect_targets(items, targets);
+    }
+}
+
+/// Returns all targets declared in the schema
+///
+/// Useful to find out which files should be collected before calling the [`read`](fn.read.html)
+/// function. All files belonging to the `fileset` targets should be collected
+/// (see [`TargetFile::belongs_to`](struct.TargetFile.html#method.belongs_to)).
+pub fn targets(schema: &Schema) -> Vec<&RawTarget> {
+    let mut targets = vec![];
+    collect_targets(schema, &mut targets);
+    targets
+}
+
+/// Returns existing files which should be deleted
+///
+/// Files belonging to the `fileset` targets are rendered per array item. Files of
+/// removed items are not rendered and they should be deleted.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `rendered` - Files returned by the [`render`](fn.render.html) function
+/// * `existing` - Files currently present on the device
+pub fn stale(schema: &Schema, rendered: &TargetFiles, existing: &TargetFiles) -> Vec<TargetFile> {
+    let targets: Vec<_> = targets(schema)
+        .into_iter()
+        .filter(|target| target.type_().is_file_set())
+        .collect();
+
+    existing
+        .iter()
+        .map(|(file, _)| file)
+        .filter(|file| !rendered.contains(file) && targets.iter().any(|target| file.belongs_to(target)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::mapping::{LocationPartition, TargetLocation};
+    use serde_json::json;
+
+    #[test]
+    fn stale_file_set_files() {
+        let schema: Schema = r#"
+        mapping:
+          targets:
+            config-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /config.json
+        properties:
+          - networks:
+              type: array
+              items:
+                type: string
+                mapping:
+                  target:
+                    type: fileset
+                    format: text
+                    location:
+                      partition: resin-boot
+                      path: /networks
+                  filename:
+                    formula: this
+        "#
+        .parse()
+        .unwrap();
+
+        let file = |path: &str, filename: Option<&str>| {
+            TargetFile::new(
+                TargetLocation::new(LocationPartition::Label("resin-boot".to_string()), path),
+                filename.map(str::to_string),
+            )
+        };
+
+        let mut existing = TargetFiles::new();
+        existing.insert(file("/config.json", None), b"{}".to_vec());
+        existing.insert(file("/networks", Some("home")), b"home".to_vec());
+        existing.insert(file("/networks", Some("office")), b"office".to_vec());
+
+        let rendered = render(&schema, &json!({"networks": ["home"]})).unwrap();
+        assert_eq!(
+            stale(&schema, &rendered, &existing),
+            vec![file("/networks", Some("office"))]
+        );
+    }
+}
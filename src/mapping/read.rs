@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Error,
+    mapping::{
+        files::{TargetFile, TargetFiles},
+        format,
+        scope::{has_own_target, MappingScope},
+        target_file, value,
+    },
+    schema::{
+        mapping::{FileName, Mapping, RawTarget},
+        PrimitiveType, Schema,
+    },
+    validator::path::PathBuf,
+};
+
+struct Reader<'a> {
+    files: &'a TargetFiles,
+    // Parsed target documents, `None` if the target file does not exist
+    documents: BTreeMap<TargetFile, Option<Value>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(files: &'a TargetFiles) -> Reader<'a> {
+        Reader {
+            files,
+            documents: BTreeMap::new(),
+        }
+    }
+
+    fn document(
+        &mut self,
+        scope: &MappingScope<'a>,
+        target: &RawTarget,
+        path: &PathBuf,
+    ) -> Result<Option<&Value>, Error> {
+        let file = target_file(target, scope.filename(), path)?;
+
+        if !self.documents.contains_key(&file) {
+            let document = match self.files.get(&file) {
+                Some(content) => Some(
+                    format::deserialize(*target.format(), &file, content)
+                        .map_err(|e| Error::message(format!("{}: {}", file, e)))?,
+                ),
+                None => None,
+            };
+            self.documents.insert(file.clone(), document);
+        }
+
+        Ok(self.documents[&file].as_ref())
+    }
+
+    // Every file belonging to the items fileset target is one array item
+    fn read_file_set_items(
+        &mut self,
+        scope: &MappingScope<'a>,
+        items_schema: &'a Schema,
+        path: &PathBuf,
+    ) -> Result<Option<Vec<Value>>, Error> {
+        let target = match scope
+            .scope_with_schema(items_schema)
+            .map_err(|e| Error::at_path(path, e.to_string()))?
+            .target()
+        {
+            Some(target) if target.type_().is_file_set() && has_own_target(items_schema) => target,
+            _ => return Ok(None),
+        };
+
+        let filenames: Vec<String> = self
+            .files
+            .iter()
+            .filter(|(file, _)| file.belongs_to(target))
+            .filter_map(|(file, _)| file.filename().map(str::to_string))
+            .collect();
+
+        if filenames.is_empty() {
+            return Ok(None);
+        }
+
+        let mut items = vec![];
+
+        for (idx, filename) in filenames.into_iter().enumerate() {
+            let mut item_scope = scope.clone();
+            item_scope.set_filename(filename);
+
+            let mut nested_path = path.clone();
+            nested_path.push_index(idx);
+
+            match self.read_schema(&item_scope, items_schema, &nested_path)? {
+                Some(item) => items.push(item),
+                None if items_schema.r#type().primitive_type() == &PrimitiveType::Object => {
+                    items.push(Value::Object(Map::new()))
+                }
+                None => {}
+            };
+        }
+
+        Ok(Some(items))
+    }
+
+    fn read_schema(
+        &mut self,
+        scope: &MappingScope<'a>,
+        schema: &'a Schema,
+        path: &PathBuf,
+    ) -> Result<Option<Value>, Error> {
+        let mut scope = scope
+            .scope_with_schema(schema)
+            .map_err(|e| Error::at_path(path, e.to_string()))?;
+        let mapping = schema.mapping();
+
+        // File name formulas can't be evaluated without data, file names are discovered
+        // via the fileset target glob instead
+        if let Some(FileName::Name(filename)) = mapping.and_then(Mapping::filename) {
+            scope.set_filename(filename.clone());
+        }
+
+        if let Some(mapping_path) = mapping.and_then(Mapping::path) {
+            let target = scope
+                .target()
+                .ok_or_else(|| Error::at_path(path, format!("mapping path '{}' without a target", mapping_path)))?;
+            return Ok(self
+                .document(&scope, target, path)?
+                .and_then(|document| value::get(document, mapping_path))
+                .map(|value| format::coerce(*target.format(), schema, value.clone())));
+        }
+
+        if !schema.properties().is_empty() {
+            let mut object = Map::new();
+
+            for property in schema.properties() {
+                let mut nested_path = path.clone();
+                nested_path.push_property(property.name());
+
+                if let Some(value) = self.read_schema(&scope, property.schema(), &nested_path)? {
+                    object.insert(property.name().to_string(), value);
+                }
+            }
+
+            if object.is_empty() {
+                return Ok(None);
+            }
+
+            return Ok(Some(Value::Object(object)));
+        }
+
+        if let [items_schema] = schema.items() {
+            if let Some(items) = self.read_file_set_items(&scope, items_schema, path)? {
+                return Ok(Some(Value::Array(items)));
+            }
+        }
+
+        if has_own_target(schema) {
+            let target = scope.target().expect("invalid has_own_target");
+            return Ok(self
+                .document(&scope, target, path)?
+                .map(|document| format::coerce(*target.format(), schema, document.clone())));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Reconstructs configuration data from target files
+///
+/// It's an inverse operation to [`render`](fn.render.html). Properties are read from
+/// target documents at the `mapping.path`, target documents values not mapped to
+/// any property (`mapping.template` values for example) are ignored. Missing target
+/// files are not considered as an error, properties mapped to them are missing.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `files` - Target files
+pub fn read(schema: &Schema, files: &TargetFiles) -> Result<Value, Error> {
+    let mut reader = Reader::new(files);
+
+    match reader.read_schema(&MappingScope::new(), schema, &PathBuf::new())? {
+        Some(value) => Ok(value),
+        None if schema.r#type().primitive_type() == &PrimitiveType::Object => Ok(Value::Object(Map::new())),
+        None => Ok(Value::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mapping::render,
+        schema::mapping::{LocationPartition, TargetLocation},
+    };
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+        mapping:
+          targets:
+            config-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /config.json
+            other-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /other.json
+        properties:
+          - advanced:
+              mapping:
+                target: config-json
+              properties:
+                - hostname:
+                    type: hostname
+                    mapping:
+                      path: hostname
+                - udevRules:
+                    type: object
+                    mapping:
+                      path: os.udevRules
+                - country:
+                    type: string?
+                    mapping:
+                      target: other-json
+                      path: wifi.country
+          - dnsServers:
+              type: array?
+              items:
+                type: string
+              mapping:
+                target: other-json
+                path: dnsServers
+    "#;
+
+    fn file(path: &str) -> TargetFile {
+        TargetFile::new(
+            TargetLocation::new(LocationPartition::Label("resin-boot".to_string()), path),
+            None,
+        )
+    }
+
+    fn files(files: &[(&str, Value)]) -> TargetFiles {
+        let mut result = TargetFiles::new();
+        for (path, content) in files {
+            result.insert(file(path), serde_json::to_vec(content).unwrap());
+        }
+        result
+    }
+
+    #[test]
+    fn read_mapped_values() {
+        let schema: Schema = SCHEMA.parse().unwrap();
+        let files = files(&[
+            (
+                "/config.json",
+                json!({"hostname": "balena", "os": {"udevRules": {"56": "foo"}}, "apiKey": "secret"}),
+            ),
+            ("/other.json", json!({"wifi": {"country": "CZ"}})),
+        ]);
+
+        assert_eq!(
+            read(&schema, &files).unwrap(),
+            json!({
+                "advanced": {
+                    "hostname": "balena",
+                    "udevRules": {"56": "foo"},
+                    "country": "CZ"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn missing_files() {
+        let schema: Schema = SCHEMA.parse().unwrap();
+        assert_eq!(read(&schema, &TargetFiles::new()).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn render_read_round_trip() {
+        let schema: Schema = SCHEMA.parse().unwrap();
+        let data = json!({
+            "advanced": {
+                "hostname": "balena",
+                "udevRules": {"56": "foo"},
+                "country": "CZ"
+            },
+            "dnsServers": ["8.8.8.8", "1.1.1.1"]
+        });
+
+        let files = render(&schema, &data).unwrap();
+        assert_eq!(read(&schema, &files).unwrap(), data);
+    }
+
+    #[test]
+    fn own_target_without_path() {
+        let schema = r#"
+        properties:
+          - network:
+              type: object
+              mapping:
+                target:
+                  type: file
+                  format: json
+                  location:
+                    partition: resin-boot
+                    path: /network.json
+        "#;
+        let schema: Schema = schema.parse().unwrap();
+        let files = files(&[("/network.json", json!({"ssid": "balena"}))]);
+
+        assert_eq!(read(&schema, &files).unwrap(), json!({"network": {"ssid": "balena"}}));
+    }
+
+    #[test]
+    fn ini_render_read_round_trip() {
+        let schema = r#"
+        properties:
+          - wifi:
+              mapping:
+                target:
+                  type: file
+                  format: ini
+                  location:
+                    partition: resin-boot
+                    path: /system-connections/balena-wifi
+                template:
+                  connection:
+                    type: wifi
+                  wifi:
+                    mode: infrastructure
+              properties:
+                - ssid:
+                    type: string
+                    mapping:
+                      path: wifi.ssid
+                - hidden:
+                    type: boolean
+                    mapping:
+                      path: wifi.hidden
+                - password:
+                    type: password
+                    mapping:
+                      path: wifi-security.psk
+                - dns:
+                    type: array
+                    items:
+                      type: string
+                    mapping:
+                      path: ipv4.dns
+        "#;
+        let schema: Schema = schema.parse().unwrap();
+        let data = json!({
+            "wifi": {
+                "ssid": "1234",
+                "hidden": true,
+                "password": "secret",
+                "dns": ["8.8.8.8", "1.1.1.1"]
+            }
+        });
+
+        let files = render(&schema, &data).unwrap();
+        assert_eq!(
+            std::str::from_utf8(files.find("/system-connections/balena-wifi").unwrap()).unwrap(),
+            "[connection]\ntype=wifi\n\n\
+             [ipv4]\ndns=8.8.8.8;1.1.1.1;\n\n\
+             [wifi]\nhidden=true\nmode=infrastructure\nssid=1234\n\n\
+             [wifi-security]\npsk=secret\n"
+        );
+        assert_eq!(read(&schema, &files).unwrap(), data);
+    }
+
+    #[test]
+    fn redsocks_render_read_round_trip() {
+        let schema = r#"
+        properties:
+          - redsocks:
+              properties:
+                - proxyType:
+                    type: string
+                    mapping:
+                      path: redsocks.type
+                - server:
+                    type: ipv4
+                    mapping:
+                      path: redsocks.ip
+                - port:
+                    type: port
+                    mapping:
+                      path: redsocks.port
+              mapping:
+                target:
+                  type: file
+                  format: redsocks
+                  location:
+                    partition: resin-boot
+                    path: /system-proxy/redsocks.conf
+                template:
+                  base:
+                    log: stderr
+                  redsocks:
+                    local_ip: 127.0.0.1
+        "#;
+        let schema: Schema = schema.parse().unwrap();
+        let data = json!({"redsocks": {"proxyType": "socks5", "server": "10.0.0.1", "port": 1080}});
+
+        let files = render(&schema, &data).unwrap();
+        assert_eq!(
+            std::str::from_utf8(files.find("/system-proxy/redsocks.conf").unwrap()).unwrap(),
+            "base {\n    log = stderr;\n}\n\n\
+             redsocks {\n    ip = 10.0.0.1;\n    local_ip = 127.0.0.1;\n    port = 1080;\n    type = socks5;\n}\n"
+        );
+        assert_eq!(read(&schema, &files).unwrap(), data);
+    }
+
+    #[test]
+    fn text_and_binary_render_read_round_trip() {
+        let schema = r#"
+        properties:
+          - logo:
+              type: file?
+              mapping:
+                target:
+                  type: file
+                  format: binary
+                  location:
+                    partition: resin-boot
+                    path: /splash/resin-logo.png
+          - proxyWhitelistIPs:
+              type: stringlist
+              separator: \n
+              items:
+                type: iptables-address
+              mapping:
+                target:
+                  type: file
+                  format: text
+                  location:
+                    partition: resin-boot
+                    path: /system-proxy/no_proxy
+        "#;
+        let schema: Schema = schema.parse().unwrap();
+        let data = json!({
+            "logo": "data:image/png;name=resin-logo.png;base64,iVBORw0KGgo=",
+            "proxyWhitelistIPs": ["10.0.0.0/8", "192.168.1.1"]
+        });
+
+        let files = render(&schema, &data).unwrap();
+        assert_eq!(
+            files.find("/splash/resin-logo.png").unwrap(),
+            &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'][..]
+        );
+        assert_eq!(
+            files.find("/system-proxy/no_proxy").unwrap(),
+            &b"10.0.0.0/8\n192.168.1.1\n"[..]
+        );
+        assert_eq!(read(&schema, &files).unwrap(), data);
+    }
+
+    #[test]
+    fn file_set_render_read_round_trip() {
+        let schema = r#"
+        properties:
+          - network:
+              type: array?
+              items:
+                type: object
+                properties:
+                  - id:
+                      type: string
+                      formula: super.ssid | SLUGIFY
+                      mapping:
+                        path: connection.id
+                  - ssid:
+                      type: string
+                      mapping:
+                        path: wifi.ssid
+                mapping:
+                  target:
+                    type: fileset
+                    format: ini
+                    location:
+                      partition: resin-boot
+                      path: /system-connections
+                  filename:
+                    formula: this.id
+        "#;
+        let schema: Schema = schema.parse().unwrap();
+        let data = json!({"network": [{"id": "home", "ssid": "Home"}, {"id": "office", "ssid": "Office"}]});
+
+        let mut files = render(&schema, &data).unwrap();
+        let mut foreign = TargetFile::new(
+            TargetLocation::new(LocationPartition::Label("resin-boot".to_string()), "/other"),
+            Some("foo".to_string()),
+        );
+        files.insert(foreign.clone(), b"[wifi]\nssid=Foo\n".to_vec());
+        assert_eq!(read(&schema, &files).unwrap(), data);
+
+        foreign = TargetFile::new(
+            TargetLocation::new(
+                LocationPartition::Label("resin-boot".to_string()),
+                "/system-connections",
+            ),
+            Some("cafe".to_string()),
+        );
+        files.insert(foreign, b"[connection]\nid=cafe\n[wifi]\nssid=Cafe\n".to_vec());
+        assert_eq!(
+            read(&schema, &files).unwrap(),
+            json!({"network": [
+                {"id": "cafe", "ssid": "Cafe"},
+                {"id": "home", "ssid": "Home"},
+                {"id": "office", "ssid": "Office"}
+            ]})
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_file_content() {
+        let schema: Schema = SCHEMA.parse().unwrap();
+        let mut files = TargetFiles::new();
+        files.insert(file("/config.json"), b"{".to_vec());
+
+        assert!(read(&schema, &files).is_err());
+    }
+}
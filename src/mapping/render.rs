@@ -0,0 +1,513 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    evaluator::evaluate_expression,
+    mapping::{
+        files::{TargetFile, TargetFiles},
+        format,
+        scope::{has_own_target, MappingScope},
+        target_file, value,
+    },
+    schema::{
+        mapping::{FileName, Mapping, RawTarget},
+        Schema,
+    },
+    validator::path::PathBuf,
+};
+
+/// Target document being rendered
+struct Document<'a> {
+    target: &'a RawTarget,
+    value: Value,
+}
+
+struct Renderer<'a> {
+    // Whole configuration data, file name formulas are evaluated against them
+    data: &'a Value,
+    documents: BTreeMap<TargetFile, Document<'a>>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(data: &'a Value) -> Renderer<'a> {
+        Renderer {
+            data,
+            documents: BTreeMap::new(),
+        }
+    }
+
+    fn document(
+        &mut self,
+        scope: &MappingScope<'a>,
+        target: &'a RawTarget,
+        path: &PathBuf,
+    ) -> Result<&mut Value, Error> {
+        let file = target_file(target, scope.filename(), path)?;
+
+        Ok(&mut self
+            .documents
+            .entry(file)
+            .or_insert_with(|| Document {
+                target,
+                value: Value::Null,
+            })
+            .value)
+    }
+
+    fn filename(&self, filename: &FileName, target: Option<&RawTarget>, path: &PathBuf) -> Result<String, Error> {
+        let filename = match filename {
+            FileName::Name(name) => name.clone(),
+            FileName::Formula(formula) => match evaluate_expression(formula, path, self.data)? {
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                _ => {
+                    return Err(Error::at_path(
+                        path,
+                        format!("file name formula '{}' must evaluate to a string", formula),
+                    ));
+                }
+            },
+        };
+
+        // File must stay in the target directory
+        if filename.is_empty() || filename == "." || filename == ".." || filename.contains(['/', '\0']) {
+            let message = match target {
+                Some(target) => format!(
+                    "invalid file name '{}' for the fileset target '{}'",
+                    filename,
+                    TargetFile::new(target.location().clone(), None)
+                ),
+                None => format!("invalid file name '{}'", filename),
+            };
+            return Err(Error::at_path(path, message));
+        }
+
+        Ok(filename)
+    }
+
+    fn render_schema(
+        &mut self,
+        scope: &MappingScope<'a>,
+        schema: &'a Schema,
+        data: Option<&Value>,
+        path: &PathBuf,
+    ) -> Result<(), Error> {
+        // Nothing to render, do not even apply templates
+        let data = match data {
+            Some(Value::Null) | None => return Ok(()),
+            Some(x) => x,
+        };
+
+        let mut scope = scope
+            .scope_with_schema(schema)
+            .map_err(|e| Error::at_path(path, e.to_string()))?;
+        let mapping = schema.mapping();
+
+        if let Some(filename) = mapping.and_then(Mapping::filename) {
+            scope.set_filename(self.filename(filename, scope.target(), path)?);
+        }
+
+        if let Some(template) = mapping.and_then(Mapping::template) {
+            let target = scope
+                .target()
+                .ok_or_else(|| Error::at_path(path, "mapping template without a target"))?;
+            value::merge_missing(self.document(&scope, target, path)?, template);
+        }
+
+        // Explicit path, the whole value belongs to the target, do not descend
+        if let Some(mapping_path) = mapping.and_then(Mapping::path) {
+            let target = scope
+                .target()
+                .ok_or_else(|| Error::at_path(path, format!("mapping path '{}' without a target", mapping_path)))?;
+            let data = format::prepare(*target.format(), schema, data.clone())
+                .map_err(|e| Error::at_path(path, e.to_string()))?;
+            return value::insert(self.document(&scope, target, path)?, mapping_path, data)
+                .map_err(|e| Error::at_path(path, e.to_string()));
+        }
+
+        if !schema.properties().is_empty() {
+            if let Some(object) = data.as_object() {
+                for property in schema.properties() {
+                    let mut nested_path = path.clone();
+                    nested_path.push_property(property.name());
+                    self.render_schema(&scope, property.schema(), object.get(property.name()), &nested_path)?;
+                }
+            }
+            return Ok(());
+        }
+
+        if let (Some(array), [items_schema]) = (data.as_array(), schema.items()) {
+            if items_schema.mapping().is_some() {
+                for (idx, item) in array.iter().enumerate() {
+                    let mut nested_path = path.clone();
+                    nested_path.push_index(idx);
+                    self.render_schema(&scope, items_schema, Some(item), &nested_path)?;
+                }
+                return Ok(());
+            }
+        }
+
+        // No path, but the target was selected by the schema itself, the whole value
+        // is the target document
+        if has_own_target(schema) {
+            let target = scope.target().expect("invalid has_own_target");
+            let data = format::prepare(*target.format(), schema, data.clone())
+                .map_err(|e| Error::at_path(path, e.to_string()))?;
+            return value::insert(self.document(&scope, target, path)?, "", data);
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders configuration data into target files
+///
+/// Data are expected to be valid (see [`validate`](../validator/fn.validate.html)) and with
+/// formulas evaluated (see [`evaluate_formulas`](../evaluator/fn.evaluate_formulas.html)).
+/// Properties without values are not rendered. Target files without any value are not
+/// rendered as well.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema
+/// * `data` - Configuration data
+pub fn render(schema: &Schema, data: &Value) -> Result<TargetFiles, Error> {
+    let mut renderer = Renderer::new(data);
+    renderer.render_schema(&MappingScope::new(), schema, Some(data), &PathBuf::new())?;
+
+    let mut files = TargetFiles::new();
+
+    for (file, document) in renderer.documents {
+        let content = format::serialize(*document.target.format(), &document.value)
+            .map_err(|e| Error::message(format!("{}: {}", file, e)))?;
+        files.insert(file, content);
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TARGETS: &str = r#"
+        mapping:
+          targets:
+            config-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /config.json
+            other-json:
+              type: file
+              format: json
+              location:
+                partition: resin-boot
+                path: /other.json
+    "#;
+
+    fn render_json(schema: &str, data: Value) -> Result<BTreeMap<String, Value>, Error> {
+        let schema: Schema = format!("{}{}", TARGETS, schema).parse().unwrap();
+        let files = render(&schema, &data)?;
+        Ok(files
+            .iter()
+            .map(|(file, content)| (file.path(), serde_json::from_slice(content).unwrap()))
+            .collect())
+    }
+
+    #[test]
+    fn inherit_target_from_parent() {
+        let schema = r#"
+        properties:
+          - advanced:
+              mapping:
+                target: config-json
+              properties:
+                - hostname:
+                    type: hostname
+                    mapping:
+                      path: hostname
+                - udevRules:
+                    type: object
+                    mapping:
+                      path: os.udevRules
+        "#;
+        let data = json!({"advanced": {"hostname": "balena", "udevRules": {"56": "foo"}}});
+        let files = render_json(schema, data).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files["/config.json"],
+            json!({"hostname": "balena", "os": {"udevRules": {"56": "foo"}}})
+        );
+    }
+
+    #[test]
+    fn override_inherited_target() {
+        let schema = r#"
+        properties:
+          - advanced:
+              mapping:
+                target: config-json
+              properties:
+                - hostname:
+                    type: hostname
+                    mapping:
+                      path: hostname
+                - country:
+                    type: string
+                    mapping:
+                      target: other-json
+                      path: wifi.country
+        "#;
+        let data = json!({"advanced": {"hostname": "balena", "country": "CZ"}});
+        let files = render_json(schema, data).unwrap();
+
+        assert_eq!(files["/config.json"], json!({"hostname": "balena"}));
+        assert_eq!(files["/other.json"], json!({"wifi": {"country": "CZ"}}));
+    }
+
+    #[test]
+    fn inherit_nearest_parent_target() {
+        let schema = r#"
+        properties:
+          - proxy:
+              mapping:
+                target: other-json
+              properties:
+                - redsocks:
+                    mapping:
+                      target: config-json
+                    properties:
+                      - port:
+                          type: port
+                          mapping:
+                            path: redsocks.port
+        "#;
+        let data = json!({"proxy": {"redsocks": {"port": 8080}}});
+        let files = render_json(schema, data).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files["/config.json"], json!({"redsocks": {"port": 8080}}));
+    }
+
+    #[test]
+    fn own_target_without_path_is_document_root() {
+        let schema = r#"
+        properties:
+          - dnsServers:
+              type: array
+              items:
+                type: string
+              mapping:
+                target: config-json
+        "#;
+        let data = json!({"dnsServers": ["8.8.8.8"]});
+        let files = render_json(schema, data).unwrap();
+
+        assert_eq!(files["/config.json"], json!(["8.8.8.8"]));
+    }
+
+    #[test]
+    fn merge_template() {
+        let schema = r#"
+        properties:
+          - redsocks:
+              properties:
+                - port:
+                    type: port
+                    mapping:
+                      path: redsocks.port
+              mapping:
+                target: config-json
+                template:
+                  base:
+                    log: stderr
+                  redsocks:
+                    port: 1080
+                    local_ip: 127.0.0.1
+        "#;
+        let data = json!({"redsocks": {"port": 8080}});
+        let files = render_json(schema, data).unwrap();
+
+        assert_eq!(
+            files["/config.json"],
+            json!({"base": {"log": "stderr"}, "redsocks": {"port": 8080, "local_ip": "127.0.0.1"}})
+        );
+    }
+
+    #[test]
+    fn skip_missing_values() {
+        let schema = r#"
+        properties:
+          - redsocks:
+              type: object?
+              properties:
+                - port:
+                    type: port
+                    mapping:
+                      path: redsocks.port
+              mapping:
+                target: config-json
+                template:
+                  base:
+                    log: stderr
+        "#;
+        let files = render_json(schema, json!({})).unwrap();
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn inline_target() {
+        let schema = r#"
+        properties:
+          - hostname:
+              type: hostname
+              mapping:
+                target:
+                  type: file
+                  format: json
+                  location:
+                    partition: 1
+                    path: /hostname.json
+                path: hostname
+        "#;
+        let files = render_json(schema, json!({"hostname": "balena"})).unwrap();
+        assert_eq!(files["/hostname.json"], json!({"hostname": "balena"}));
+    }
+
+    #[test]
+    fn file_set_item_per_file() {
+        let schema: Schema = r#"
+        properties:
+          - network:
+              type: array
+              items:
+                type: object
+                properties:
+                  - id:
+                      type: string
+                      mapping:
+                        path: connection.id
+                  - ssid:
+                      type: string
+                      mapping:
+                        path: wifi.ssid
+                mapping:
+                  target:
+                    type: fileset
+                    format: ini
+                    location:
+                      partition: resin-boot
+                      path: /system-connections
+                  filename:
+                    formula: this.id
+                  template:
+                    connection:
+                      type: wifi
+        "#
+        .parse()
+        .unwrap();
+        let data = json!({"network": [{"id": "home", "ssid": "Home"}, {"id": "office", "ssid": "Office"}]});
+        let files = render(&schema, &data).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            files.find("/system-connections/home").unwrap(),
+            &b"[connection]\nid=home\ntype=wifi\n\n[wifi]\nssid=Home\n"[..]
+        );
+        assert_eq!(
+            files.find("/system-connections/office").unwrap(),
+            &b"[connection]\nid=office\ntype=wifi\n\n[wifi]\nssid=Office\n"[..]
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_file_name() {
+        let schema = |filename: &str| -> Schema {
+            format!(
+                r#"
+                properties:
+                  - networks:
+                      type: array
+                      items:
+                        type: string
+                        mapping:
+                          target:
+                            type: fileset
+                            format: text
+                            location:
+                              partition: resin-boot
+                              path: /networks
+                          {}
+                "#,
+                filename
+            )
+            .parse()
+            .unwrap()
+        };
+        let data = json!({"networks": ["foo/bar"]});
+
+        assert!(render(&schema(""), &data).is_err());
+        assert!(render(&schema("filename: ''"), &data).is_err());
+        assert!(render(&schema("filename: {formula: this}"), &data).is_err());
+        assert!(render(&schema("filename: {formula: 10 > 5}"), &data).is_err());
+        assert!(render(&schema("filename: '..'"), &data).is_err());
+        assert_eq!(
+            render(&schema("filename: {formula: this}"), &json!({"networks": [".."]}))
+                .unwrap_err()
+                .to_string(),
+            "'networks[0]': invalid file name '..' for the fileset target 'resin-boot:/networks'"
+        );
+        assert!(render(&schema("filename: {formula: this}"), &json!({"networks": ["."]})).is_err());
+        assert!(render(
+            &schema("filename: {formula: this ~ `.txt`}"),
+            &json!({"networks": ["foo"]})
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn fail_on_undeclared_target() {
+        let schema = r#"
+        properties:
+          - hostname:
+              type: hostname
+              mapping:
+                target: foo
+                path: hostname
+        "#;
+        assert!(render_json(schema, json!({"hostname": "balena"})).is_err());
+    }
+
+    #[test]
+    fn fail_on_path_without_target() {
+        let schema = r#"
+        properties:
+          - hostname:
+              type: hostname
+              mapping:
+                path: hostname
+        "#;
+        assert!(render_json(schema, json!({"hostname": "balena"})).is_err());
+    }
+
+    #[test]
+    fn ignore_properties_without_mapping() {
+        let schema = r#"
+        properties:
+          - advanced:
+              mapping:
+                target: config-json
+              properties:
+                - hostname:
+                    type: hostname
+        "#;
+        let files = render_json(schema, json!({"advanced": {"hostname": "balena"}})).unwrap();
+        assert!(files.is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    schema::{
+        mapping::{RawTarget, Target},
+        Schema,
+    },
+};
+
+/// Mapping scope
+///
+/// Tracks targets declared via `mapping.targets`, the target selected by
+/// the nearest `mapping.target` (the property itself or one of its parents)
+/// and the file name for `fileset` targets.
+#[derive(Debug, Clone, Default)]
+pub struct MappingScope<'a> {
+    targets: HashMap<&'a str, &'a RawTarget>,
+    target: Option<&'a RawTarget>,
+    filename: Option<String>,
+}
+
+impl<'a> MappingScope<'a> {
+    pub fn new() -> MappingScope<'a> {
+        MappingScope::default()
+    }
+
+    /// Returns target inherited by / selected for the current schema
+    pub fn target(&self) -> Option<&'a RawTarget> {
+        self.target
+    }
+
+    /// Returns file name inherited by / selected for the current schema
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    pub fn set_filename(&mut self, filename: String) {
+        self.filename = Some(filename);
+    }
+
+    /// Creates new scope for the given (nested) schema
+    ///
+    /// Targets declared in the `schema` are visible to the `schema` and all its
+    /// descendants. `mapping.target` of the `schema` overrides the inherited one.
+    pub fn scope_with_schema(&self, schema: &'a Schema) -> Result<MappingScope<'a>, Error> {
+        let mapping = match schema.mapping() {
+            Some(x) => x,
+            None => return Ok(self.clone()),
+        };
+
+        let mut scope = self.clone();
+
+        for (name, target) in mapping.targets() {
+            scope.targets.insert(name, target);
+        }
+
+        match mapping.target() {
+            Some(Target::Reference(name)) => {
+                let target = scope
+                    .targets
+                    .get(name.as_str())
+                    .ok_or_else(|| Error::message(format!("undeclared mapping target '{}'", name)))?;
+                scope.target = Some(target);
+            }
+            Some(Target::Raw(target)) => scope.target = Some(target),
+            None => {}
+        };
+
+        Ok(scope)
+    }
+}
+
+/// Checks if the schema selects a target on its own (doesn't inherit it)
+pub fn has_own_target(schema: &Schema) -> bool {
+    schema.mapping().and_then(|x| x.target()).is_some()
+}
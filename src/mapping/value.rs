@@ -0,0 +1,131 @@
+//! Target document helpers
+//!
+//! Mapping paths are dotted paths (`wifi-security.psk`) pointing to a value
+//! inside a target document.
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('.').filter(|x| !x.is_empty())
+}
+
+/// Inserts a value into the document at the given path
+///
+/// Missing intermediate objects are created. Existing objects are merged with
+/// the `value` (`value` wins), any other existing value is replaced.
+pub fn insert(document: &mut Value, path: &str, value: Value) -> Result<(), Error> {
+    let mut current = document;
+
+    for component in components(path) {
+        if current.is_null() {
+            *current = Value::Object(Map::new());
+        }
+
+        current = match current {
+            Value::Object(map) => map.entry(component).or_insert(Value::Null),
+            _ => {
+                return Err(Error::message(format!(
+                    "unable to map path '{}': '{}' parent is not an object",
+                    path, component
+                )));
+            }
+        };
+    }
+
+    if current.is_object() && value.is_object() {
+        merge(current, &value);
+    } else {
+        *current = value;
+    }
+
+    Ok(())
+}
+
+/// Returns a value from the document at the given path
+pub fn get<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    components(path).try_fold(document, |current, component| current.get(component))
+}
+
+/// Deeply merges `other` into the `document`, values from `other` win
+pub fn merge(document: &mut Value, other: &Value) {
+    match (document, other) {
+        (Value::Object(document), Value::Object(other)) => {
+            for (k, v) in other {
+                match document.get_mut(k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        document.insert(k.clone(), v.clone());
+                    }
+                };
+            }
+        }
+        (document, other) => *document = other.clone(),
+    }
+}
+
+/// Deeply merges `other` into the `document`, existing `document` values win
+pub fn merge_missing(document: &mut Value, other: &Value) {
+    match (document, other) {
+        (Value::Object(document), Value::Object(other)) => {
+            for (k, v) in other {
+                match document.get_mut(k) {
+                    Some(existing) => merge_missing(existing, v),
+                    None => {
+                        document.insert(k.clone(), v.clone());
+                    }
+                };
+            }
+        }
+        (document, other) if document.is_null() => *document = other.clone(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn insert_creates_intermediate_objects() {
+        let mut document = Value::Null;
+        insert(&mut document, "wifi-security.psk", json!("secret")).unwrap();
+        assert_eq!(document, json!({"wifi-security": {"psk": "secret"}}));
+    }
+
+    #[test]
+    fn insert_at_root() {
+        let mut document = Value::Null;
+        insert(&mut document, "", json!("foo")).unwrap();
+        assert_eq!(document, json!("foo"));
+    }
+
+    #[test]
+    fn insert_merges_objects() {
+        let mut document = json!({"os": {"foo": "bar"}});
+        insert(&mut document, "os", json!({"udevRules": {}})).unwrap();
+        assert_eq!(document, json!({"os": {"foo": "bar", "udevRules": {}}}));
+    }
+
+    #[test]
+    fn insert_fails_on_non_object_parent() {
+        let mut document = json!({"wifi": "foo"});
+        assert!(insert(&mut document, "wifi.ssid", json!("bar")).is_err());
+    }
+
+    #[test]
+    fn get_nested() {
+        let document = json!({"redsocks": {"port": 8080}});
+        assert_eq!(get(&document, "redsocks.port"), Some(&json!(8080)));
+        assert_eq!(get(&document, "redsocks.ip"), None);
+        assert_eq!(get(&document, ""), Some(&document));
+    }
+
+    #[test]
+    fn merge_missing_keeps_existing_values() {
+        let mut document = json!({"base": {"log": "file"}});
+        merge_missing(&mut document, &json!({"base": {"log": "stderr", "log_info": "on"}}));
+        assert_eq!(document, json!({"base": {"log": "file", "log_info": "on"}}));
+    }
+}
@@ -0,0 +1,380 @@
+//! A module containing configuration data migrations
+//!
+//! Configuration data stored for the older schema revision are upgraded with the
+//! schema `migrations` (see [`Migration`](../schema/struct.Migration.html)). All the
+//! migrations newer than the data revision are applied in the revision order and
+//! the result is validated against the schema.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::migration::migrate;
+//! use jellyschema::schema::Schema;
+//! use serde_json::json;
+//!
+//! let schema: Schema = r#"
+//!   version: 2
+//!   revision: 3
+//!   properties:
+//!     - host:
+//!         type: hostname
+//!     - advanced:
+//!         properties:
+//!           - ntp:
+//!               type: string-list
+//!   migrations:
+//!     - revision: 2
+//!       operations:
+//!         - move:
+//!             from: hostname
+//!             to: host
+//!     - revision: 3
+//!       operations:
+//!         - move:
+//!             from: ntp
+//!             to: advanced.ntp
+//!         - convert:
+//!             path: advanced.ntp
+//!             type: string-list
+//! "#.parse().unwrap();
+//!
+//! let data = json!({"hostname": "balena", "ntp": "0.pool.ntp.org 1.pool.ntp.org"});
+//! let migrated = migrate(&schema, &data, 1).unwrap();
+//!
+//! assert_eq!(
+//!     migrated,
+//!     json!({"host": "balena", "advanced": {"ntp": ["0.pool.ntp.org", "1.pool.ntp.org"]}})
+//! );
+//! ```
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Error,
+    schema::{Operation, PrimitiveType, Schema},
+    validator::validate,
+};
+
+fn components(path: &str) -> Vec<&str> {
+    path.split('.').filter(|x| !x.is_empty()).collect()
+}
+
+fn get_mut<'a>(data: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    components(path)
+        .into_iter()
+        .try_fold(data, |value, name| value.as_object_mut()?.get_mut(name))
+}
+
+fn remove(data: &mut Value, path: &str) -> Option<Value> {
+    let mut components = components(path);
+    let name = components.pop()?;
+
+    components
+        .into_iter()
+        .try_fold(data, |value, name| value.as_object_mut()?.get_mut(name))?
+        .as_object_mut()?
+        .remove(name)
+}
+
+// Inserts the value, missing objects are created
+fn insert(data: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let mut components = components(path);
+    let name = components.pop().ok_or_else(|| "empty path".to_string())?;
+
+    let mut parent = data;
+    for component in components {
+        if parent.is_null() {
+            *parent = Value::Object(Map::new());
+        }
+        parent = parent
+            .as_object_mut()
+            .ok_or_else(|| format!("'{}' is not an object", component))?
+            .entry(component)
+            .or_insert(Value::Null);
+    }
+
+    if parent.is_null() {
+        *parent = Value::Object(Map::new());
+    }
+    parent
+        .as_object_mut()
+        .ok_or_else(|| "parent is not an object".to_string())?
+        .insert(name.to_string(), value);
+    Ok(())
+}
+
+fn as_string(value: &Value, separator: &str) -> Option<String> {
+    match value {
+        Value::String(x) => Some(x.clone()),
+        Value::Number(x) => Some(x.to_string()),
+        Value::Bool(x) => Some(x.to_string()),
+        Value::Array(items) => items
+            .iter()
+            .map(|x| as_string(x, separator))
+            .collect::<Option<Vec<_>>>()
+            .map(|x| x.join(separator)),
+        _ => None,
+    }
+}
+
+fn convert(value: &Value, primitive_type: &PrimitiveType, separator: Option<&str>) -> Option<Value> {
+    match primitive_type {
+        PrimitiveType::Object => None,
+        PrimitiveType::Array | PrimitiveType::StringList => match value {
+            Value::Array(_) => Some(value.clone()),
+            Value::String(x) => {
+                let items: Vec<&str> = match separator {
+                    Some(separator) => x.split(separator).map(str::trim).filter(|x| !x.is_empty()).collect(),
+                    None => x.split_whitespace().collect(),
+                };
+                Some(Value::Array(items.into_iter().map(Value::from).collect()))
+            }
+            Value::Null | Value::Object(_) => None,
+            _ => Some(Value::Array(vec![value.clone()])),
+        },
+        PrimitiveType::Boolean => match value {
+            Value::Bool(_) => Some(value.clone()),
+            Value::String(x) => x.trim().parse::<bool>().ok().map(Value::Bool),
+            _ => None,
+        },
+        PrimitiveType::Integer | PrimitiveType::Port => match value {
+            Value::Number(x) if x.is_i64() || x.is_u64() => Some(value.clone()),
+            Value::Number(x) => x.as_f64().filter(|x| x.fract() == 0.0).map(|x| Value::from(x as i64)),
+            Value::String(x) => x.trim().parse::<i64>().ok().map(Value::from),
+            _ => None,
+        },
+        PrimitiveType::Number => match value {
+            Value::Number(_) => Some(value.clone()),
+            Value::String(x) => x
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            _ => None,
+        },
+        // All other types are string based
+        _ => as_string(value, separator.unwrap_or(" ")).map(Value::String),
+    }
+}
+
+fn apply(data: &mut Value, operation: &Operation) -> Result<(), String> {
+    match operation {
+        Operation::Move { from, to } => {
+            // Nothing to move, the data can be migrated already
+            if get_mut(data, from).is_none() {
+                return Ok(());
+            }
+            if get_mut(data, to).is_some() {
+                return Err(format!("'{}': value already exists", to));
+            }
+            if let Some(value) = remove(data, from) {
+                insert(data, to, value).map_err(|e| format!("'{}': {}", to, e))?;
+            }
+        }
+        Operation::Remove(path) => {
+            remove(data, path);
+        }
+        Operation::Convert {
+            path,
+            r#type,
+            separator,
+        } => {
+            if let Some(value) = get_mut(data, path) {
+                *value = convert(value, r#type, separator.as_deref())
+                    .ok_or_else(|| format!("'{}': unable to convert {} to '{}'", path, value, r#type))?;
+            }
+        }
+        Operation::Set { path, value } => {
+            insert(data, path, value.clone()).map_err(|e| format!("'{}': {}", path, e))?;
+        }
+    };
+
+    Ok(())
+}
+
+/// Migrates configuration data to the schema revision
+///
+/// Applies all the schema migrations newer than the data `revision` and validates
+/// the result. Missing values are skipped by all operations except `set`. Schema
+/// without the `revision` keyword is at the revision `0`.
+///
+/// # Arguments
+///
+/// * `schema` - JellySchema with migrations
+/// * `data` - Configuration data
+/// * `revision` - Schema revision the data were stored with
+pub fn migrate(schema: &Schema, data: &Value, revision: u32) -> Result<Value, Error> {
+    let current = schema.revision().unwrap_or_default();
+
+    if revision > current {
+        return Err(Error::message(format!(
+            "data revision {} is newer than the schema revision {}",
+            revision, current
+        )));
+    }
+
+    let mut migrations: Vec<_> = schema.migrations().iter().filter(|x| x.revision() > revision).collect();
+    migrations.sort_by_key(|x| x.revision());
+
+    if let Some(migration) = migrations.iter().find(|x| x.revision() > current) {
+        return Err(Error::message(format!(
+            "migration to the revision {} is newer than the schema revision {}",
+            migration.revision(),
+            current
+        )));
+    }
+
+    let mut result = data.clone();
+
+    for migration in migrations {
+        for operation in migration.operations() {
+            apply(&mut result, operation)
+                .map_err(|e| Error::message(format!("migration to the revision {}: {}", migration.revision(), e)))?;
+        }
+    }
+
+    let state = validate(schema, &result);
+    if let Some(error) = state.errors().first() {
+        return Err(Error::message(format!(
+            "migrated data are not valid: '{}': {}",
+            error.data_path(),
+            error.message()
+        )));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const SCHEMA: &str = r#"
+    version: 2
+    revision: 3
+    properties:
+      - host:
+          type: hostname
+      - port:
+          type: port?
+      - advanced:
+          type: object?
+          properties:
+            - enabled:
+                type: boolean
+            - ntp:
+                type: string-list?
+    migrations:
+      - revision: 3
+        operations:
+          - convert:
+              path: port
+              type: port
+          - set:
+              path: advanced.enabled
+              value: true
+      - revision: 2
+        operations:
+          - move:
+              from: hostname
+              to: host
+          - remove: legacy
+          - move:
+              from: ntp
+              to: advanced.ntp
+          - convert:
+              path: advanced.ntp
+              type: string-list
+              separator: ","
+    "#;
+
+    fn schema() -> Schema {
+        SCHEMA.parse().unwrap()
+    }
+
+    #[test]
+    fn migrate_from_first_revision() {
+        let data = json!({"hostname": "balena", "legacy": 1, "port": "8080", "ntp": "a, b,"});
+        assert_eq!(
+            migrate(&schema(), &data, 1).unwrap(),
+            json!({"host": "balena", "port": 8080, "advanced": {"enabled": true, "ntp": ["a", "b"]}})
+        );
+    }
+
+    #[test]
+    fn apply_newer_migrations_only() {
+        let data = json!({"host": "balena", "port": "8080"});
+        assert_eq!(
+            migrate(&schema(), &data, 2).unwrap(),
+            json!({"host": "balena", "port": 8080, "advanced": {"enabled": true}})
+        );
+    }
+
+    #[test]
+    fn current_revision_is_validated() {
+        let data = json!({"host": "balena"});
+        assert_eq!(migrate(&schema(), &data, 3).unwrap(), data);
+
+        let error = migrate(&schema(), &json!({"hostname": "balena"}), 3).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "migrated data are not valid: 'host': 'hostname' is not an optional type"
+        );
+    }
+
+    #[test]
+    fn fail_on_newer_data() {
+        let error = migrate(&schema(), &json!({}), 4).unwrap_err();
+        assert_eq!(error.to_string(), "data revision 4 is newer than the schema revision 3");
+    }
+
+    #[test]
+    fn fail_on_invalid_conversion() {
+        let data = json!({"hostname": "balena", "port": "http"});
+        let error = migrate(&schema(), &data, 2).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "migration to the revision 3: 'port': unable to convert \"http\" to 'port'"
+        );
+    }
+
+    #[test]
+    fn migrate_twice() {
+        let data = json!({"hostname": "balena", "legacy": 1, "port": "8080", "ntp": "a, b,"});
+        let migrated = migrate(&schema(), &data, 1).unwrap();
+        assert_eq!(migrate(&schema(), &migrated, 1).unwrap(), migrated);
+    }
+
+    #[test]
+    fn fail_on_existing_move_target() {
+        let data = json!({"hostname": "balena", "host": "balena"});
+        let error = migrate(&schema(), &data, 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "migration to the revision 2: 'host': value already exists"
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(
+            convert(&json!(["a", "b"]), &PrimitiveType::String, None),
+            Some(json!("a b"))
+        );
+        assert_eq!(
+            convert(&json!(["a", "b"]), &PrimitiveType::String, Some(",")),
+            Some(json!("a,b"))
+        );
+        assert_eq!(
+            convert(&json!("true"), &PrimitiveType::Boolean, None),
+            Some(json!(true))
+        );
+        assert_eq!(convert(&json!(1.0), &PrimitiveType::Integer, None), Some(json!(1)));
+        assert_eq!(convert(&json!("1.5"), &PrimitiveType::Number, None), Some(json!(1.5)));
+        assert_eq!(convert(&json!("a"), &PrimitiveType::Array, None), Some(json!(["a"])));
+        assert_eq!(convert(&json!({}), &PrimitiveType::String, None), None);
+        assert_eq!(convert(&json!("a"), &PrimitiveType::Object, None), None);
+    }
+}
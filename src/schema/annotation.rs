@@ -0,0 +1,185 @@
+//! Localized annotations
+//!
+//! Annotation keywords (`title`, `help`, `warning`, `description`, `placeholder`) and enum
+//! entry titles are either a string or a mapping of locales to strings. The mapping must
+//! contain the English (`en`) variant, which is used when the requested locale (`pt-BR`)
+//! and its language (`pt`) are missing.
+//!
+//! ```yaml
+//! title:
+//!   en: Network
+//!   de: Netzwerk
+//!   pt-BR: Rede
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{de, ser};
+use serde_json::Value;
+
+use crate::locale::{self, DEFAULT_LOCALE};
+
+/// Annotation text with optional per-locale variants
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    // Always contains the `DEFAULT_LOCALE` variant
+    variants: BTreeMap<String, String>,
+}
+
+impl Annotation {
+    /// Creates a new annotation without localized variants
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - English text
+    pub fn new<S>(text: S) -> Annotation
+    where
+        S: Into<String>,
+    {
+        let mut variants = BTreeMap::new();
+        variants.insert(DEFAULT_LOCALE.to_string(), text.into());
+        Annotation { variants }
+    }
+
+    /// Adds the localized variant, an existing variant is replaced
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    /// * `text` - Localized text
+    pub fn with_variant<L, S>(mut self, locale: L, text: S) -> Annotation
+    where
+        L: AsRef<str>,
+        S: Into<String>,
+    {
+        self.variants.insert(locale::normalize(locale.as_ref()), text.into());
+        self
+    }
+
+    /// English text
+    pub fn text(&self) -> &str {
+        &self.variants[DEFAULT_LOCALE]
+    }
+
+    /// Text in the given locale
+    ///
+    /// Falls back to the locale language and then to English.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    pub fn get(&self, locale: &str) -> &str {
+        locale::fallbacks(locale)
+            .iter()
+            .find_map(|x| self.variants.get(x))
+            .unwrap_or_else(|| &self.variants[DEFAULT_LOCALE])
+    }
+
+    /// Locales of all variants including English
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.variants.keys().map(String::as_str)
+    }
+
+    /// Returns `true` if the annotation has any non English variant
+    pub fn is_localized(&self) -> bool {
+        self.variants.len() > 1
+    }
+}
+
+impl From<String> for Annotation {
+    fn from(text: String) -> Annotation {
+        Annotation::new(text)
+    }
+}
+
+impl From<&str> for Annotation {
+    fn from(text: &str) -> Annotation {
+        Annotation::new(text)
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Annotation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::String(s) => Ok(Annotation::new(s)),
+            // Plain YAML scalars (`title: 2019`) are accepted as strings
+            Value::Bool(b) => Ok(Annotation::new(b.to_string())),
+            Value::Number(n) => Ok(Annotation::new(n.to_string())),
+            Value::Object(m) => {
+                let mut variants = BTreeMap::new();
+
+                for (locale, text) in m {
+                    match text {
+                        Value::String(s) => variants.insert(locale::normalize(&locale), s),
+                        _ => return Err(de::Error::custom(format!("'{}' variant is not a string", locale))),
+                    };
+                }
+
+                if !variants.contains_key(DEFAULT_LOCALE) {
+                    return Err(de::Error::custom(format!("missing '{}' variant", DEFAULT_LOCALE)));
+                }
+
+                Ok(Annotation { variants })
+            }
+            _ => Err(de::Error::custom(
+                "expected a string or a mapping of locales to strings",
+            )),
+        }
+    }
+}
+
+impl ser::Serialize for Annotation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        if self.is_localized() {
+            self.variants.serialize(serializer)
+        } else {
+            serializer.serialize_str(self.text())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_string() {
+        let annotation: Annotation = serde_yaml::from_str("Network").unwrap();
+        assert_eq!(annotation.text(), "Network");
+        assert_eq!(annotation.get("de"), "Network");
+        assert!(!annotation.is_localized());
+        assert_eq!(
+            serde_json::to_value(&annotation).unwrap(),
+            Value::String("Network".to_string())
+        );
+    }
+
+    #[test]
+    fn locale_fallback() {
+        let annotation: Annotation = serde_yaml::from_str("{en: Network, de: Netzwerk, pt_br: Rede}").unwrap();
+        assert_eq!(annotation.get("de-AT"), "Netzwerk");
+        assert_eq!(annotation.get("pt-BR"), "Rede");
+        assert_eq!(annotation.get("pt"), "Network");
+        assert_eq!(annotation.get("fr"), "Network");
+        assert_eq!(annotation.locales().collect::<Vec<_>>(), vec!["de", "en", "pt-BR"]);
+    }
+
+    #[test]
+    fn require_english_variant() {
+        assert!(serde_yaml::from_str::<Annotation>("{de: Netzwerk}").is_err());
+        assert!(serde_yaml::from_str::<Annotation>("{en: Network, de: 1}").is_err());
+        assert!(serde_yaml::from_str::<Annotation>("[Network]").is_err());
+    }
+}
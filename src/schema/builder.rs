@@ -0,0 +1,799 @@
+//! Programmatic schema construction
+//!
+//! Every keyword supported by the [`Schema`](struct.Schema.html) has a corresponding
+//! builder method. Keyword values are checked in the same way as when the schema is
+//! parsed and the first error is returned from the [`build`](struct.SchemaBuilder.html#method.build)
+//! method. References to definitions are resolved by the `build` method as well.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::Schema;
+//!
+//! let schema = Schema::builder()
+//!     .version(1)
+//!     .property(
+//!         "ssid",
+//!         Schema::builder().string().title("Network SSID").min_length(1).max_length(32),
+//!     )
+//!     .property("passphrase", Schema::builder().password().optional())
+//!     .build()
+//!     .unwrap();
+//!
+//! let parsed: Schema = r#"
+//!   version: 1
+//!   properties:
+//!     - ssid:
+//!         type: string
+//!         title: Network SSID
+//!         minLength: 1
+//!         maxLength: 32
+//!     - passphrase:
+//!         type: password?
+//! "#.parse().unwrap();
+//!
+//! assert_eq!(
+//!     serde_yaml::to_string(&schema).unwrap(),
+//!     serde_yaml::to_string(&parsed).unwrap()
+//! );
+//! ```
+use regex::Regex;
+use serde_json::{Number, Value};
+
+use crate::{
+    error::Error,
+    schema::{
+        definitions, mapping::Mapping, source, Annotation, EnumEntry, Migration, PrimitiveType, Property, Schema, Type,
+        UniqueItems, Version,
+    },
+};
+
+/// Schema builder
+///
+/// Created with the [`Schema::builder`](struct.Schema.html#method.builder) function.
+#[derive(Debug, Default)]
+pub struct SchemaBuilder {
+    schema: Schema,
+    error: Option<Error>,
+}
+
+impl SchemaBuilder {
+    /// Builds the schema, returns the first keyword error if there's any
+    pub fn build(self) -> Result<Schema, Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => {
+                let mut schema = self.schema;
+                // There's no schema source, `$ref` keywords are checked on the serialized schema
+                let source = serde_yaml::to_string(&schema).map_err(|e| Error::message(e.to_string()))?;
+                if let Some(root) = source::parse(&source)? {
+                    definitions::check_reference_keywords(&root)?;
+                }
+                schema.resolve_references()?;
+                Ok(schema)
+            }
+        }
+    }
+
+    // Builds the nested schema, the first error is kept
+    fn nested(&mut self, keyword: &str, builder: SchemaBuilder) -> Schema {
+        let SchemaBuilder { schema, error } = builder;
+
+        if let Some(error) = error {
+            self.fail(Error::message(format!("{}: {}", keyword, error)));
+        }
+
+        schema
+    }
+
+    fn fail(&mut self, error: Error) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    pub fn version(mut self, version: u8) -> SchemaBuilder {
+        match Version::new(version) {
+            Ok(version) => self.schema.version = Some(version),
+            Err(e) => self.fail(e),
+        };
+        self
+    }
+}
+
+//
+// Data migrations
+//
+impl SchemaBuilder {
+    /// Sets the schema revision (root schema only)
+    pub fn revision(mut self, revision: u32) -> SchemaBuilder {
+        self.schema.revision = Some(revision);
+        self
+    }
+
+    /// Adds a data migration to the revision (root schema only)
+    pub fn migration(mut self, migration: Migration) -> SchemaBuilder {
+        self.schema.migrations.push(migration);
+        self
+    }
+}
+
+//
+// Definitions & references
+//
+impl SchemaBuilder {
+    /// Adds a reusable subschema (root schema only)
+    pub fn definition<S>(mut self, name: S, schema: SchemaBuilder) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        let schema = self.nested(&format!("definitions.{}", name), schema);
+        self.schema.definitions.insert(name, schema);
+        self
+    }
+
+    /// References a definition by name (`$ref: "#/definitions/name"`)
+    pub fn reference<S>(mut self, name: S) -> SchemaBuilder
+    where
+        S: AsRef<str>,
+    {
+        self.schema.r#ref = Some(format!("#/definitions/{}", name.as_ref()));
+        self
+    }
+}
+
+//
+// Any instance type
+//
+impl SchemaBuilder {
+    pub fn r#type(mut self, r#type: Type) -> SchemaBuilder {
+        self.schema.r#type = r#type;
+        self
+    }
+
+    /// Sets the primitive type, optionality is kept
+    pub fn primitive_type(mut self, primitive_type: PrimitiveType) -> SchemaBuilder {
+        let optional = self.schema.r#type.is_optional();
+        self.schema.r#type = Type::new(primitive_type, optional);
+        self
+    }
+
+    /// Marks the type as optional (`type: string?`)
+    pub fn optional(mut self) -> SchemaBuilder {
+        let primitive_type = *self.schema.r#type.primitive_type();
+        self.schema.r#type = Type::new_optional(primitive_type);
+        self
+    }
+
+    pub fn object(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Object)
+    }
+
+    pub fn array(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Array)
+    }
+
+    pub fn boolean(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Boolean)
+    }
+
+    pub fn string(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::String)
+    }
+
+    pub fn password(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Password)
+    }
+
+    pub fn integer(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Integer)
+    }
+
+    pub fn number(self) -> SchemaBuilder {
+        self.primitive_type(PrimitiveType::Number)
+    }
+
+    pub fn r#const<V>(mut self, value: V) -> SchemaBuilder
+    where
+        V: Into<Value>,
+    {
+        self.schema.r#const = Some(value.into());
+        self
+    }
+
+    pub fn r#default<V>(mut self, value: V) -> SchemaBuilder
+    where
+        V: Into<Value>,
+    {
+        self.schema.r#default = Some(value.into());
+        self
+    }
+
+    /// Adds an enum entry without a title
+    ///
+    /// Fails for `null`, array and object values, use the [`enum_entry`](#method.enum_entry)
+    /// method instead.
+    pub fn enum_value<V>(mut self, value: V) -> SchemaBuilder
+    where
+        V: Into<Value>,
+    {
+        match EnumEntry::new(None, value.into()) {
+            Ok(entry) => self.schema.r#enum.push(entry),
+            Err(e) => self.fail(Error::message(format!("enum: {}", e))),
+        };
+        self
+    }
+
+    /// Adds an enum entry with a title
+    pub fn enum_entry<S, V>(mut self, title: S, value: V) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+        V: Into<Value>,
+    {
+        match EnumEntry::new(Some(title.into()), value.into()) {
+            Ok(entry) => self.schema.r#enum.push(entry),
+            Err(e) => self.fail(Error::message(format!("enum: {}", e))),
+        };
+        self
+    }
+
+    pub fn formula<S>(mut self, formula: S) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        self.schema.formula = Some(formula.into());
+        self
+    }
+
+    pub fn when<S>(mut self, when: S) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        self.schema.when = Some(when.into());
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> SchemaBuilder {
+        self.schema.read_only = read_only;
+        self
+    }
+
+    pub fn write_only(mut self, write_only: bool) -> SchemaBuilder {
+        self.schema.write_only = write_only;
+        self
+    }
+
+    pub fn placeholder<S>(mut self, placeholder: S) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+    {
+        self.schema.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> SchemaBuilder {
+        self.schema.hidden = hidden;
+        self
+    }
+}
+
+//
+// Mapping extension
+//
+impl SchemaBuilder {
+    /// Sets the mapping, see [`Mapping::builder`](mapping/struct.Mapping.html#method.builder)
+    pub fn mapping(mut self, mapping: Mapping) -> SchemaBuilder {
+        self.schema.mapping = Some(mapping);
+        self
+    }
+}
+
+//
+// Object validation keywords
+//
+impl SchemaBuilder {
+    /// Appends a property, properties are kept in the insertion order
+    pub fn property<S>(mut self, name: S, schema: SchemaBuilder) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+        let schema = self.nested(&format!("properties.{}", name), schema);
+        self.schema.properties.push(Property::new(name, schema));
+        self
+    }
+
+    pub fn keys(mut self, schema: SchemaBuilder) -> SchemaBuilder {
+        let schema = self.nested("keys", schema);
+        self.schema.keys = Some(Box::new(schema));
+        self
+    }
+
+    pub fn values(mut self, schema: SchemaBuilder) -> SchemaBuilder {
+        let schema = self.nested("values", schema);
+        self.schema.values = Some(Box::new(schema));
+        self
+    }
+
+    pub fn additional_properties(mut self, additional_properties: bool) -> SchemaBuilder {
+        self.schema.additional_properties = additional_properties;
+        self
+    }
+}
+
+//
+// StringList keywords
+//
+impl SchemaBuilder {
+    pub fn separator<S>(mut self, separator: S) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        self.schema.separator = Some(separator.into());
+        self
+    }
+}
+
+//
+// Annotation keywords
+//
+impl SchemaBuilder {
+    pub fn title<S>(mut self, title: S) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+    {
+        self.schema.title = Some(title.into());
+        self
+    }
+
+    pub fn help<S>(mut self, help: S) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+    {
+        self.schema.help = Some(help.into());
+        self
+    }
+
+    pub fn warning<S>(mut self, warning: S) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+    {
+        self.schema.warning = Some(warning.into());
+        self
+    }
+
+    pub fn description<S>(mut self, description: S) -> SchemaBuilder
+    where
+        S: Into<Annotation>,
+    {
+        self.schema.description = Some(description.into());
+        self
+    }
+
+    pub fn collapsible(mut self, collapsible: bool) -> SchemaBuilder {
+        self.schema.collapsible = Some(collapsible);
+        self
+    }
+
+    pub fn collapsed(mut self, collapsed: bool) -> SchemaBuilder {
+        self.schema.collapsed = Some(collapsed);
+        self
+    }
+}
+
+//
+// Array validation keywords
+//
+impl SchemaBuilder {
+    /// Appends an items schema, more items schemas means that an item must match one of them
+    pub fn items(mut self, schema: SchemaBuilder) -> SchemaBuilder {
+        let schema = self.nested("items", schema);
+        self.schema.items.push(schema);
+        self
+    }
+
+    /// Sets the property selecting the items schema (`discriminator`)
+    pub fn discriminator<S>(mut self, property: S) -> SchemaBuilder
+    where
+        S: Into<String>,
+    {
+        self.schema.discriminator = Some(property.into());
+        self
+    }
+
+    pub fn max_items(mut self, max_items: usize) -> SchemaBuilder {
+        self.schema.max_items = Some(max_items);
+        self
+    }
+
+    pub fn min_items(mut self, min_items: usize) -> SchemaBuilder {
+        self.schema.min_items = Some(min_items);
+        self
+    }
+
+    pub fn unique_items(mut self, unique_items: bool) -> SchemaBuilder {
+        self.schema.unique_items = UniqueItems::Boolean(unique_items);
+        self
+    }
+
+    /// Items must be unique in the given paths (`uniqueItems: [ssid]`)
+    pub fn unique_items_paths<I, S>(mut self, paths: I) -> SchemaBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let paths: Vec<String> = paths.into_iter().map(Into::into).collect();
+
+        // Same as when deserialized, empty sequence means no uniqueness
+        self.schema.unique_items = if paths.is_empty() {
+            UniqueItems::Boolean(false)
+        } else {
+            UniqueItems::Paths(paths)
+        };
+        self
+    }
+
+    pub fn orderable(mut self, orderable: bool) -> SchemaBuilder {
+        self.schema.orderable = Some(orderable);
+        self
+    }
+
+    pub fn addable(mut self, addable: bool) -> SchemaBuilder {
+        self.schema.addable = Some(addable);
+        self
+    }
+
+    pub fn removable(mut self, removable: bool) -> SchemaBuilder {
+        self.schema.removable = Some(removable);
+        self
+    }
+}
+
+//
+// Number validation keywords
+//
+impl SchemaBuilder {
+    pub fn multiple_of<N>(mut self, multiple_of: N) -> SchemaBuilder
+    where
+        N: Into<Number>,
+    {
+        self.schema.multiple_of = Some(multiple_of.into());
+        self
+    }
+
+    pub fn max<N>(mut self, max: N) -> SchemaBuilder
+    where
+        N: Into<Number>,
+    {
+        self.schema.max = Some(max.into());
+        self
+    }
+
+    pub fn min<N>(mut self, min: N) -> SchemaBuilder
+    where
+        N: Into<Number>,
+    {
+        self.schema.min = Some(min.into());
+        self
+    }
+
+    pub fn exclusive_max<N>(mut self, exclusive_max: N) -> SchemaBuilder
+    where
+        N: Into<Number>,
+    {
+        self.schema.exclusive_max = Some(exclusive_max.into());
+        self
+    }
+
+    pub fn exclusive_min<N>(mut self, exclusive_min: N) -> SchemaBuilder
+    where
+        N: Into<Number>,
+    {
+        self.schema.exclusive_min = Some(exclusive_min.into());
+        self
+    }
+}
+
+//
+// String based types validation keywords
+//
+impl SchemaBuilder {
+    pub fn max_length(mut self, max_length: usize) -> SchemaBuilder {
+        self.schema.max_length = Some(max_length);
+        self
+    }
+
+    pub fn min_length(mut self, min_length: usize) -> SchemaBuilder {
+        self.schema.min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the regular expression pattern, fails if the pattern is not valid
+    pub fn pattern(mut self, pattern: &str) -> SchemaBuilder {
+        match Regex::new(pattern) {
+            Ok(regex) => self.schema.pattern = Some(regex),
+            Err(e) => self.fail(Error::message(format!("pattern: {}", e))),
+        };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::schema::mapping::{FileName, LocationPartition, RawTarget, TargetFormat, TargetLocation, TargetType};
+
+    fn assert_same(built: Schema, yaml: &str) {
+        let parsed: Schema = yaml.parse().unwrap();
+        assert_eq!(
+            serde_yaml::to_string(&built).unwrap(),
+            serde_yaml::to_string(&parsed).unwrap()
+        );
+    }
+
+    #[test]
+    fn all_keywords() {
+        let location = TargetLocation::new(LocationPartition::Label("resin-boot".to_string()), "/config.json");
+
+        let schema = Schema::builder()
+            .version(1)
+            .title("Device")
+            .mapping(
+                Mapping::builder()
+                    .declare_target(
+                        "config-json",
+                        RawTarget::new(TargetType::File, TargetFormat::Json, location),
+                    )
+                    .build(),
+            )
+            .property(
+                "hostname",
+                Schema::builder()
+                    .primitive_type(PrimitiveType::Hostname)
+                    .help("Device hostname")
+                    .warning("Reboot required")
+                    .description("Hostname")
+                    .placeholder("balena")
+                    .default("balena")
+                    .mapping(
+                        Mapping::builder()
+                            .target_reference("config-json")
+                            .path("hostname")
+                            .build(),
+                    ),
+            )
+            .property(
+                "port",
+                Schema::builder()
+                    .integer()
+                    .optional()
+                    .min(1)
+                    .max(65535)
+                    .exclusive_min(0)
+                    .exclusive_max(65536)
+                    .multiple_of(1)
+                    .read_only(true)
+                    .hidden(true),
+            )
+            .property(
+                "mode",
+                Schema::builder()
+                    .string()
+                    .enum_value("ap")
+                    .enum_entry("Client", "client")
+                    .r#const("ap")
+                    .write_only(true),
+            )
+            .property(
+                "networks",
+                Schema::builder()
+                    .array()
+                    .min_items(1)
+                    .max_items(10)
+                    .unique_items_paths(vec!["ssid"])
+                    .orderable(false)
+                    .addable(true)
+                    .removable(true)
+                    .items(
+                        Schema::builder()
+                            .property("ssid", Schema::builder().string().min_length(1).max_length(32))
+                            .property(
+                                "id",
+                                Schema::builder().string().pattern("^[a-z]+$").formula("super.ssid"),
+                            )
+                            .collapsible(true)
+                            .collapsed(false)
+                            .mapping(
+                                Mapping::builder()
+                                    .target(
+                                        RawTarget::new(
+                                            TargetType::FileSet,
+                                            TargetFormat::Ini,
+                                            TargetLocation::new(LocationPartition::Index(1), "/system-connections"),
+                                        )
+                                        .with_glob("*.conf"),
+                                    )
+                                    .filename(FileName::Formula("this.id".to_string()))
+                                    .template(json!({"connection": {"type": "wifi"}}))
+                                    .build(),
+                            ),
+                    ),
+            )
+            .property(
+                "dns",
+                Schema::builder()
+                    .primitive_type(PrimitiveType::StringList)
+                    .separator(",")
+                    .unique_items(true),
+            )
+            .property(
+                "labels",
+                Schema::builder()
+                    .keys(Schema::builder().string())
+                    .values(Schema::builder().string())
+                    .additional_properties(true),
+            )
+            .build()
+            .unwrap();
+
+        assert_same(
+            schema,
+            r#"
+            version: 1
+            title: Device
+            mapping:
+              targets:
+                config-json:
+                  type: file
+                  format: json
+                  location:
+                    partition: resin-boot
+                    path: /config.json
+            properties:
+              - hostname:
+                  type: hostname
+                  help: Device hostname
+                  warning: Reboot required
+                  description: Hostname
+                  placeholder: balena
+                  default: balena
+                  mapping:
+                    target: config-json
+                    path: hostname
+              - port:
+                  type: integer?
+                  min: 1
+                  max: 65535
+                  exclusiveMin: 0
+                  exclusiveMax: 65536
+                  multipleOf: 1
+                  readOnly: true
+                  hidden: true
+              - mode:
+                  type: string
+                  enum:
+                    - ap
+                    - title: Client
+                      value: client
+                  const: ap
+                  writeOnly: true
+              - networks:
+                  type: array
+                  minItems: 1
+                  maxItems: 10
+                  uniqueItems:
+                    - ssid
+                  orderable: false
+                  addable: true
+                  removable: true
+                  items:
+                    collapsible: true
+                    collapsed: false
+                    mapping:
+                      target:
+                        type: fileset
+                        format: ini
+                        glob: "*.conf"
+                        location:
+                          partition: 1
+                          path: /system-connections
+                      filename:
+                        formula: this.id
+                      template:
+                        connection:
+                          type: wifi
+                    properties:
+                      - ssid:
+                          type: string
+                          minLength: 1
+                          maxLength: 32
+                      - id:
+                          type: string
+                          pattern: ^[a-z]+$
+                          formula: super.ssid
+              - dns:
+                  type: stringlist
+                  separator: ","
+                  uniqueItems: true
+              - labels:
+                  keys:
+                    type: string
+                  values:
+                    type: string
+                  additionalProperties: true
+            "#,
+        );
+    }
+
+    #[test]
+    fn definitions() {
+        let schema = Schema::builder()
+            .definition("address", Schema::builder().primitive_type(PrimitiveType::IPv4))
+            .property(
+                "dns",
+                Schema::builder()
+                    .reference("address")
+                    .title("DNS")
+                    .primitive_type(PrimitiveType::IPv4)
+                    .optional(),
+            )
+            .build()
+            .unwrap();
+
+        assert_same(
+            schema,
+            r##"
+            definitions:
+              address:
+                type: ipv4
+            properties:
+              - dns:
+                  $ref: "#/definitions/address"
+                  title: DNS
+                  type: ipv4?
+            "##,
+        );
+    }
+
+    #[test]
+    fn keyword_along_with_reference() {
+        let error = Schema::builder()
+            .definition("name", Schema::builder().string())
+            .property("foo", Schema::builder().reference("name").max_length(10))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'properties[0].foo': keyword 'maxLength' can't be combined with '$ref'"
+        );
+    }
+
+    #[test]
+    fn optional_type_kept() {
+        let schema = Schema::builder().optional().string().build().unwrap();
+        assert_eq!(schema.r#type(), &Type::new_optional(PrimitiveType::String));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let error = Schema::builder()
+            .property("name", Schema::builder().string().pattern("("))
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().starts_with("properties.name: pattern: "));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(Schema::builder().version(3).build().is_err());
+    }
+
+    #[test]
+    fn enum_value_requires_title() {
+        assert!(Schema::builder().enum_value(json!([1, 2])).build().is_err());
+        assert!(Schema::builder().enum_entry("List", json!([1, 2])).build().is_ok());
+    }
+}
@@ -0,0 +1,407 @@
+//! Definitions & references
+//!
+//! Reusable subschemas are declared in the root schema `definitions` keyword and
+//! referenced with the `$ref` keyword (`$ref: "#/definitions/name"`). References are
+//! resolved when the schema is parsed, the referencing schema gets all the definition
+//! keywords.
+//!
+//! Referencing schema can override the definition annotations (`title`, `help`,
+//! `warning`, `description`, `placeholder`), it can make the type optional
+//! (`type: object?`) and the property conditional (`when`). Any other keyword along
+//! with the `$ref` is an error.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::Schema;
+//!
+//! let schema: Schema = r##"
+//!   version: 1
+//!   definitions:
+//!     network:
+//!       properties:
+//!         - ssid:
+//!             type: string
+//!   properties:
+//!     - primary:
+//!         $ref: "#/definitions/network"
+//!     - secondary:
+//!         $ref: "#/definitions/network"
+//!         title: Secondary network
+//!         type: object?
+//! "##.parse().unwrap();
+//!
+//! let secondary = schema.properties()[1].schema();
+//! assert_eq!(secondary.r#ref(), Some("#/definitions/network"));
+//! assert_eq!(secondary.title(), Some("Secondary network"));
+//! assert_eq!(secondary.properties()[0].name(), "ssid");
+//! ```
+use std::collections::BTreeMap;
+
+use crate::{
+    error::Error,
+    schema::{keywords, source::Node, Schema, Type},
+    validator::path::PathBuf,
+};
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+// Keywords allowed along with the `$ref` keyword
+const REFERENCE_KEYWORDS: &[&str] = &[
+    "$ref",
+    "title",
+    "help",
+    "warning",
+    "description",
+    "placeholder",
+    "type",
+    "when",
+];
+
+struct Resolver<'a> {
+    definitions: &'a BTreeMap<String, Schema>,
+    resolved: BTreeMap<String, Schema>,
+    // Definitions being resolved, used to detect circular references
+    stack: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn definition(&mut self, reference: &str, path: &PathBuf) -> Result<Schema, Error> {
+        let name = reference
+            .strip_prefix(DEFINITIONS_PREFIX)
+            .ok_or_else(|| Error::at_schema_path(path, format!("invalid reference '{}'", reference)))?;
+
+        if let Some(schema) = self.resolved.get(name) {
+            return Ok(schema.clone());
+        }
+
+        if self.stack.iter().any(|x| x == name) {
+            let mut cycle = self.stack.clone();
+            cycle.push(name.to_string());
+            return Err(Error::at_schema_path(
+                path,
+                format!("circular reference: {}", cycle.join(" -> ")),
+            ));
+        }
+
+        let mut schema = self
+            .definitions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::at_schema_path(path, format!("unknown definition '{}'", name)))?;
+
+        let mut definition_path = PathBuf::new();
+        definition_path.push_property("definitions");
+        definition_path.push_property(name);
+
+        self.stack.push(name.to_string());
+        self.resolve_schema(&mut schema, &definition_path)?;
+        self.stack.pop();
+
+        self.resolved.insert(name.to_string(), schema.clone());
+        Ok(schema)
+    }
+
+    fn resolve_reference(&mut self, reference: &Schema, path: &PathBuf) -> Result<Schema, Error> {
+        let mut schema = self.definition(reference.r#ref().unwrap_or_default(), path)?;
+
+        if reference.r#type() != &Type::default() {
+            if reference.r#type().primitive_type() != schema.r#type().primitive_type() {
+                return Err(Error::at_schema_path(
+                    path,
+                    format!(
+                        "type '{}' does not match the definition type '{}'",
+                        reference.r#type(),
+                        schema.r#type()
+                    ),
+                ));
+            }
+            schema.r#type = reference.r#type.clone();
+        }
+
+        macro_rules! override_annotation {
+            ($keyword:ident) => {
+                if reference.$keyword.is_some() {
+                    schema.$keyword = reference.$keyword.clone();
+                }
+            };
+        }
+
+        override_annotation!(title);
+        override_annotation!(help);
+        override_annotation!(warning);
+        override_annotation!(description);
+        override_annotation!(placeholder);
+        override_annotation!(when);
+
+        schema.r#ref = reference.r#ref.clone();
+        schema.unresolved = Some(Box::new(reference.clone()));
+        Ok(schema)
+    }
+
+    fn resolve_schema(&mut self, schema: &mut Schema, path: &PathBuf) -> Result<(), Error> {
+        if schema.r#ref().is_some() {
+            *schema = self.resolve_reference(schema, path)?;
+            return Ok(());
+        }
+
+        for (idx, property) in schema.properties.iter_mut().enumerate() {
+            let mut property_path = path.clone();
+            property_path.push_property("properties");
+            property_path.push_index(idx);
+            property_path.push_property(property.name());
+            self.resolve_nested(property.schema_mut(), &property_path)?;
+        }
+
+        for (idx, items) in schema.items.iter_mut().enumerate() {
+            let mut items_path = path.clone();
+            items_path.push_property("items");
+            items_path.push_index(idx);
+            self.resolve_nested(items, &items_path)?;
+        }
+
+        if let Some(keys) = schema.keys.as_mut() {
+            let mut keys_path = path.clone();
+            keys_path.push_property("keys");
+            self.resolve_nested(keys, &keys_path)?;
+        }
+
+        if let Some(values) = schema.values.as_mut() {
+            let mut values_path = path.clone();
+            values_path.push_property("values");
+            self.resolve_nested(values, &values_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn resolve_nested(&mut self, schema: &mut Schema, path: &PathBuf) -> Result<(), Error> {
+        if !schema.definitions.is_empty() {
+            return Err(Error::at_schema_path(
+                path,
+                "definitions are allowed in the root schema only",
+            ));
+        }
+        self.resolve_schema(schema, path)
+    }
+}
+
+/// Resolves all references in the schema
+///
+/// # Arguments
+///
+/// * `schema` - Root schema with definitions
+pub(crate) fn resolve(schema: &mut Schema) -> Result<(), Error> {
+    let definitions = std::mem::take(&mut schema.definitions);
+
+    let mut resolver = Resolver {
+        definitions: &definitions,
+        resolved: BTreeMap::new(),
+        stack: vec![],
+    };
+
+    // Resolve even unused definitions to report errors early
+    for name in definitions.keys() {
+        let mut path = PathBuf::new();
+        path.push_property("definitions");
+        path.push_property(name.as_str());
+        if !definitions[name].definitions.is_empty() {
+            return Err(Error::at_schema_path(
+                &path,
+                "definitions are allowed in the root schema only",
+            ));
+        }
+        resolver.definition(&format!("{}{}", DEFINITIONS_PREFIX, name), &path)?;
+    }
+
+    resolver.resolve_schema(schema, &PathBuf::new())?;
+
+    schema.definitions = resolver.resolved;
+    Ok(())
+}
+
+/// Checks that the `$ref` keyword is combined with the allowed keywords only
+///
+/// # Arguments
+///
+/// * `root` - Schema source
+pub(crate) fn check_reference_keywords(root: &Node) -> Result<(), Error> {
+    match keywords::reference_keywords(root)
+        .into_iter()
+        .find(|x| !REFERENCE_KEYWORDS.contains(&x.keyword.as_str()))
+    {
+        Some(keyword) => Err(Error::at_schema_path(
+            &keyword.schema_path,
+            format!("keyword '{}' can't be combined with '$ref'", keyword.keyword),
+        )
+        .with_position(keyword.position.line(), keyword.position.column())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(schema: &str) -> String {
+        schema.parse::<Schema>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn nested_references() {
+        let schema: Schema = r##"
+        definitions:
+          address:
+            type: ipv4
+          network:
+            properties:
+              - gateway:
+                  $ref: "#/definitions/address"
+        properties:
+          - networks:
+              type: array
+              items:
+                $ref: "#/definitions/network"
+        "##
+        .parse()
+        .unwrap();
+
+        let items = &schema.properties()[0].schema().items()[0];
+        assert_eq!(items.r#ref(), Some("#/definitions/network"));
+        assert_eq!(items.properties()[0].schema().r#type().to_string(), "ipv4");
+        assert_eq!(schema.definitions().len(), 2);
+    }
+
+    #[test]
+    fn circular_reference() {
+        let error = parse_error(
+            r##"
+            definitions:
+              a:
+                properties:
+                  - b:
+                      $ref: "#/definitions/b"
+              b:
+                type: array
+                items:
+                  $ref: "#/definitions/a"
+            "##,
+        );
+        assert_eq!(error, "'definitions.b.items[0]': circular reference: a -> b -> a");
+    }
+
+    #[test]
+    fn unknown_definition() {
+        let error = parse_error(
+            r##"
+            properties:
+              - foo:
+                  $ref: "#/definitions/foo"
+            "##,
+        );
+        assert_eq!(error, "'properties[0].foo': unknown definition 'foo'");
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let error = parse_error(
+            r##"
+            properties:
+              - foo:
+                  $ref: foo
+            "##,
+        );
+        assert_eq!(error, "'properties[0].foo': invalid reference 'foo'");
+    }
+
+    #[test]
+    fn keyword_along_with_reference() {
+        let error = parse_error(
+            r##"
+            definitions:
+              name:
+                type: string
+            properties:
+              - foo:
+                  $ref: "#/definitions/name"
+                  maxLength: 10
+            "##,
+        );
+        assert_eq!(
+            error,
+            "'properties[0].foo': keyword 'maxLength' can't be combined with '$ref'"
+        );
+    }
+
+    #[test]
+    fn default_keyword_along_with_reference() {
+        let error = parse_error(
+            r##"
+            definitions:
+              name:
+                type: string
+            properties:
+              - foo:
+                  $ref: "#/definitions/name"
+                  hidden: false
+            "##,
+        );
+        assert_eq!(
+            error,
+            "'properties[0].foo': keyword 'hidden' can't be combined with '$ref'"
+        );
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let error = parse_error(
+            r##"
+            definitions:
+              name:
+                type: string
+            properties:
+              - foo:
+                  $ref: "#/definitions/name"
+                  type: integer?
+            "##,
+        );
+        assert_eq!(
+            error,
+            "'properties[0].foo': type 'integer?' does not match the definition type 'string'"
+        );
+    }
+
+    #[test]
+    fn nested_definitions() {
+        let error = parse_error(
+            r##"
+            properties:
+              - foo:
+                  definitions:
+                    name:
+                      type: string
+            "##,
+        );
+        assert_eq!(
+            error,
+            "'properties[0].foo': definitions are allowed in the root schema only"
+        );
+    }
+
+    #[test]
+    fn serialize_unresolved_references() {
+        let source = r##"version: 1
+properties:
+  - name:
+      $ref: "#/definitions/name"
+      title: Name
+      type: string?
+definitions:
+  name:
+    type: string
+    maxLength: 10
+"##;
+        let schema: Schema = source.parse().unwrap();
+        assert_eq!(serde_yaml::to_string(&schema).unwrap(), format!("---\n{}", source));
+    }
+}
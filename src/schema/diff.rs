@@ -0,0 +1,721 @@
+//! Schema changes detection
+//!
+//! Two schemas are compared keyword by keyword and every change is classified as
+//! compatible or breaking for the configuration data valid against the old schema.
+//! A breaking change means that the existing data can become invalid or they're
+//! rendered into different target files and a data migration is probably needed.
+//!
+//! Properties are matched by name, array items by index. Annotations (`title`,
+//! `help`, `hidden`, ...) are not compared.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::{diff, Compatibility, Schema};
+//!
+//! let old: Schema = r#"
+//!   version: 1
+//!   properties:
+//!     - port:
+//!         type: port
+//!         max: 8080
+//! "#.parse().unwrap();
+//!
+//! let new: Schema = r#"
+//!   version: 1
+//!   properties:
+//!     - port:
+//!         type: port
+//!         max: 1024
+//!     - hostname:
+//!         type: hostname?
+//! "#.parse().unwrap();
+//!
+//! let diff = diff(&old, &new);
+//!
+//! assert!(diff.is_breaking());
+//! assert_eq!(diff.changes()[0].schema_path(), "properties[0].port.max");
+//! assert_eq!(diff.changes()[0].compatibility(), Compatibility::Breaking);
+//! assert_eq!(diff.changes()[1].compatibility(), Compatibility::Compatible);
+//! ```
+use std::fmt;
+
+use serde::Serialize;
+use serde_derive::Serialize;
+use serde_json::{Number, Value};
+
+use crate::{
+    schema::{discriminator, mapping::Mapping, Schema, UniqueItems},
+    validator::path::PathBuf,
+};
+
+/// Change compatibility with the existing configuration data
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    /// Data valid against the old schema are valid against the new one
+    Compatible,
+    /// Data valid against the old schema can be invalid or rendered differently
+    Breaking,
+}
+
+/// Single schema change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    #[serde(rename = "schemaPath")]
+    schema_path: String,
+    message: String,
+    compatibility: Compatibility,
+}
+
+impl Change {
+    /// Path to the changed keyword, old schema path for removed properties
+    pub fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn compatibility(&self) -> Compatibility {
+        self.compatibility
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let compatibility = match self.compatibility {
+            Compatibility::Compatible => "compatible",
+            Compatibility::Breaking => "breaking",
+        };
+        write!(f, "{}: '{}': {}", compatibility, self.schema_path, self.message)
+    }
+}
+
+/// Changes between two schemas
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Checks if there's at least one breaking change
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(Change::is_breaking)
+    }
+
+    fn push<S>(&mut self, path: &PathBuf, keyword: &str, compatibility: Compatibility, message: S)
+    where
+        S: Into<String>,
+    {
+        let mut schema_path = path.clone();
+        if !keyword.is_empty() {
+            schema_path.push_property(keyword);
+        }
+
+        self.changes.push(Change {
+            schema_path: schema_path.to_string(),
+            message: message.into(),
+            compatibility,
+        });
+    }
+
+    fn compatible<S: Into<String>>(&mut self, path: &PathBuf, keyword: &str, message: S) {
+        self.push(path, keyword, Compatibility::Compatible, message);
+    }
+
+    fn breaking<S: Into<String>>(&mut self, path: &PathBuf, keyword: &str, message: S) {
+        self.push(path, keyword, Compatibility::Breaking, message);
+    }
+
+    // Keyword limiting the value from above (`max`, `maxLength`, ...), lowered limit is breaking
+    fn upper_bound<T>(&mut self, path: &PathBuf, keyword: &str, old: Option<T>, new: Option<T>)
+    where
+        T: PartialOrd + fmt::Display,
+    {
+        match (old, new) {
+            (None, Some(new)) => self.breaking(path, keyword, format!("added ({})", new)),
+            (Some(_), None) => self.compatible(path, keyword, "removed"),
+            (Some(old), Some(new)) if new < old => {
+                self.breaking(path, keyword, format!("lowered from {} to {}", old, new))
+            }
+            (Some(old), Some(new)) if new > old => {
+                self.compatible(path, keyword, format!("raised from {} to {}", old, new))
+            }
+            _ => {}
+        };
+    }
+
+    // Keyword limiting the value from below (`min`, `minLength`, ...), raised limit is breaking
+    fn lower_bound<T>(&mut self, path: &PathBuf, keyword: &str, old: Option<T>, new: Option<T>)
+    where
+        T: PartialOrd + fmt::Display,
+    {
+        match (old, new) {
+            (None, Some(new)) => self.breaking(path, keyword, format!("added ({})", new)),
+            (Some(_), None) => self.compatible(path, keyword, "removed"),
+            (Some(old), Some(new)) if new > old => {
+                self.breaking(path, keyword, format!("raised from {} to {}", old, new))
+            }
+            (Some(old), Some(new)) if new < old => {
+                self.compatible(path, keyword, format!("lowered from {} to {}", old, new))
+            }
+            _ => {}
+        };
+    }
+
+    // Keyword restricting the value in a way we can't compare (`pattern`, `const`, ...)
+    fn restriction<T>(&mut self, path: &PathBuf, keyword: &str, old: Option<T>, new: Option<T>)
+    where
+        T: PartialEq + fmt::Display,
+    {
+        match (old, new) {
+            (None, Some(new)) => self.breaking(path, keyword, format!("added ({})", new)),
+            (Some(_), None) => self.compatible(path, keyword, "removed"),
+            (Some(old), Some(new)) if old != new => {
+                self.breaking(path, keyword, format!("changed from {} to {}", old, new))
+            }
+            _ => {}
+        };
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn as_f64(number: Option<&Number>) -> Option<f64> {
+    number.and_then(Number::as_f64)
+}
+
+fn compare_type(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    let (old_type, new_type) = (old.r#type(), new.r#type());
+
+    if old_type.primitive_type() != new_type.primitive_type() {
+        diff.breaking(path, "type", format!("changed from '{}' to '{}'", old_type, new_type));
+    } else if old_type.is_optional() && new_type.is_required() {
+        diff.breaking(path, "type", format!("'{}' is not optional anymore", old_type));
+    } else if old_type.is_required() && new_type.is_optional() {
+        diff.compatible(path, "type", format!("'{}' is optional now", new_type));
+    }
+}
+
+fn compare_values(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    diff.restriction(path, "const", old.r#const(), new.r#const());
+
+    if !old.r#enum().is_empty() || !new.r#enum().is_empty() {
+        let old_values: Vec<&Value> = old.r#enum().iter().map(|x| x.value()).collect();
+        let new_values: Vec<&Value> = new.r#enum().iter().map(|x| x.value()).collect();
+
+        if new_values.is_empty() {
+            diff.compatible(path, "enum", "removed");
+        } else if old_values.is_empty() {
+            diff.breaking(path, "enum", "added");
+        } else {
+            for value in old_values.iter().filter(|x| !new_values.contains(x)) {
+                diff.breaking(path, "enum", format!("value {} removed", value));
+            }
+            for value in new_values.iter().filter(|x| !old_values.contains(x)) {
+                diff.compatible(path, "enum", format!("value {} added", value));
+            }
+        }
+    }
+
+    if old.r#default() != new.r#default() {
+        diff.compatible(path, "default", "changed");
+    }
+
+    // Changes the computed value, rendered files & computed file names
+    if old.formula() != new.formula() {
+        diff.breaking(path, "formula", "changed");
+    }
+}
+
+fn compare_number_keywords(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    diff.upper_bound(path, "max", as_f64(old.max()), as_f64(new.max()));
+    diff.upper_bound(
+        path,
+        "exclusiveMax",
+        as_f64(old.exclusive_max()),
+        as_f64(new.exclusive_max()),
+    );
+    diff.lower_bound(path, "min", as_f64(old.min()), as_f64(new.min()));
+    diff.lower_bound(
+        path,
+        "exclusiveMin",
+        as_f64(old.exclusive_min()),
+        as_f64(new.exclusive_min()),
+    );
+    diff.restriction(path, "multipleOf", old.multiple_of(), new.multiple_of());
+}
+
+fn compare_string_keywords(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    diff.upper_bound(path, "maxLength", old.max_length(), new.max_length());
+    diff.lower_bound(path, "minLength", old.min_length(), new.min_length());
+    diff.restriction(
+        path,
+        "pattern",
+        old.pattern().map(|x| x.as_str()),
+        new.pattern().map(|x| x.as_str()),
+    );
+
+    // Changes the rendered value of string lists
+    if old.separator() != new.separator() {
+        diff.breaking(path, "separator", "changed");
+    }
+}
+
+fn compare_array_keywords(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    diff.upper_bound(path, "maxItems", old.max_items(), new.max_items());
+    diff.lower_bound(path, "minItems", old.min_items(), new.min_items());
+
+    match (old.unique_items(), new.unique_items()) {
+        (old, new) if old == new => {}
+        (_, UniqueItems::Boolean(false)) => diff.compatible(path, "uniqueItems", "removed"),
+        _ => diff.breaking(path, "uniqueItems", "changed"),
+    };
+
+    if old.discriminator() != new.discriminator() {
+        diff.breaking(path, "discriminator", "changed");
+    }
+
+    for (idx, old_items) in old.items().iter().enumerate() {
+        let mut items_path = path.clone();
+        items_path.push_property("items");
+        items_path.push_index(idx);
+
+        match new.items().get(idx) {
+            Some(new_items) => compare_schemas(diff, old_items, new_items, &items_path),
+            None => diff.breaking(&items_path, "", "items schema removed"),
+        };
+    }
+
+    for (idx, new_items) in new.items().iter().enumerate().skip(old.items().len()) {
+        let mut items_path = path.clone();
+        items_path.push_property("items");
+        items_path.push_index(idx);
+
+        if is_new_discriminated_items(old, new, new_items) {
+            diff.compatible(&items_path, "", "items schema added");
+        } else {
+            diff.breaking(&items_path, "", "items schema added");
+        }
+    }
+}
+
+// Added items schema restricts existing items (single schema) or makes them ambiguous
+// (multiple schemas), unless it's selected by a discriminator value no existing item has
+fn is_new_discriminated_items(old: &Schema, new: &Schema, new_items: &Schema) -> bool {
+    let discriminator = match (old.discriminator(), new.discriminator()) {
+        (Some(old), Some(new)) if old == new => new,
+        _ => return false,
+    };
+
+    match discriminator::discriminator_value(new_items, discriminator) {
+        Some(value) => old
+            .items()
+            .iter()
+            .all(|x| discriminator::discriminator_value(x, discriminator) != Some(value)),
+        None => false,
+    }
+}
+
+// Property without a value in the existing data is fine if it's optional or the value
+// can be filled / computed, a condition can be true for the existing data
+fn is_backward_compatible_property(schema: &Schema) -> bool {
+    schema.r#type().is_optional() || schema.r#default().is_some() || schema.formula().is_some()
+}
+
+// Condition changes which properties are required & validated, only an optional
+// property can become unconditional
+fn compare_when(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    match (old.when(), new.when()) {
+        (old, new) if old == new => {}
+        (Some(_), None) if new.r#type().is_optional() => diff.compatible(path, "when", "removed"),
+        (Some(_), None) => diff.breaking(path, "when", "removed, property is always required now"),
+        (None, Some(_)) => diff.breaking(path, "when", "added"),
+        _ => diff.breaking(path, "when", "changed"),
+    };
+}
+
+fn compare_object_keywords(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    for (idx, old_property) in old.properties().iter().enumerate() {
+        let new_property = new
+            .properties()
+            .iter()
+            .enumerate()
+            .find(|(_, x)| x.name() == old_property.name());
+
+        match new_property {
+            Some((new_idx, new_property)) => {
+                let mut property_path = path.clone();
+                property_path.push_property("properties");
+                property_path.push_index(new_idx);
+                property_path.push_property(new_property.name());
+                compare_schemas(diff, old_property.schema(), new_property.schema(), &property_path);
+            }
+            None => {
+                let mut property_path = path.clone();
+                property_path.push_property("properties");
+                property_path.push_index(idx);
+                property_path.push_property(old_property.name());
+                diff.breaking(&property_path, "", "property removed");
+            }
+        };
+    }
+
+    for (idx, new_property) in new.properties().iter().enumerate() {
+        if old.properties().iter().any(|x| x.name() == new_property.name()) {
+            continue;
+        }
+
+        let mut property_path = path.clone();
+        property_path.push_property("properties");
+        property_path.push_index(idx);
+        property_path.push_property(new_property.name());
+
+        if is_backward_compatible_property(new_property.schema()) {
+            diff.compatible(&property_path, "", "property added");
+        } else {
+            diff.breaking(&property_path, "", "required property added without a default value");
+        }
+    }
+
+    match (old.additional_properties(), new.additional_properties()) {
+        (true, false) => diff.breaking(path, "additionalProperties", "not allowed anymore"),
+        (false, true) => diff.compatible(path, "additionalProperties", "allowed now"),
+        _ => {}
+    };
+
+    for keyword in &["keys", "values"] {
+        let (old_schema, new_schema) = match *keyword {
+            "keys" => (old.keys(), new.keys()),
+            _ => (old.values(), new.values()),
+        };
+
+        let mut keyword_path = path.clone();
+        keyword_path.push_property(*keyword);
+
+        match (old_schema, new_schema) {
+            (Some(old_schema), Some(new_schema)) => compare_schemas(diff, old_schema, new_schema, &keyword_path),
+            (None, Some(_)) => diff.breaking(&keyword_path, "", "added"),
+            (Some(_), None) => diff.breaking(&keyword_path, "", "removed"),
+            _ => {}
+        };
+    }
+}
+
+fn compare_mappings(diff: &mut Diff, old: Option<&Mapping>, new: Option<&Mapping>, path: &PathBuf) {
+    let mut mapping_path = path.clone();
+    mapping_path.push_property("mapping");
+
+    let (old, new) = match (old, new) {
+        (None, None) => return,
+        (None, Some(_)) => return diff.compatible(&mapping_path, "", "added"),
+        (Some(_), None) => return diff.breaking(&mapping_path, "", "removed"),
+        (Some(old), Some(new)) => (old, new),
+    };
+
+    let keywords = [
+        ("targets", to_value(old.targets()), to_value(new.targets())),
+        ("target", to_value(old.target()), to_value(new.target())),
+        ("filename", to_value(old.filename()), to_value(new.filename())),
+        ("path", to_value(old.path()), to_value(new.path())),
+        ("template", to_value(old.template()), to_value(new.template())),
+    ];
+
+    for (keyword, old, new) in keywords.iter() {
+        if old != new {
+            diff.breaking(&mapping_path, keyword, "changed");
+        }
+    }
+}
+
+fn compare_schemas(diff: &mut Diff, old: &Schema, new: &Schema, path: &PathBuf) {
+    compare_type(diff, old, new, path);
+    compare_values(diff, old, new, path);
+
+    compare_when(diff, old, new, path);
+
+    compare_number_keywords(diff, old, new, path);
+    compare_string_keywords(diff, old, new, path);
+    compare_array_keywords(diff, old, new, path);
+    compare_object_keywords(diff, old, new, path);
+    compare_mappings(diff, old.mapping(), new.mapping(), path);
+}
+
+/// Compares two schemas
+///
+/// Returns all the changes with their compatibility with the data valid against
+/// the `old` schema.
+///
+/// # Arguments
+///
+/// * `old` - Currently used schema
+/// * `new` - Schema to compare with
+pub fn diff(old: &Schema, new: &Schema) -> Diff {
+    let mut diff = Diff::default();
+    compare_schemas(&mut diff, old, new, &PathBuf::new());
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        let old: Schema = old.parse().unwrap();
+        let new: Schema = new.parse().unwrap();
+        diff(&old, &new).changes().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn same_schema() {
+        let schema = "properties:\n  - foo:\n      type: string\n      title: Foo\n";
+        assert!(changes(schema, schema).is_empty());
+        assert!(changes(schema, &schema.replace("Foo", "Bar")).is_empty());
+    }
+
+    #[test]
+    fn properties() {
+        let old = r#"
+        properties:
+          - removed:
+              type: string
+          - kept:
+              type: string
+        "#;
+        let new = r#"
+        properties:
+          - kept:
+              type: string
+          - required:
+              type: string
+          - defaulted:
+              type: string
+              default: foo
+          - optional:
+              type: string?
+        "#;
+        assert_eq!(
+            changes(old, new),
+            vec![
+                "breaking: 'properties[0].removed': property removed",
+                "breaking: 'properties[1].required': required property added without a default value",
+                "compatible: 'properties[2].defaulted': property added",
+                "compatible: 'properties[3].optional': property added",
+            ]
+        );
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(
+            changes("type: string", "type: integer"),
+            vec!["breaking: 'type': changed from 'string' to 'integer'"]
+        );
+        assert_eq!(
+            changes("type: string?", "type: string"),
+            vec!["breaking: 'type': 'string?' is not optional anymore"]
+        );
+        assert_eq!(
+            changes("type: string", "type: string?"),
+            vec!["compatible: 'type': 'string?' is optional now"]
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(
+            changes(
+                "type: integer\nmax: 10\nmin: 1",
+                "type: integer\nmax: 5\nmin: 0\nmultipleOf: 2"
+            ),
+            vec![
+                "breaking: 'max': lowered from 10 to 5",
+                "compatible: 'min': lowered from 1 to 0",
+                "breaking: 'multipleOf': added (2)",
+            ]
+        );
+        assert_eq!(
+            changes("type: string\nmaxLength: 5", "type: string\nminLength: 1"),
+            vec!["compatible: 'maxLength': removed", "breaking: 'minLength': added (1)"]
+        );
+    }
+
+    #[test]
+    fn enum_values() {
+        assert_eq!(
+            changes("type: string\nenum: [foo, bar]", "type: string\nenum: [bar, baz]"),
+            vec![
+                "breaking: 'enum': value \"foo\" removed",
+                "compatible: 'enum': value \"baz\" added",
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let old = r#"
+        type: array
+        items:
+          properties:
+            - ssid:
+                type: string
+        "#;
+        let new = r#"
+        type: array
+        uniqueItems: true
+        items:
+          properties:
+            - ssid:
+                type: string
+                maxLength: 32
+        "#;
+        assert_eq!(
+            changes(old, new),
+            vec![
+                "breaking: 'uniqueItems': changed",
+                "breaking: 'items[0].properties[0].ssid.maxLength': added (32)",
+            ]
+        );
+    }
+
+    #[test]
+    fn added_items() {
+        let old = "type: array\n";
+        let new = "type: array\nitems:\n  type: integer\n";
+        assert_eq!(changes(old, new), vec!["breaking: 'items[0]': items schema added"]);
+
+        let old = "type: array\nitems:\n  - type: integer\n";
+        let new = "type: array\nitems:\n  - type: integer\n  - type: number\n";
+        assert_eq!(changes(old, new), vec!["breaking: 'items[1]': items schema added"]);
+    }
+
+    #[test]
+    fn added_discriminated_items() {
+        let old = r#"
+        type: array
+        discriminator: type
+        items:
+          - properties:
+              - type:
+                  type: string
+                  const: wifi
+        "#;
+        let new = r#"
+        type: array
+        discriminator: type
+        items:
+          - properties:
+              - type:
+                  type: string
+                  const: wifi
+          - properties:
+              - type:
+                  type: string
+                  const: ethernet
+        "#;
+        assert_eq!(changes(old, new), vec!["compatible: 'items[1]': items schema added"]);
+
+        let old = old.replace("        discriminator: type\n", "");
+        let new = new.replace("        discriminator: type\n", "");
+        assert_eq!(changes(&old, &new), vec!["breaking: 'items[1]': items schema added"]);
+    }
+
+    #[test]
+    fn mapping() {
+        let old = r#"
+        properties:
+          - hostname:
+              type: string
+              mapping:
+                target: config-json
+                path: hostname
+        "#;
+        assert_eq!(
+            changes(old, &old.replace("path: hostname", "path: host")),
+            vec!["breaking: 'properties[0].hostname.mapping.path': changed"]
+        );
+        assert_eq!(
+            changes(old, &old.replace("target: config-json", "target: hostname")),
+            vec!["breaking: 'properties[0].hostname.mapping.target': changed"]
+        );
+    }
+
+    #[test]
+    fn conditional_properties() {
+        let old = r#"
+        properties:
+          - server:
+              type: string
+              when: super.enabled
+        "#;
+        assert_eq!(
+            changes(old, "properties:\n  - server:\n      type: string\n"),
+            vec!["breaking: 'properties[0].server.when': removed, property is always required now"]
+        );
+        assert_eq!(
+            changes(old, "properties:\n  - server:\n      type: string?\n"),
+            vec![
+                "compatible: 'properties[0].server.type': 'string?' is optional now",
+                "compatible: 'properties[0].server.when': removed",
+            ]
+        );
+        assert_eq!(
+            changes(old, &old.replace("super.enabled", "!super.enabled")),
+            vec!["breaking: 'properties[0].server.when': changed"]
+        );
+        assert_eq!(
+            changes(
+                "properties:\n  - server:\n      type: string?\n",
+                &old.replace("string", "string?")
+            ),
+            vec!["breaking: 'properties[0].server.when': added"]
+        );
+    }
+
+    #[test]
+    fn added_conditional_property() {
+        let old = r#"
+        properties:
+          - enabled:
+              type: boolean
+        "#;
+        let new = r#"
+        properties:
+          - enabled:
+              type: boolean
+          - server:
+              type: hostname
+              when: super.enabled
+        "#;
+        let diff = diff(&old.parse().unwrap(), &new.parse().unwrap());
+        assert!(diff.is_breaking());
+        assert_eq!(
+            diff.changes()[0].to_string(),
+            "breaking: 'properties[1].server': required property added without a default value"
+        );
+    }
+
+    #[test]
+    fn formula() {
+        assert_eq!(
+            changes("type: string\nformula: host", "type: string\nformula: host ~ `.local`"),
+            vec!["breaking: 'formula': changed"]
+        );
+    }
+}
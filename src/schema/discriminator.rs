@@ -0,0 +1,199 @@
+//! Discriminated array items
+//!
+//! Array with multiple `items` schemas can declare the `discriminator` property. Every
+//! items schema must be an object with this property and its `const` keyword value
+//! must be unique. An array item is then validated against (and filled from) the items
+//! schema with the matching value only, instead of trying all of them.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::Schema;
+//! use serde_json::json;
+//!
+//! let schema: Schema = r#"
+//!   version: 1
+//!   type: array
+//!   discriminator: type
+//!   items:
+//!     - properties:
+//!         - type:
+//!             type: string
+//!             const: wifi
+//!         - ssid:
+//!             type: string
+//!     - properties:
+//!         - type:
+//!             type: string
+//!             const: ethernet
+//! "#.parse().unwrap();
+//!
+//! let (idx, _) = schema.discriminated_items(&json!({"type": "ethernet"})).unwrap();
+//! assert_eq!(idx, 1);
+//! ```
+use serde_json::Value;
+
+use crate::{error::Error, schema::Schema, validator::path::PathBuf};
+
+// Discriminator property value of the items schema
+pub(crate) fn discriminator_value<'a>(items: &'a Schema, discriminator: &str) -> Option<&'a Value> {
+    items
+        .properties()
+        .iter()
+        .find(|x| x.name() == discriminator)
+        .and_then(|x| x.schema().r#const())
+}
+
+/// Returns the items schema (and its index) matching the item discriminator value
+pub(crate) fn select<'a>(schema: &'a Schema, item: &Value) -> Option<(usize, &'a Schema)> {
+    let discriminator = schema.discriminator()?;
+    let value = item.get(discriminator)?;
+
+    schema
+        .items()
+        .iter()
+        .enumerate()
+        .find(|(_, items)| discriminator_value(items, discriminator) == Some(value))
+}
+
+fn check_items(schema: &Schema, path: &PathBuf) -> Result<(), Error> {
+    let discriminator = match schema.discriminator() {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    if schema.items().is_empty() {
+        let mut discriminator_path = path.clone();
+        discriminator_path.push_property("discriminator");
+        return Err(Error::at_schema_path(
+            &discriminator_path,
+            "discriminator requires items schemas",
+        ));
+    }
+
+    let mut values: Vec<&Value> = vec![];
+
+    for (idx, items) in schema.items().iter().enumerate() {
+        let mut items_path = path.clone();
+        items_path.push_property("items");
+        items_path.push_index(idx);
+
+        let value = discriminator_value(items, discriminator).ok_or_else(|| {
+            Error::at_schema_path(
+                &items_path,
+                format!(
+                    "discriminator property '{}' with the const keyword is missing",
+                    discriminator
+                ),
+            )
+        })?;
+
+        if values.contains(&value) {
+            return Err(Error::at_schema_path(
+                &items_path,
+                format!("duplicate discriminator value {}", value),
+            ));
+        }
+        values.push(value);
+    }
+
+    Ok(())
+}
+
+fn check_schema(schema: &Schema, path: &PathBuf) -> Result<(), Error> {
+    check_items(schema, path)?;
+
+    for (idx, property) in schema.properties().iter().enumerate() {
+        let mut property_path = path.clone();
+        property_path.push_property("properties");
+        property_path.push_index(idx);
+        property_path.push_property(property.name());
+        check_schema(property.schema(), &property_path)?;
+    }
+
+    for (idx, items) in schema.items().iter().enumerate() {
+        let mut items_path = path.clone();
+        items_path.push_property("items");
+        items_path.push_index(idx);
+        check_schema(items, &items_path)?;
+    }
+
+    if let Some(values) = schema.values() {
+        let mut values_path = path.clone();
+        values_path.push_property("values");
+        check_schema(values, &values_path)?;
+    }
+
+    for (name, definition) in schema.definitions() {
+        let mut definition_path = PathBuf::new();
+        definition_path.push_property("definitions");
+        definition_path.push_property(name.as_str());
+        check_schema(definition, &definition_path)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that all the discriminated items schemas have unique discriminator values
+pub(crate) fn check(schema: &Schema) -> Result<(), Error> {
+    check_schema(schema, &PathBuf::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(schema: &str) -> String {
+        schema.parse::<Schema>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn missing_discriminator_property() {
+        let error = parse_error(
+            r#"
+            properties:
+              - networks:
+                  type: array
+                  discriminator: type
+                  items:
+                    - properties:
+                        - type:
+                            type: string
+                            const: wifi
+                    - properties:
+                        - type:
+                            type: string
+            "#,
+        );
+        assert_eq!(
+            error,
+            "'properties[0].networks.items[1]': discriminator property 'type' with the const keyword is missing"
+        );
+    }
+
+    #[test]
+    fn duplicate_discriminator_value() {
+        let error = parse_error(
+            r#"
+            type: array
+            discriminator: type
+            items:
+              - properties:
+                  - type:
+                      type: string
+                      const: wifi
+              - properties:
+                  - type:
+                      type: string
+                      const: wifi
+            "#,
+        );
+        assert_eq!(error, "'items[1]': duplicate discriminator value \"wifi\"");
+    }
+
+    #[test]
+    fn discriminator_without_items() {
+        let error = parse_error("type: array\ndiscriminator: type\n");
+        assert_eq!(error, "'discriminator': discriminator requires items schemas");
+    }
+}
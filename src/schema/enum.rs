@@ -1,16 +1,41 @@
-use serde::de;
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
 use serde_json::Value;
 
+use crate::{error::Error, locale::DEFAULT_LOCALE, schema::Annotation};
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct EnumEntry {
-    title: Option<String>,
+    title: Option<Annotation>,
     value: Value,
 }
 
 impl EnumEntry {
+    /// Creates a new enum entry
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Entry title, required for `null`, array and object values
+    /// * `value` - Entry value
+    pub fn new(title: Option<Annotation>, value: Value) -> Result<EnumEntry, Error> {
+        match (&title, &value) {
+            (None, Value::Null) | (None, Value::Array(_)) | (None, Value::Object(_)) => {
+                Err(Error::message("title is required for null, sequence or mapping value"))
+            }
+            _ => Ok(EnumEntry { title, value }),
+        }
+    }
+
     pub fn title(&self) -> String {
+        self.title_in(DEFAULT_LOCALE)
+    }
+
+    /// Entry title in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn title_in(&self, locale: &str) -> String {
         match &self.title {
-            Some(v) => v.clone(),
+            Some(v) => v.get(locale).to_string(),
             None => match &self.value {
                 Value::Bool(x) => format!("{}", x),
                 Value::Number(x) => format!("{}", x),
@@ -41,7 +66,8 @@ impl<'de> de::Deserialize<'de> for EnumEntry {
                     .ok_or_else(|| de::Error::custom("missing title keyword"))?;
 
                 let title = match title {
-                    Value::String(s) => s,
+                    Value::String(s) => Annotation::new(s),
+                    Value::Object(_) => Annotation::deserialize(title).map_err(de::Error::custom)?,
                     _ => return Err(de::Error::custom("title is not a string")),
                 };
 
@@ -58,6 +84,23 @@ impl<'de> de::Deserialize<'de> for EnumEntry {
     }
 }
 
+impl ser::Serialize for EnumEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match &self.title {
+            Some(title) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("title", title)?;
+                map.serialize_entry("value", &self.value)?;
+                map.end()
+            }
+            None => self.value.serialize(serializer),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,6 +138,20 @@ mod tests {
         assert_eq!(e.value(), &Value::String("Bar".to_string()));
     }
 
+    #[test]
+    fn localized_title() {
+        let schema = r#"
+            title:
+              en: Ethernet
+              de: Kabelgebunden
+            value: ethernet
+        "#;
+        let e: EnumEntry = serde_yaml::from_str(schema).unwrap();
+        assert_eq!(e.title(), "Ethernet".to_string());
+        assert_eq!(e.title_in("de-CH"), "Kabelgebunden".to_string());
+        assert!(serde_yaml::from_str::<EnumEntry>("{title: {de: Kabelgebunden}, value: ethernet}").is_err());
+    }
+
     #[test]
     fn require_title_for_null_value() {
         let x: Result<EnumEntry, _> = serde_yaml::from_str("~");
@@ -0,0 +1,538 @@
+//! Multi-document schemas
+//!
+//! Any schema can include other schema documents with the `include` keyword (a document
+//! name or a list of names). Included documents can contain `properties`, `definitions`
+//! and `include` keywords only.
+//!
+//! * included document properties are inserted before the including schema properties,
+//! * included document definitions are merged into the root schema definitions,
+//!   duplicate definition names are not allowed,
+//! * included documents can include other documents, circular includes are not allowed.
+//!
+//! Documents are loaded with the [`IncludeResolver`](trait.IncludeResolver.html), schemas
+//! parsed from a string (`Schema::parse`, `FromStr`) can't include any document. Errors
+//! in the included documents contain the document name (see [`Error::file`](../error/struct.Error.html#method.file)).
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::{InMemoryResolver, Schema};
+//!
+//! let mut resolver = InMemoryResolver::new();
+//! resolver.insert(
+//!     "ntp.yml",
+//!     r#"
+//!     properties:
+//!       - ntpServers:
+//!           type: stringlist
+//!     "#,
+//! );
+//!
+//! let (schema, _) = Schema::parse_with_resolver(
+//!     r#"
+//!     version: 1
+//!     include: ntp.yml
+//!     properties:
+//!       - hostname:
+//!           type: hostname
+//!     "#,
+//!     None,
+//!     &resolver,
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(schema.properties()[0].name(), "ntpServers");
+//! assert_eq!(schema.properties()[1].name(), "hostname");
+//! ```
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    error::Error,
+    schema::{source, ParseMode, Schema, UnknownKeyword, Version},
+    validator::path::PathBuf,
+};
+
+// Keywords allowed in the included document
+const INCLUDED_DOCUMENT_KEYWORDS: &[&str] = &["version", "include", "properties", "definitions"];
+
+/// Schema document loaded by the [`IncludeResolver`](trait.IncludeResolver.html)
+#[derive(Debug, Clone)]
+pub struct Document {
+    name: String,
+    source: String,
+}
+
+impl Document {
+    pub fn new<N, S>(name: N, source: S) -> Document
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        Document {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Unique document name, used in errors and for relative includes
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Loads included schema documents
+pub trait IncludeResolver {
+    /// Loads the document
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - `include` keyword value
+    /// * `parent` - Including document name, `None` for the root document
+    fn resolve(&self, include: &str, parent: Option<&str>) -> Result<Document, Error>;
+}
+
+/// Resolver rejecting all includes
+///
+/// Used by [`Schema::parse`](struct.Schema.html#method.parse) & `FromStr`, so that parsing a
+/// schema string never touches the file system. Use
+/// [`Schema::parse_file`](struct.Schema.html#method.parse_file) or
+/// [`Schema::parse_with_resolver`](struct.Schema.html#method.parse_with_resolver) to load
+/// included documents.
+#[derive(Debug, Default)]
+pub struct NoIncludeResolver;
+
+impl IncludeResolver for NoIncludeResolver {
+    fn resolve(&self, _include: &str, _parent: Option<&str>) -> Result<Document, Error> {
+        Err(Error::message("includes are not allowed without an include resolver"))
+    }
+}
+
+/// File system resolver
+///
+/// Includes are relative to the including document directory or to the current
+/// working directory if the root document is not a file.
+#[derive(Debug, Default)]
+pub struct FileSystemResolver;
+
+impl IncludeResolver for FileSystemResolver {
+    fn resolve(&self, include: &str, parent: Option<&str>) -> Result<Document, Error> {
+        let path = match parent.and_then(|x| Path::new(x).parent()) {
+            Some(directory) => directory.join(include),
+            None => Path::new(include).to_path_buf(),
+        };
+        let name = path.to_string_lossy().to_string();
+
+        let source =
+            fs::read_to_string(&path).map_err(|e| Error::message(format!("unable to read '{}': {}", name, e)))?;
+        Ok(Document::new(name, source))
+    }
+}
+
+/// In-memory resolver
+///
+/// Documents are looked up by the `include` keyword value, useful when there's no
+/// file system (WebAssembly).
+#[derive(Debug, Default)]
+pub struct InMemoryResolver {
+    documents: HashMap<String, String>,
+}
+
+impl InMemoryResolver {
+    pub fn new() -> InMemoryResolver {
+        InMemoryResolver::default()
+    }
+
+    /// Inserts a document, returns previous source if the document was already present
+    pub fn insert<N, S>(&mut self, name: N, source: S) -> Option<String>
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.documents.insert(name.into(), source.into())
+    }
+}
+
+impl IncludeResolver for InMemoryResolver {
+    fn resolve(&self, include: &str, _parent: Option<&str>) -> Result<Document, Error> {
+        self.documents
+            .get(include)
+            .map(|source| Document::new(include, source.as_str()))
+            .ok_or_else(|| Error::message(format!("document '{}' not found", include)))
+    }
+}
+
+// Checks the included document root keywords in the source, keywords with default
+// values are not serialized
+fn check_included_keywords(source: &str) -> Result<(), Error> {
+    let root = source::parse(source)?;
+
+    for (key, _) in root.as_ref().and_then(|x| x.as_mapping()).unwrap_or_default() {
+        if let Some(keyword) = key.as_str().filter(|x| !INCLUDED_DOCUMENT_KEYWORDS.contains(x)) {
+            return Err(
+                Error::message(format!("keyword '{}' is not allowed in the included document", keyword))
+                    .with_position(key.position().line(), key.position().column())
+                    .with_schema_path(keyword),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Attaches the document name unless the error comes from a nested document
+fn in_document(error: Error, document: Option<&str>) -> Error {
+    match document {
+        Some(document) if error.file().is_none() => error.with_file(document),
+        _ => error,
+    }
+}
+
+struct Includer<'a> {
+    resolver: &'a dyn IncludeResolver,
+    mode: ParseMode,
+    // Documents being included, used to detect circular includes
+    stack: Vec<String>,
+    definitions: Vec<(String, Schema, String)>,
+    unknown: Vec<UnknownKeyword>,
+}
+
+impl<'a> Includer<'a> {
+    fn include(&mut self, include: &str, document: Option<&str>, path: &PathBuf) -> Result<Schema, Error> {
+        let included = self.resolver.resolve(include, document).map_err(|e| {
+            in_document(
+                Error::at_schema_path(path, format!("unable to include '{}': {}", include, e)),
+                document,
+            )
+        })?;
+        let name = included.name();
+
+        if self.stack.iter().any(|x| x == name) {
+            let mut cycle = self.stack.clone();
+            cycle.push(name.to_string());
+            return Err(in_document(
+                Error::at_schema_path(path, format!("circular include: {}", cycle.join(" -> "))),
+                document,
+            ));
+        }
+
+        let (mut schema, unknown) =
+            Schema::parse_document(included.source(), Some(self.mode)).map_err(|e| in_document(e, Some(name)))?;
+        self.unknown.extend(unknown.into_iter().map(|x| x.with_file(name)));
+
+        check_included_keywords(included.source()).map_err(|e| e.with_file(name))?;
+
+        self.stack.push(name.to_string());
+        self.expand(&mut schema, Some(name), &PathBuf::new())?;
+        self.stack.pop();
+
+        for (definition, definition_schema) in std::mem::take(&mut schema.definitions) {
+            self.definitions.push((definition, definition_schema, name.to_string()));
+        }
+
+        Ok(schema)
+    }
+
+    fn expand(&mut self, schema: &mut Schema, document: Option<&str>, path: &PathBuf) -> Result<(), Error> {
+        let mut properties = vec![];
+
+        for include in std::mem::take(&mut schema.include) {
+            let included = self.include(&include, document, path)?;
+            properties.extend(included.properties);
+        }
+
+        if !properties.is_empty() {
+            properties.append(&mut schema.properties);
+            schema.properties = properties;
+        }
+
+        for (idx, property) in schema.properties.iter_mut().enumerate() {
+            let mut property_path = path.clone();
+            property_path.push_property("properties");
+            property_path.push_index(idx);
+            property_path.push_property(property.name());
+            self.expand(property.schema_mut(), document, &property_path)?;
+        }
+
+        for (idx, items) in schema.items.iter_mut().enumerate() {
+            let mut items_path = path.clone();
+            items_path.push_property("items");
+            items_path.push_index(idx);
+            self.expand(items, document, &items_path)?;
+        }
+
+        if let Some(keys) = schema.keys.as_mut() {
+            let mut keys_path = path.clone();
+            keys_path.push_property("keys");
+            self.expand(keys, document, &keys_path)?;
+        }
+
+        if let Some(values) = schema.values.as_mut() {
+            let mut values_path = path.clone();
+            values_path.push_property("values");
+            self.expand(values, document, &values_path)?;
+        }
+
+        for (name, definition) in schema.definitions.iter_mut() {
+            let mut definition_path = path.clone();
+            definition_path.push_property("definitions");
+            definition_path.push_property(name.as_str());
+            self.expand(definition, document, &definition_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the document, expands includes and resolves references
+///
+/// # Arguments
+///
+/// * `name` - Root document name, `None` if the document is not a file
+/// * `source` - Root document source
+/// * `mode` - Unknown keywords handling, `None` for the root schema version default
+/// * `resolver` - Included documents resolver
+pub(crate) fn parse(
+    name: Option<&str>,
+    source: &str,
+    mode: Option<ParseMode>,
+    resolver: &dyn IncludeResolver,
+) -> Result<(Schema, Vec<UnknownKeyword>), Error> {
+    let (mut schema, mut unknown) = Schema::parse_document(source, mode).map_err(|e| in_document(e, name))?;
+    if let Some(name) = name {
+        unknown = unknown.into_iter().map(|x| x.with_file(name)).collect();
+    }
+
+    let mut includer = Includer {
+        resolver,
+        mode: mode
+            .unwrap_or_else(|| ParseMode::for_version(schema.version().unwrap_or_else(|| Version::default().value()))),
+        stack: name.into_iter().map(str::to_string).collect(),
+        definitions: vec![],
+        unknown: vec![],
+    };
+
+    includer.expand(&mut schema, name, &PathBuf::new())?;
+
+    for (definition, definition_schema, document) in includer.definitions {
+        if schema.definitions.contains_key(&definition) {
+            return Err(Error::message(format!("duplicate definition '{}'", definition))
+                .with_schema_path(format!("definitions.{}", definition))
+                .with_file(document));
+        }
+        schema.definitions.insert(definition, definition_schema);
+    }
+
+    unknown.extend(includer.unknown);
+
+    schema.resolve_references().map_err(|e| in_document(e, name))?;
+    Ok((schema, unknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(documents: &[(&str, &str)]) -> InMemoryResolver {
+        let mut resolver = InMemoryResolver::new();
+        for (name, source) in documents {
+            resolver.insert(*name, *source);
+        }
+        resolver
+    }
+
+    #[test]
+    fn include_definitions_and_nested_properties() {
+        let resolver = resolver(&[
+            (
+                "network.yml",
+                r##"
+                include: address.yml
+                definitions:
+                  network:
+                    properties:
+                      - ssid:
+                          type: string
+                      - gateway:
+                          $ref: "#/definitions/address"
+                "##,
+            ),
+            (
+                "address.yml",
+                r#"
+                definitions:
+                  address:
+                    type: ipv4
+                "#,
+            ),
+            (
+                "proxy.yml",
+                r#"
+                properties:
+                  - host:
+                      type: hostname
+                  - port:
+                      type: port
+                "#,
+            ),
+        ]);
+
+        let (schema, _) = Schema::parse_with_resolver(
+            r##"
+            include:
+              - network.yml
+            properties:
+              - networks:
+                  type: array
+                  items:
+                    $ref: "#/definitions/network"
+              - proxy:
+                  include: proxy.yml
+                  properties:
+                    - type:
+                        type: string
+            "##,
+            None,
+            &resolver,
+        )
+        .unwrap();
+
+        assert_eq!(schema.definitions().len(), 2);
+
+        let network = &schema.properties()[0].schema().items()[0];
+        assert_eq!(network.properties()[1].schema().r#type().to_string(), "ipv4");
+
+        let proxy: Vec<_> = schema.properties()[1]
+            .schema()
+            .properties()
+            .iter()
+            .map(|x| x.name())
+            .collect();
+        assert_eq!(proxy, vec!["host", "port", "type"]);
+    }
+
+    #[test]
+    fn error_in_included_document() {
+        let resolver = resolver(&[(
+            "proxy.yml",
+            r#"
+            properties:
+              - port:
+                  type: foo
+            "#,
+        )]);
+
+        let error = Schema::parse_with_resolver("include: proxy.yml", None, &resolver).unwrap_err();
+        assert_eq!(error.file(), Some("proxy.yml"));
+        assert_eq!(error.line(), Some(4));
+        assert_eq!(error.schema_path(), Some("properties[0].port.type"));
+        assert!(error.to_string().starts_with("proxy.yml: "));
+    }
+
+    #[test]
+    fn unknown_keyword_in_included_document() {
+        let resolver = resolver(&[(
+            "proxy.yml",
+            r#"
+            properties:
+              - port:
+                  type: port
+                  maximum: 10
+            "#,
+        )]);
+
+        let (_, unknown) = Schema::parse_with_resolver("include: proxy.yml", None, &resolver).unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].file(), Some("proxy.yml"));
+        assert_eq!(
+            unknown[0].to_string(),
+            "unknown keyword 'maximum' at 'proxy.yml' line 5, column 19 (schema path: 'properties[0].port.maximum')"
+        );
+    }
+
+    #[test]
+    fn circular_include() {
+        let resolver = resolver(&[("a.yml", "include: b.yml"), ("b.yml", "include: a.yml")]);
+
+        let error = Schema::parse_with_resolver("include: a.yml", None, &resolver).unwrap_err();
+        assert_eq!(error.file(), Some("b.yml"));
+        assert_eq!(error.to_string(), "b.yml: circular include: a.yml -> b.yml -> a.yml");
+    }
+
+    #[test]
+    fn missing_document() {
+        let error = Schema::parse_with_resolver("include: foo.yml", None, &InMemoryResolver::new()).unwrap_err();
+        assert_eq!(error.file(), None);
+        assert_eq!(
+            error.to_string(),
+            "unable to include 'foo.yml': document 'foo.yml' not found"
+        );
+    }
+
+    #[test]
+    fn reject_includes_without_resolver() {
+        let error = "include: /etc/passwd".parse::<Schema>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unable to include '/etc/passwd': includes are not allowed without an include resolver"
+        );
+    }
+
+    #[test]
+    fn duplicate_definition() {
+        let resolver = resolver(&[("a.yml", "definitions: { name: { type: string } }")]);
+
+        let error = Schema::parse_with_resolver(
+            "{ include: a.yml, definitions: { name: { type: string } } }",
+            None,
+            &resolver,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "a.yml: duplicate definition 'name'");
+    }
+
+    #[test]
+    fn included_document_keywords() {
+        let resolver = resolver(&[("a.yml", "title: Foo"), ("b.yml", "hidden: false")]);
+
+        let error = Schema::parse_with_resolver("include: b.yml", None, &resolver).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "b.yml: keyword 'hidden' is not allowed in the included document"
+        );
+
+        let error = Schema::parse_with_resolver("include: a.yml", None, &resolver).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "a.yml: keyword 'title' is not allowed in the included document"
+        );
+    }
+
+    #[test]
+    fn file_system_relative_includes() {
+        let directory = std::env::temp_dir().join(format!("jellyschema-include-{}", std::process::id()));
+        fs::create_dir_all(directory.join("common")).unwrap();
+        fs::write(
+            directory.join("device.yml"),
+            "version: 1\ninclude: common/network.yml\n",
+        )
+        .unwrap();
+        fs::write(directory.join("common/network.yml"), "include: ntp.yml\n").unwrap();
+        fs::write(
+            directory.join("common/ntp.yml"),
+            "properties:\n  - ntp:\n      type: stringlist\n",
+        )
+        .unwrap();
+
+        let result = Schema::parse_file(directory.join("device.yml"), None);
+        fs::remove_dir_all(&directory).unwrap();
+
+        let (schema, _) = result.unwrap();
+        assert_eq!(schema.properties()[0].name(), "ntp");
+    }
+}
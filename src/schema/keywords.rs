@@ -0,0 +1,521 @@
+//! Known keywords
+//!
+//! `Schema` deserialization ignores unknown keywords. Typos like `minLenght` silently
+//! disappear. Schema source is checked against the list of known keywords before it's
+//! deserialized.
+use std::fmt;
+
+use serde_derive::Serialize;
+
+use crate::{
+    schema::source::{Node, Position},
+    validator::path::PathBuf,
+};
+
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "version",
+    "revision",
+    "migrations",
+    "include",
+    "definitions",
+    "$ref",
+    "mapping",
+    "type",
+    "const",
+    "default",
+    "enum",
+    "formula",
+    "when",
+    "readOnly",
+    "writeOnly",
+    "placeholder",
+    "hidden",
+    "properties",
+    "keys",
+    "values",
+    "additionalProperties",
+    "separator",
+    "title",
+    "help",
+    "warning",
+    "description",
+    "collapsible",
+    "collapsed",
+    "items",
+    "discriminator",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "orderable",
+    "addable",
+    "removable",
+    "multipleOf",
+    "max",
+    "exclusiveMax",
+    "min",
+    "exclusiveMin",
+    "maxLength",
+    "minLength",
+    "pattern",
+];
+
+const MAPPING_KEYWORDS: &[&str] = &["targets", "target", "filename", "path", "template"];
+const TARGET_KEYWORDS: &[&str] = &["type", "format", "glob", "location"];
+const LOCATION_KEYWORDS: &[&str] = &["partition", "path"];
+const FILENAME_KEYWORDS: &[&str] = &["formula"];
+const ENUM_ENTRY_KEYWORDS: &[&str] = &["title", "value"];
+const MIGRATION_KEYWORDS: &[&str] = &["revision", "operations"];
+const OPERATION_KEYWORDS: &[&str] = &["move", "remove", "convert", "set"];
+const MOVE_KEYWORDS: &[&str] = &["from", "to"];
+const CONVERT_KEYWORDS: &[&str] = &["path", "type", "separator"];
+const SET_KEYWORDS: &[&str] = &["path", "value"];
+
+/// Checks if the keyword is a known schema keyword
+pub(crate) fn is_schema_keyword(keyword: &str) -> bool {
+    SCHEMA_KEYWORDS.contains(&keyword)
+}
+
+/// Unknown keywords handling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseMode {
+    /// Unknown keyword is an error
+    Strict,
+    /// Unknown keywords are reported as warnings
+    Lenient,
+}
+
+impl ParseMode {
+    /// Returns default mode for the schema version
+    ///
+    /// Version 1 schemas are parsed in the lenient mode (backward compatibility),
+    /// newer versions in the strict mode.
+    pub fn for_version(version: u8) -> ParseMode {
+        if version > 1 {
+            ParseMode::Strict
+        } else {
+            ParseMode::Lenient
+        }
+    }
+}
+
+/// Unknown keyword found in the schema source
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnknownKeyword {
+    keyword: String,
+    #[serde(rename = "schemaPath")]
+    schema_path: String,
+    line: usize,
+    column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+}
+
+impl UnknownKeyword {
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    /// Path to the keyword itself
+    pub fn schema_path(&self) -> &str {
+        &self.schema_path
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Schema document name, `None` for the root document
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub(crate) fn with_file<S>(self, file: S) -> UnknownKeyword
+    where
+        S: Into<String>,
+    {
+        UnknownKeyword {
+            file: Some(file.into()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for UnknownKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown keyword '{}' at ", self.keyword)?;
+        if let Some(file) = &self.file {
+            write!(f, "'{}' ", file)?;
+        }
+        write!(
+            f,
+            "line {}, column {} (schema path: '{}')",
+            self.line, self.column, self.schema_path
+        )
+    }
+}
+
+/// Schema `type` keyword value found in the schema source
+pub(crate) struct TypeKeyword {
+    pub value: String,
+    pub schema_path: String,
+    pub position: Position,
+}
+
+/// Schema keyword found along with the `$ref` keyword in the schema source
+pub(crate) struct ReferenceKeyword {
+    pub keyword: String,
+    pub schema_path: String,
+    pub position: Position,
+}
+
+#[derive(Default)]
+struct Checker {
+    unknown: Vec<UnknownKeyword>,
+    types: Vec<TypeKeyword>,
+    references: Vec<ReferenceKeyword>,
+}
+
+impl Checker {
+    // Checks mapping keys against known keywords, returns known (keyword, value) pairs
+    fn check_keywords<'a>(&mut self, node: &'a Node, path: &PathBuf, known: &[&str]) -> Vec<(&'a str, &'a Node)> {
+        let entries = match node.as_mapping() {
+            Some(x) => x,
+            None => return vec![],
+        };
+
+        let mut result = vec![];
+
+        for (key, value) in entries {
+            let keyword = match key.as_str() {
+                Some(x) => x,
+                None => continue,
+            };
+
+            if known.contains(&keyword) {
+                result.push((keyword, value));
+            } else {
+                let mut schema_path = path.clone();
+                schema_path.push_property(keyword);
+                self.unknown.push(UnknownKeyword {
+                    keyword: keyword.to_string(),
+                    schema_path: schema_path.to_string(),
+                    line: key.position().line(),
+                    column: key.position().column(),
+                    file: None,
+                });
+            }
+        }
+
+        result
+    }
+
+    fn check_target(&mut self, node: &Node, path: &PathBuf) {
+        for (keyword, value) in self.check_keywords(node, path, TARGET_KEYWORDS) {
+            if keyword == "location" {
+                let mut nested_path = path.clone();
+                nested_path.push_property(keyword);
+                self.check_keywords(value, &nested_path, LOCATION_KEYWORDS);
+            }
+        }
+    }
+
+    fn check_mapping(&mut self, node: &Node, path: &PathBuf) {
+        for (keyword, value) in self.check_keywords(node, path, MAPPING_KEYWORDS) {
+            let mut nested_path = path.clone();
+            nested_path.push_property(keyword);
+
+            match keyword {
+                "targets" => {
+                    for (name, target) in value.as_mapping().unwrap_or_default() {
+                        let mut target_path = nested_path.clone();
+                        target_path.push_property(name.as_str().unwrap_or_default());
+                        self.check_target(target, &target_path);
+                    }
+                }
+                "target" => self.check_target(value, &nested_path),
+                "filename" => {
+                    self.check_keywords(value, &nested_path, FILENAME_KEYWORDS);
+                }
+                _ => {}
+            };
+        }
+    }
+
+    fn check_migration(&mut self, node: &Node, path: &PathBuf) {
+        for (keyword, value) in self.check_keywords(node, path, MIGRATION_KEYWORDS) {
+            if keyword != "operations" {
+                continue;
+            }
+
+            for (idx, operation) in value.as_sequence().unwrap_or_default().iter().enumerate() {
+                let mut operation_path = path.clone();
+                operation_path.push_property(keyword);
+                operation_path.push_index(idx);
+
+                for (name, arguments) in self.check_keywords(operation, &operation_path, OPERATION_KEYWORDS) {
+                    let mut arguments_path = operation_path.clone();
+                    arguments_path.push_property(name);
+
+                    let known = match name {
+                        "move" => MOVE_KEYWORDS,
+                        "convert" => CONVERT_KEYWORDS,
+                        "set" => SET_KEYWORDS,
+                        _ => continue,
+                    };
+                    self.check_keywords(arguments, &arguments_path, known);
+                }
+            }
+        }
+    }
+
+    fn check_schema(&mut self, node: &Node, path: &PathBuf) {
+        if node.get("$ref").is_some() {
+            for (key, _) in node.as_mapping().unwrap_or_default() {
+                match key.as_str() {
+                    Some(keyword) if keyword != "$ref" && is_schema_keyword(keyword) => {
+                        self.references.push(ReferenceKeyword {
+                            keyword: keyword.to_string(),
+                            schema_path: path.to_string(),
+                            position: key.position(),
+                        })
+                    }
+                    _ => {}
+                };
+            }
+        }
+
+        for (keyword, value) in self.check_keywords(node, path, SCHEMA_KEYWORDS) {
+            let mut nested_path = path.clone();
+            nested_path.push_property(keyword);
+
+            match keyword {
+                "type" => {
+                    if let Some(x) = value.as_str() {
+                        self.types.push(TypeKeyword {
+                            value: x.to_string(),
+                            schema_path: nested_path.to_string(),
+                            position: value.position(),
+                        });
+                    }
+                }
+                "mapping" => self.check_mapping(value, &nested_path),
+                "migrations" => {
+                    for (idx, migration) in value.as_sequence().unwrap_or_default().iter().enumerate() {
+                        let mut migration_path = nested_path.clone();
+                        migration_path.push_index(idx);
+                        self.check_migration(migration, &migration_path);
+                    }
+                }
+                "properties" => {
+                    for (idx, property) in value.as_sequence().unwrap_or_default().iter().enumerate() {
+                        for (name, schema) in property.as_mapping().unwrap_or_default() {
+                            let mut property_path = nested_path.clone();
+                            property_path.push_index(idx);
+                            property_path.push_property(name.as_str().unwrap_or_default());
+                            self.check_schema(schema, &property_path);
+                        }
+                    }
+                }
+                "items" => match value.as_sequence() {
+                    Some(items) => {
+                        for (idx, schema) in items.iter().enumerate() {
+                            let mut items_path = nested_path.clone();
+                            items_path.push_index(idx);
+                            self.check_schema(schema, &items_path);
+                        }
+                    }
+                    None => {
+                        nested_path.push_index(0);
+                        self.check_schema(value, &nested_path);
+                    }
+                },
+                "keys" | "values" => self.check_schema(value, &nested_path),
+                "definitions" => {
+                    for (name, schema) in value.as_mapping().unwrap_or_default() {
+                        let mut definition_path = nested_path.clone();
+                        definition_path.push_property(name.as_str().unwrap_or_default());
+                        self.check_schema(schema, &definition_path);
+                    }
+                }
+                "enum" => {
+                    for (idx, entry) in value.as_sequence().unwrap_or_default().iter().enumerate() {
+                        let mut entry_path = nested_path.clone();
+                        entry_path.push_index(idx);
+                        self.check_keywords(entry, &entry_path, ENUM_ENTRY_KEYWORDS);
+                    }
+                }
+                _ => {}
+            };
+        }
+    }
+}
+
+fn check(root: &Node) -> Checker {
+    let mut checker = Checker::default();
+    checker.check_schema(root, &PathBuf::new());
+    checker
+}
+
+/// Returns all unknown keywords in the schema source
+pub fn unknown_keywords(root: &Node) -> Vec<UnknownKeyword> {
+    check(root).unknown
+}
+
+/// Returns all schema `type` keywords in the schema source
+pub(crate) fn type_keywords(root: &Node) -> Vec<TypeKeyword> {
+    check(root).types
+}
+
+/// Returns all schema keywords combined with the `$ref` keyword in the schema source
+pub(crate) fn reference_keywords(root: &Node) -> Vec<ReferenceKeyword> {
+    check(root).references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::source::parse;
+
+    fn unknown(source: &str) -> Vec<(String, String, usize, usize)> {
+        unknown_keywords(&parse(source).unwrap().unwrap())
+            .into_iter()
+            .map(|x| {
+                (
+                    x.keyword().to_string(),
+                    x.schema_path().to_string(),
+                    x.line(),
+                    x.column(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn known_keywords() {
+        let source = r#"
+version: 1
+mapping:
+  targets:
+    config-json:
+      type: file
+      format: json
+      location:
+        partition: resin-boot
+        path: /config.json
+properties:
+  - name:
+      type: string
+      minLength: 1
+      enum:
+        - foo
+        - title: Bar
+          value: bar
+      mapping:
+        target: config-json
+        path: name
+        template:
+          anything: goes
+  - list:
+      type: array
+      items:
+        type: string
+revision: 2
+migrations:
+  - revision: 2
+    operations:
+      - move:
+          from: foo
+          to: bar
+      - remove: baz
+      - convert:
+          path: list
+          type: string-list
+          separator: ","
+      - set:
+          path: name
+          value: foo
+"#;
+        assert!(unknown(source).is_empty());
+    }
+
+    #[test]
+    fn unknown_migration_keywords() {
+        let source = r#"
+migrations:
+  - revision: 2
+    operations:
+      - rename:
+          from: foo
+          to: bar
+      - move:
+          form: foo
+          to: bar
+"#;
+        assert_eq!(
+            unknown(source),
+            vec![
+                (
+                    "rename".to_string(),
+                    "migrations[0].operations[0].rename".to_string(),
+                    5,
+                    9
+                ),
+                (
+                    "form".to_string(),
+                    "migrations[0].operations[1].move.form".to_string(),
+                    9,
+                    11
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_keywords_with_location() {
+        let source = r#"
+version: 1
+colapsed: true
+mapping:
+  targets:
+    config-json:
+      type: file
+      fromat: json
+properties:
+  - name:
+      type: string
+      minLenght: 1
+  - list:
+      type: array
+      items:
+        typo: string
+"#;
+        assert_eq!(
+            unknown(source),
+            vec![
+                ("colapsed".to_string(), "colapsed".to_string(), 3, 1),
+                (
+                    "fromat".to_string(),
+                    "mapping.targets.config-json.fromat".to_string(),
+                    8,
+                    7
+                ),
+                (
+                    "minLenght".to_string(),
+                    "properties[0].name.minLenght".to_string(),
+                    12,
+                    7
+                ),
+                (
+                    "typo".to_string(),
+                    "properties[1].list.items[0].typo".to_string(),
+                    16,
+                    9
+                ),
+            ]
+        );
+    }
+}
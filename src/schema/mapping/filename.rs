@@ -1,7 +1,10 @@
-use serde::de;
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
 use serde_json::Value;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FileName {
     /// A real file name
     Name(String),
@@ -25,6 +28,22 @@ impl FileName {
     }
 }
 
+impl ser::Serialize for FileName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            FileName::Name(name) => serializer.serialize_str(name),
+            FileName::Formula(formula) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("formula", formula)?;
+                map.end()
+            }
+        }
+    }
+}
+
 impl<'de> de::Deserialize<'de> for FileName {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -3,13 +3,12 @@
 //! do it without it. If not, I'll put it back. But less stuff we have, more
 //! better it is.
 //!
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use serde_derive::Deserialize;
+use serde::ser;
+use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::deref::OptionDeref;
-
 pub use self::{
     filename::FileName,
     target::{LocationPartition, RawTarget, Target, TargetFormat, TargetLocation, TargetType},
@@ -19,9 +18,13 @@ mod filename;
 mod target;
 
 /// Mapping structure
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Mapping {
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_targets"
+    )]
     targets: HashMap<String, RawTarget>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -38,6 +41,11 @@ pub struct Mapping {
 }
 
 impl Mapping {
+    /// Creates a new mapping builder
+    pub fn builder() -> MappingBuilder {
+        MappingBuilder::default()
+    }
+
     pub fn targets(&self) -> &HashMap<String, RawTarget> {
         &self.targets
     }
@@ -59,6 +67,73 @@ impl Mapping {
     }
 }
 
+/// Mapping builder
+///
+/// See [`SchemaBuilder::mapping`](../struct.SchemaBuilder.html#method.mapping).
+#[derive(Debug, Default)]
+pub struct MappingBuilder {
+    mapping: Mapping,
+}
+
+impl MappingBuilder {
+    /// Declares a named target (`mapping.targets`)
+    pub fn declare_target<S>(mut self, name: S, target: RawTarget) -> MappingBuilder
+    where
+        S: Into<String>,
+    {
+        self.mapping.targets.insert(name.into(), target);
+        self
+    }
+
+    /// Selects a declared target by name (`mapping.target`)
+    pub fn target_reference<S>(mut self, name: S) -> MappingBuilder
+    where
+        S: Into<String>,
+    {
+        self.mapping.target = Some(Target::Reference(name.into()));
+        self
+    }
+
+    /// Selects an inline target (`mapping.target`)
+    pub fn target(mut self, target: RawTarget) -> MappingBuilder {
+        self.mapping.target = Some(Target::Raw(target));
+        self
+    }
+
+    /// Sets the `fileset` target file name (`mapping.filename`)
+    pub fn filename(mut self, filename: FileName) -> MappingBuilder {
+        self.mapping.filename = Some(filename);
+        self
+    }
+
+    /// Sets the path in the target document (`mapping.path`)
+    pub fn path<S>(mut self, path: S) -> MappingBuilder
+    where
+        S: Into<String>,
+    {
+        self.mapping.path = Some(path.into());
+        self
+    }
+
+    /// Sets the base document (`mapping.template`)
+    pub fn template(mut self, template: Value) -> MappingBuilder {
+        self.mapping.template = Some(template);
+        self
+    }
+
+    pub fn build(self) -> Mapping {
+        self.mapping
+    }
+}
+
+// Targets are serialized in the alphabetical order, HashMap order is random
+fn serialize_sorted_targets<S>(targets: &HashMap<String, RawTarget>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    ser::Serialize::serialize(&targets.iter().collect::<BTreeMap<_, _>>(), serializer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,42 +1,52 @@
 use std::fmt;
 
-use serde::de;
-use serde_derive::Deserialize;
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
+use serde_derive::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::deref::OptionDeref;
-
 /// Target type
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub enum TargetType {
     /// Single file
     #[serde(rename = "file")]
     File,
     /// List of files
     ///
-    /// This type allows you to use glob patterns in `Location.path`.
+    /// `Location.path` is a directory and the `glob` pattern selects files in it.
     #[serde(rename = "fileset")]
     FileSet,
 }
 
-impl TargetType {
-    pub fn is_file(self) -> bool {
+impl AsRef<str> for TargetType {
+    fn as_ref(&self) -> &str {
         match self {
-            TargetType::File => true,
-            _ => false,
+            TargetType::File => "file",
+            TargetType::FileSet => "fileset",
         }
     }
+}
+
+impl fmt::Display for TargetType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+impl TargetType {
+    pub fn is_file(self) -> bool {
+        matches!(self, TargetType::File)
+    }
 
     pub fn is_file_set(self) -> bool {
-        match self {
-            TargetType::FileSet => true,
-            _ => false,
-        }
+        matches!(self, TargetType::FileSet)
     }
 }
 
 /// Target file format
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub enum TargetFormat {
     #[serde(rename = "ini")]
     Ini,
@@ -50,44 +60,47 @@ pub enum TargetFormat {
     Redsocks,
 }
 
-impl TargetFormat {
-    pub fn is_ini(self) -> bool {
+impl AsRef<str> for TargetFormat {
+    fn as_ref(&self) -> &str {
         match self {
-            TargetFormat::Ini => true,
-            _ => false,
+            TargetFormat::Ini => "ini",
+            TargetFormat::Json => "json",
+            TargetFormat::Binary => "binary",
+            TargetFormat::Text => "text",
+            TargetFormat::Redsocks => "redsocks",
         }
     }
+}
+
+impl fmt::Display for TargetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+impl TargetFormat {
+    pub fn is_ini(self) -> bool {
+        matches!(self, TargetFormat::Ini)
+    }
 
     pub fn is_json(self) -> bool {
-        match self {
-            TargetFormat::Json => true,
-            _ => false,
-        }
+        matches!(self, TargetFormat::Json)
     }
 
     pub fn is_binary(self) -> bool {
-        match self {
-            TargetFormat::Binary => true,
-            _ => false,
-        }
+        matches!(self, TargetFormat::Binary)
     }
 
     pub fn is_text(self) -> bool {
-        match self {
-            TargetFormat::Text => true,
-            _ => false,
-        }
+        matches!(self, TargetFormat::Text)
     }
 
     pub fn is_redsocks(self) -> bool {
-        match self {
-            TargetFormat::Redsocks => true,
-            _ => false,
-        }
+        matches!(self, TargetFormat::Redsocks)
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum LocationPartition {
     Index(u8),
     Uuid(Uuid),
@@ -117,13 +130,33 @@ impl LocationPartition {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+impl fmt::Display for LocationPartition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationPartition::Index(index) => write!(f, "{}", index),
+            LocationPartition::Uuid(uuid) => write!(f, "{}", uuid),
+            LocationPartition::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
 pub struct TargetLocation {
     path: String,
     partition: LocationPartition,
 }
 
 impl TargetLocation {
+    pub fn new<S>(partition: LocationPartition, path: S) -> TargetLocation
+    where
+        S: Into<String>,
+    {
+        TargetLocation {
+            path: path.into(),
+            partition,
+        }
+    }
+
     pub fn path(&self) -> &str {
         &self.path
     }
@@ -133,16 +166,35 @@ impl TargetLocation {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct RawTarget {
     #[serde(rename = "type")]
     type_: TargetType,
     format: TargetFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
     glob: Option<String>,
     location: TargetLocation,
 }
 
 impl RawTarget {
+    pub fn new(type_: TargetType, format: TargetFormat, location: TargetLocation) -> RawTarget {
+        RawTarget {
+            type_,
+            format,
+            glob: None,
+            location,
+        }
+    }
+
+    /// Sets the file name glob pattern of the `fileset` target
+    pub fn with_glob<S>(mut self, glob: S) -> RawTarget
+    where
+        S: Into<String>,
+    {
+        self.glob = Some(glob.into());
+        self
+    }
+
     pub fn type_(&self) -> &TargetType {
         &self.type_
     }
@@ -160,7 +212,7 @@ impl RawTarget {
     }
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Target {
     Reference(String),
@@ -196,7 +248,7 @@ impl<'de> de::Visitor<'de> for PartitionVisitor {
     where
         E: de::Error,
     {
-        if v > u64::from(std::u8::MAX) {
+        if v > u64::from(u8::MAX) {
             return Err(de::Error::custom("partition index out of bounds"));
         }
         Ok(LocationPartition::Index(v as u8))
@@ -214,6 +266,31 @@ impl<'de> de::Visitor<'de> for PartitionVisitor {
     }
 }
 
+impl ser::Serialize for LocationPartition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            LocationPartition::Index(index) => serializer.serialize_u8(*index),
+            LocationPartition::Uuid(uuid) => serializer.serialize_str(&uuid.to_string()),
+            LocationPartition::Label(label) => serializer.serialize_str(label),
+        }
+    }
+}
+
+impl ser::Serialize for TargetLocation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("partition", &self.partition)?;
+        map.serialize_entry("path", &self.path)?;
+        map.end()
+    }
+}
+
 impl<'de> de::Deserialize<'de> for LocationPartition {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
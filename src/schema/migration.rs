@@ -0,0 +1,86 @@
+//! Data migrations
+//!
+//! The root schema can declare its `revision` and `migrations` transforming
+//! configuration data from the previous revisions. Every migration upgrades the data
+//! to its `revision` with a list of operations:
+//!
+//! * `move` - moves (renames) the value, `from` & `to` are dotted paths (`proxy.type`),
+//!   missing `from` value is skipped and an existing `to` value is an error,
+//! * `remove` - removes the value,
+//! * `convert` - converts the value to another `type` (`string` to `string-list`, ...),
+//! * `set` - sets the `value`.
+//!
+//! See [`migrate`](../../migration/fn.migrate.html) for more info.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::{Operation, Schema};
+//!
+//! let schema: Schema = r#"
+//!   version: 2
+//!   revision: 2
+//!   properties:
+//!     - host:
+//!         type: hostname
+//!   migrations:
+//!     - revision: 2
+//!       operations:
+//!         - move:
+//!             from: hostname
+//!             to: host
+//! "#.parse().unwrap();
+//!
+//! assert_eq!(schema.revision(), Some(2));
+//! assert_eq!(
+//!     schema.migrations()[0].operations()[0],
+//!     Operation::Move { from: "hostname".to_string(), to: "host".to_string() }
+//! );
+//! ```
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::schema::PrimitiveType;
+
+/// Migration of the configuration data to the schema revision
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Migration {
+    revision: u32,
+    #[serde(default)]
+    operations: Vec<Operation>,
+}
+
+impl Migration {
+    pub fn new(revision: u32, operations: Vec<Operation>) -> Migration {
+        Migration { revision, operations }
+    }
+
+    /// Schema revision the data are migrated to
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+}
+
+/// Data migration operation
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// Moves the value
+    Move { from: String, to: String },
+    /// Removes the value
+    Remove(String),
+    /// Converts the value to another type, `separator` is used to split / join lists
+    Convert {
+        path: String,
+        #[serde(rename = "type", deserialize_with = "super::raw::deserialize_from_str")]
+        r#type: PrimitiveType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        separator: Option<String>,
+    },
+    /// Sets the value
+    Set { path: String, value: Value },
+}
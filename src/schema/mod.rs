@@ -1,27 +1,49 @@
-use std::fmt;
-use std::marker::PhantomData;
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::str::FromStr;
 
 use regex::Regex;
-use serde_derive::Deserialize;
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
 use serde_json::{Number, Value};
 
 // Reexport everything except mapping, which is a public module
 pub use self::{
+    annotation::Annotation,
+    builder::SchemaBuilder,
+    diff::{diff, Change, Compatibility, Diff},
+    include::{Document, FileSystemResolver, InMemoryResolver, IncludeResolver, NoIncludeResolver},
+    keywords::{ParseMode, UnknownKeyword},
+    migration::{Migration, Operation},
     property::Property,
     r#enum::EnumEntry,
     r#type::{PrimitiveType, Type},
     unique_items::UniqueItems,
+    upgrade::upgrade,
     version::Version,
 };
 
-use crate::{deref::OptionDeref, error::Error};
+use crate::error::Error;
 
+mod annotation;
+mod builder;
+mod definitions;
+mod diff;
+mod discriminator;
 mod r#enum;
+mod include;
+mod keywords;
 pub mod mapping;
+mod migration;
+mod overlay;
 mod property;
+mod raw;
+mod source;
 mod r#type;
 mod unique_items;
+mod upgrade;
 mod version;
 
 /// JellySchema structure
@@ -32,126 +54,151 @@ mod version;
 /// `serde_json` structures like `Value`, `Number` or Rust types. The reason is
 /// that we're generating JSON values from the JellySchema. And this allows us
 /// to catch missing JSON features (when compared with YAML) during deserialization.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default)]
 pub struct Schema {
-    #[serde(default)]
     version: Option<Version>,
     //
+    // Data migrations
+    //
+    revision: Option<u32>,
+    migrations: Vec<migration::Migration>,
+    //
+    // Definitions & references
+    //
+    include: Vec<String>,
+    definitions: BTreeMap<String, Schema>,
+    r#ref: Option<String>,
+    // Referencing schema as it was written, set when the reference is resolved
+    unresolved: Option<Box<Schema>>,
+    //
     // Mapping extension
     //
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     mapping: Option<mapping::Mapping>,
     //
     // Any instance type validation keywords
     //
-    #[serde(default, rename = "type", deserialize_with = "deserialize_from_str")]
     r#type: Type,
-    #[serde(default, rename = "const", skip_serializing_if = "Option::is_none")]
     r#const: Option<Value>,
-    #[serde(default, rename = "default", skip_serializing_if = "Option::is_none")]
     r#default: Option<Value>,
-    #[serde(default, rename = "enum", skip_serializing_if = "Vec::is_empty")]
     r#enum: Vec<EnumEntry>,
-    #[serde(
-        default,
-        deserialize_with = "deserialize_as_optional_string",
-        skip_serializing_if = "Option::is_none"
-    )]
     formula: Option<String>,
-    #[serde(default, rename = "readOnly")]
+    when: Option<String>,
     read_only: bool,
-    #[serde(default, rename = "writeOnly")]
     write_only: bool,
-    #[serde(default)]
-    placeholder: Option<String>,
-    #[serde(default)]
+    placeholder: Option<Annotation>,
     hidden: bool,
     //
     // Object validation keywords
     //
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     properties: Vec<Property>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     keys: Option<Box<Schema>>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     values: Option<Box<Schema>>,
-    #[serde(default, rename = "additionalProperties")]
     additional_properties: bool,
     //
     // StringList keywords
     //
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     separator: Option<String>,
     //
     // Annotation keywords
     //
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    title: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    help: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    warning: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    description: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<Annotation>,
+    help: Option<Annotation>,
+    warning: Option<Annotation>,
+    description: Option<Annotation>,
     collapsible: Option<bool>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     collapsed: Option<bool>,
     //
     // Array validation keywords
     //
-    #[serde(
-        default,
-        skip_serializing_if = "Vec::is_empty",
-        deserialize_with = "deserialize_struct_or_vec"
-    )]
     items: Vec<Schema>,
-    #[serde(default, rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    discriminator: Option<String>,
     max_items: Option<usize>,
-    #[serde(default, rename = "minItems", skip_serializing_if = "Option::is_none")]
     min_items: Option<usize>,
-    #[serde(default, rename = "uniqueItems")]
     unique_items: UniqueItems,
-    #[serde(default)]
     orderable: Option<bool>,
-    #[serde(default)]
     addable: Option<bool>,
-    #[serde(default)]
     removable: Option<bool>,
     //
     // Number validation keywords
     //
-    #[serde(default, rename = "multipleOf", skip_serializing_if = "Option::is_none")]
     multiple_of: Option<Number>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     max: Option<Number>,
-    #[serde(default, rename = "exclusiveMax", skip_serializing_if = "Option::is_none")]
     exclusive_max: Option<Number>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     min: Option<Number>,
-    #[serde(default, rename = "exclusiveMin", skip_serializing_if = "Option::is_none")]
     exclusive_min: Option<Number>,
     //
     // String based types validation keywords
     //
-    #[serde(default, rename = "maxLength", skip_serializing_if = "Option::is_none")]
     max_length: Option<usize>,
-    #[serde(default, rename = "minLength", skip_serializing_if = "Option::is_none")]
     min_length: Option<usize>,
-    #[serde(
-        default,
-        skip_serializing_if = "Option::is_none",
-        deserialize_with = "deserialize_option_from_str"
-    )]
     pattern: Option<Regex>,
 }
 
 impl Schema {
+    /// Creates a new schema builder
+    ///
+    /// See [`SchemaBuilder`](struct.SchemaBuilder.html) for more info.
+    pub fn builder() -> SchemaBuilder {
+        <SchemaBuilder as Default>::default()
+    }
+
     pub fn version(&self) -> Option<u8> {
         self.version.as_ref().map(Version::value)
     }
 }
 
+//
+// Data migrations
+//
+impl Schema {
+    /// Schema revision (root schema only), configuration data are migrated to it
+    pub fn revision(&self) -> Option<u32> {
+        self.revision
+    }
+
+    /// Configuration data migrations from the previous revisions (root schema only)
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+}
+
+//
+// Definitions & references
+//
+impl Schema {
+    /// Returns reusable subschemas (root schema only)
+    pub fn definitions(&self) -> &BTreeMap<String, Schema> {
+        &self.definitions
+    }
+
+    /// Returns the reference (`#/definitions/name`) this schema was resolved from
+    ///
+    /// References are resolved when the schema is parsed, the schema itself contains
+    /// all the definition keywords.
+    pub fn r#ref(&self) -> Option<&str> {
+        self.r#ref.as_deref()
+    }
+
+    // Resolves `$ref` keywords & checks discriminators, must be called on the root schema
+    // after deserialization
+    pub(crate) fn resolve_references(&mut self) -> Result<(), Error> {
+        definitions::resolve(self)?;
+        discriminator::check(self)
+    }
+
+    // Checks if the reference has keywords overriding the definition ones
+    pub(crate) fn overrides_reference(&self) -> bool {
+        self.unresolved.as_ref().is_some_and(|x| {
+            x.title.is_some()
+                || x.help.is_some()
+                || x.warning.is_some()
+                || x.description.is_some()
+                || x.placeholder.is_some()
+                || x.when.is_some()
+        })
+    }
+}
+
 //
 // Any instance type
 //
@@ -176,8 +223,22 @@ impl Schema {
         self.formula.as_deref()
     }
 
+    /// Condition controlling whether the property is active
+    ///
+    /// An expression evaluated at the property position (`super` refers to the parent
+    /// object). Property is active only if the expression evaluates to `true`, inactive
+    /// property is neither required nor validated and its default value is not filled.
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
+
     pub fn placeholder(&self) -> Option<&str> {
-        self.placeholder.as_deref()
+        self.placeholder.as_ref().map(Annotation::text)
+    }
+
+    /// `placeholder` in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn placeholder_in(&self, locale: &str) -> Option<&str> {
+        self.placeholder.as_ref().map(|x| x.get(locale))
     }
 
     pub fn read_only(&self) -> bool {
@@ -237,19 +298,39 @@ impl Schema {
 //
 impl Schema {
     pub fn title(&self) -> Option<&str> {
-        self.title.as_deref()
+        self.title.as_ref().map(Annotation::text)
+    }
+
+    /// `title` in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn title_in(&self, locale: &str) -> Option<&str> {
+        self.title.as_ref().map(|x| x.get(locale))
     }
 
     pub fn help(&self) -> Option<&str> {
-        self.help.as_deref()
+        self.help.as_ref().map(Annotation::text)
+    }
+
+    /// `help` in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn help_in(&self, locale: &str) -> Option<&str> {
+        self.help.as_ref().map(|x| x.get(locale))
     }
 
     pub fn warning(&self) -> Option<&str> {
-        self.warning.as_deref()
+        self.warning.as_ref().map(Annotation::text)
+    }
+
+    /// `warning` in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn warning_in(&self, locale: &str) -> Option<&str> {
+        self.warning.as_ref().map(|x| x.get(locale))
     }
 
     pub fn description(&self) -> Option<&str> {
-        self.description.as_deref()
+        self.description.as_ref().map(Annotation::text)
+    }
+
+    /// `description` in the given locale, see [`Annotation::get`](struct.Annotation.html#method.get)
+    pub fn description_in(&self, locale: &str) -> Option<&str> {
+        self.description.as_ref().map(|x| x.get(locale))
     }
 
     pub fn collapsed(&self) -> Option<bool> {
@@ -311,6 +392,23 @@ impl Schema {
         self.items.as_slice()
     }
 
+    /// Name of the property selecting the items schema
+    ///
+    /// Every items schema has this property with the `const` keyword and an array item
+    /// is validated against the schema with the matching value only.
+    pub fn discriminator(&self) -> Option<&str> {
+        self.discriminator.as_deref()
+    }
+
+    /// Returns items schema (and its index) selected by the discriminator property value
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Array item
+    pub fn discriminated_items(&self, item: &Value) -> Option<(usize, &Schema)> {
+        discriminator::select(self, item)
+    }
+
     pub fn max_items(&self) -> Option<usize> {
         self.max_items
     }
@@ -336,80 +434,423 @@ impl Schema {
     }
 }
 
+impl Schema {
+    /// Parses the schema, unknown keywords are handled according to the schema version
+    ///
+    /// See [`ParseMode::for_version`](enum.ParseMode.html#method.for_version).
+    pub fn parse(s: &str) -> Result<(Schema, Vec<UnknownKeyword>), Error> {
+        Schema::parse_with_mode(s, None)
+    }
+
+    /// Parses the schema with the unknown keywords handling mode
+    ///
+    /// Returns the schema and unknown keywords found in the lenient mode. Fails
+    /// with the first unknown keyword in the strict mode. The `include` keyword is
+    /// rejected, see [`parse_with_resolver`](#method.parse_with_resolver).
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Schema source
+    /// * `mode` - Unknown keywords handling, `None` for the schema version default
+    pub fn parse_with_mode(s: &str, mode: Option<ParseMode>) -> Result<(Schema, Vec<UnknownKeyword>), Error> {
+        Schema::parse_with_resolver(s, mode, &NoIncludeResolver)
+    }
+
+    /// Parses the schema, included documents are loaded with the `resolver`
+    ///
+    /// See [`IncludeResolver`](trait.IncludeResolver.html) for more info.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Schema source
+    /// * `mode` - Unknown keywords handling, `None` for the schema version default
+    /// * `resolver` - Included documents resolver
+    pub fn parse_with_resolver(
+        s: &str,
+        mode: Option<ParseMode>,
+        resolver: &dyn IncludeResolver,
+    ) -> Result<(Schema, Vec<UnknownKeyword>), Error> {
+        include::parse(None, s, mode, resolver)
+    }
+
+    /// Parses the schema file, included documents are loaded from the file system
+    ///
+    /// Relative includes are relative to the including document directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Schema file path
+    /// * `mode` - Unknown keywords handling, `None` for the schema version default
+    pub fn parse_file<P>(path: P, mode: Option<ParseMode>) -> Result<(Schema, Vec<UnknownKeyword>), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let resolver = FileSystemResolver;
+        let document = resolver.resolve(&path.as_ref().to_string_lossy(), None)?;
+        include::parse(Some(document.name()), document.source(), mode, &resolver)
+    }
+
+    // Parses a single document, includes and references are not resolved
+    fn parse_document(s: &str, mode: Option<ParseMode>) -> Result<(Schema, Vec<UnknownKeyword>), Error> {
+        let root = source::parse(s)?;
+
+        let unknown = match &root {
+            Some(root) => {
+                let version = root
+                    .get("version")
+                    .and_then(|x| x.as_str())
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or_else(|| Version::default().value());
+                let mode = mode.unwrap_or_else(|| ParseMode::for_version(version));
+
+                if version > 1 {
+                    check_renamed_types(root)?;
+                }
+
+                definitions::check_reference_keywords(root)?;
+
+                let unknown = keywords::unknown_keywords(root);
+
+                if mode == ParseMode::Strict {
+                    if let Some(keyword) = unknown.first() {
+                        return Err(Error::message(keyword.to_string())
+                            .with_position(keyword.line(), keyword.column())
+                            .with_schema_path(keyword.schema_path()));
+                    }
+                }
+
+                unknown
+            }
+            None => vec![],
+        };
+
+        let schema = serde_yaml::from_str::<raw::RawSchema>(s).map_err(|e| {
+            let error = Error::message(e.to_string());
+
+            match (e.location(), &root) {
+                (Some(location), Some(root)) => error
+                    .with_position(location.line(), location.column())
+                    .with_schema_path(
+                        source::path_at(root, source::Position::new(location.line(), location.column())).to_string(),
+                    ),
+                (Some(location), None) => error.with_position(location.line(), location.column()),
+                _ => error,
+            }
+        })?;
+
+        Ok((schema.0, unknown))
+    }
+}
+
+// Version 1 type names are not allowed in newer versions
+fn check_renamed_types(root: &source::Node) -> Result<(), Error> {
+    for keyword in keywords::type_keywords(root) {
+        let name = keyword.value.strip_suffix('?').unwrap_or(&keyword.value);
+
+        if let Some(primitive_type) = PrimitiveType::from_v1_name(name) {
+            return Err(Error::message(format!(
+                "type '{}' was renamed to '{}' in version 2",
+                name, primitive_type
+            ))
+            .with_position(keyword.position.line(), keyword.position.column())
+            .with_schema_path(keyword.schema_path));
+        }
+    }
+
+    Ok(())
+}
+
 impl FromStr for Schema {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Schema, Error> {
-        let schema: Schema = serde_yaml::from_str(s)?;
-        Ok(schema)
+        Ok(Schema::parse(s)?.0)
     }
 }
 
-fn deserialize_from_str<'de, S, D>(deserializer: D) -> Result<S, D::Error>
-where
-    S: FromStr,
-    S::Err: std::fmt::Display,
-    D: serde::de::Deserializer<'de>,
-{
-    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
-    S::from_str(&s).map_err(serde::de::Error::custom)
-}
-
-fn deserialize_as_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
-where
-    D: serde::de::Deserializer<'de>,
-{
-    let s: String = match serde::de::Deserialize::deserialize(deserializer)? {
-        Value::Bool(x) => format!("{}", x),
-        Value::Number(x) => format!("{}", x),
-        Value::String(x) => x.clone(),
-        _ => return Err(serde::de::Error::custom("unable to deserialize as string")),
-    };
-
-    Ok(Some(s))
+// Parses already deserialized schema document
+pub(crate) fn parse_value(value: &serde_yaml::Value) -> Result<Schema, Error> {
+    let source = serde_yaml::to_string(value).map_err(|e| Error::message(e.to_string()))?;
+    Ok(Schema::parse(&source)?.0)
 }
 
-fn deserialize_option_from_str<'de, S, D>(deserializer: D) -> Result<Option<S>, D::Error>
-where
-    S: FromStr,
-    S::Err: std::fmt::Display,
-    D: serde::de::Deserializer<'de>,
-{
-    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
-    Ok(Some(S::from_str(&s).map_err(serde::de::Error::custom)?))
+/// Deserializes the schema with [`Schema::parse`](struct.Schema.html#method.parse)
+///
+/// Error positions refer to the re-serialized document, use `parse` directly to get
+/// positions in the original source.
+impl<'de> de::Deserialize<'de> for Schema {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        parse_value(&value).map_err(de::Error::custom)
+    }
 }
 
-fn deserialize_struct_or_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
-where
-    T: serde::de::Deserialize<'de>,
-    D: serde::de::Deserializer<'de>,
-{
-    struct StructOrVec<T>(PhantomData<T>);
-
-    impl<'de, T> serde::de::Visitor<'de> for StructOrVec<T>
+/// Canonical Jelly Schema serialization
+///
+/// Keywords are serialized in the fixed order (annotations, type, values, type specific
+/// keywords, mapping, definitions) and keywords with default values are omitted.
+impl ser::Serialize for Schema {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        T: serde::de::Deserialize<'de>,
+        S: ser::Serializer,
     {
-        type Value = Vec<T>;
+        // Serialize references as they were written, not resolved
+        if let Some(unresolved) = &self.unresolved {
+            return unresolved.serialize(serializer);
+        }
+
+        let mut map = serializer.serialize_map(None)?;
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("schema or list of schemas")
+        macro_rules! entry_if_some {
+            ($key:expr, $value:expr) => {
+                if let Some(value) = $value {
+                    map.serialize_entry($key, &value)?;
+                }
+            };
         }
 
-        fn visit_map<M>(self, visitor: M) -> Result<Self::Value, M::Error>
-        where
-            M: serde::de::MapAccess<'de>,
-        {
-            serde::de::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(visitor)).map(|x| vec![x])
+        macro_rules! entry_if_true {
+            ($key:expr, $value:expr) => {
+                if $value {
+                    map.serialize_entry($key, &true)?;
+                }
+            };
         }
 
-        fn visit_seq<S>(self, visitor: S) -> Result<Self::Value, S::Error>
-        where
-            S: serde::de::SeqAccess<'de>,
-        {
-            serde::de::Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(visitor))
+        entry_if_some!("version", self.version());
+        entry_if_some!("revision", self.revision());
+        if !self.include.is_empty() {
+            map.serialize_entry("include", &self.include)?;
+        }
+        entry_if_some!("$ref", self.r#ref());
+        // Annotations
+        entry_if_some!("title", self.title.as_ref());
+        entry_if_some!("help", self.help.as_ref());
+        entry_if_some!("warning", self.warning.as_ref());
+        entry_if_some!("description", self.description.as_ref());
+        entry_if_some!("placeholder", self.placeholder.as_ref());
+        // Any instance type
+        if self.r#type() != &Type::default() {
+            map.serialize_entry("type", self.r#type())?;
+        }
+        entry_if_some!("default", self.r#default());
+        entry_if_some!("const", self.r#const());
+        if !self.r#enum().is_empty() {
+            map.serialize_entry("enum", self.r#enum())?;
+        }
+        entry_if_some!("formula", self.formula());
+        entry_if_some!("when", self.when());
+        entry_if_true!("readOnly", self.read_only());
+        entry_if_true!("writeOnly", self.write_only());
+        entry_if_true!("hidden", self.hidden());
+        entry_if_some!("collapsible", self.collapsible());
+        entry_if_some!("collapsed", self.collapsed());
+        // Number
+        entry_if_some!("min", self.min());
+        entry_if_some!("max", self.max());
+        entry_if_some!("exclusiveMin", self.exclusive_min());
+        entry_if_some!("exclusiveMax", self.exclusive_max());
+        entry_if_some!("multipleOf", self.multiple_of());
+        // String
+        entry_if_some!("minLength", self.min_length());
+        entry_if_some!("maxLength", self.max_length());
+        entry_if_some!("pattern", self.pattern().map(Regex::as_str));
+        entry_if_some!("separator", self.separator());
+        // Array
+        match self.items() {
+            [] => {}
+            [items] => map.serialize_entry("items", items)?,
+            items => map.serialize_entry("items", items)?,
+        };
+        entry_if_some!("discriminator", self.discriminator());
+        entry_if_some!("minItems", self.min_items());
+        entry_if_some!("maxItems", self.max_items());
+        if self.unique_items() != &UniqueItems::default() {
+            map.serialize_entry("uniqueItems", self.unique_items())?;
         }
+        entry_if_some!("orderable", self.orderable());
+        entry_if_some!("addable", self.addable());
+        entry_if_some!("removable", self.removable());
+        // Object
+        if !self.properties().is_empty() {
+            map.serialize_entry("properties", self.properties())?;
+        }
+        entry_if_some!("keys", self.keys());
+        entry_if_some!("values", self.values());
+        entry_if_true!("additionalProperties", self.additional_properties());
+        // Mapping extension
+        entry_if_some!("mapping", self.mapping());
+        // Definitions
+        if !self.definitions().is_empty() {
+            map.serialize_entry("definitions", self.definitions())?;
+        }
+        // Data migrations
+        if !self.migrations().is_empty() {
+            map.serialize_entry("migrations", self.migrations())?;
+        }
+
+        map.end()
     }
+}
 
-    deserializer.deserialize_any(StructOrVec(PhantomData))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        version: 1
+        properties:
+          - name:
+              type: string
+              minLenght: 1
+    "#;
+
+    #[test]
+    fn lenient_mode_returns_warnings() {
+        let (schema, unknown) = Schema::parse_with_mode(SCHEMA, Some(ParseMode::Lenient)).unwrap();
+        assert_eq!(schema.properties().len(), 1);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].keyword(), "minLenght");
+        assert_eq!(unknown[0].schema_path(), "properties[0].name.minLenght");
+        assert_eq!((unknown[0].line(), unknown[0].column()), (6, 15));
+    }
+
+    #[test]
+    fn strict_mode_fails() {
+        let error = Schema::parse_with_mode(SCHEMA, Some(ParseMode::Strict)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown keyword 'minLenght' at line 6, column 15 (schema path: 'properties[0].name.minLenght')"
+        );
+    }
+
+    #[test]
+    fn error_position() {
+        let schema = r#"
+        properties:
+          - network:
+              type: array
+              items:
+                properties:
+                  - ssid:
+                      type: string
+                  - password:
+                      type: foo
+        "#;
+        let error = schema.parse::<Schema>().unwrap_err();
+        assert_eq!(error.line(), Some(10));
+        assert_eq!(
+            error.schema_path(),
+            Some("properties[0].network.items.properties[1].password.type")
+        );
+    }
+
+    #[test]
+    fn version_one_defaults_to_lenient_mode() {
+        assert!(SCHEMA.parse::<Schema>().is_ok());
+        assert_eq!(Schema::parse(SCHEMA).unwrap().1.len(), 1);
+    }
+
+    #[test]
+    fn serialize_canonical_key_order() {
+        let schema: Schema = r#"
+        properties:
+          - port:
+              max: 65535
+              default: 8080
+              type: port
+              title: Port
+              readOnly: false
+          - name:
+              enum:
+                - value: foo
+                  title: Foo
+                - bar
+        version: 1
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            serde_yaml::to_string(&schema).unwrap(),
+            r#"---
+version: 1
+properties:
+  - port:
+      title: Port
+      type: port
+      default: 8080
+      max: 65535
+  - name:
+      enum:
+        - title: Foo
+          value: foo
+        - bar
+"#
+        );
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        for seed in &[
+            include_str!("../../fuzz/seeds/balena-os.yml"),
+            include_str!("../../fuzz/seeds/technologic.yml"),
+        ] {
+            let schema: Schema = seed.parse().unwrap();
+
+            let yaml = serde_yaml::to_string(&schema).unwrap();
+            let reparsed: Schema = yaml.parse().unwrap();
+            assert_eq!(serde_yaml::to_string(&reparsed).unwrap(), yaml);
+
+            let json = serde_json::to_string(&schema).unwrap();
+            let reparsed: Schema = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&reparsed).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn deserialize_runs_parser_checks() {
+        let schema: Schema = serde_yaml::from_str(
+            r##"
+        version: 2
+        properties:
+          - wifi:
+              $ref: "#/definitions/wifi"
+        definitions:
+          wifi:
+            type: string
+        "##,
+        )
+        .unwrap();
+        assert_eq!(schema.properties()[0].schema().r#type(), &"string".parse().unwrap());
+
+        assert!(serde_yaml::from_str::<Schema>("version: 2\ntype: datetime").is_err());
+        assert!(serde_yaml::from_str::<Schema>("version: 2\nminLenght: 1").is_err());
+        assert!(serde_yaml::from_str::<Schema>("include: /etc/passwd").is_err());
+    }
+
+    #[test]
+    fn localized_annotations() {
+        let schema: Schema = r#"
+        version: 1
+        title:
+          en: Network
+          pt_br: Rede
+        help: Network configuration
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(schema.title(), Some("Network"));
+        assert_eq!(schema.title_in("pt-BR"), Some("Rede"));
+        assert_eq!(schema.help_in("pt-BR"), Some("Network configuration"));
+        assert_eq!(schema.description_in("pt-BR"), None);
+        assert_eq!(
+            serde_yaml::to_string(&schema).unwrap(),
+            "---\nversion: 1\ntitle:\n  en: Network\n  pt-BR: Rede\nhelp: Network configuration\n"
+        );
+    }
 }
@@ -0,0 +1,358 @@
+//! Schema overlays
+//!
+//! An overlay document derives a new schema from the base one. It mirrors the base
+//! schema structure (`properties`, `items`, `keys`, `values`) and any other keyword
+//! replaces the base schema keyword:
+//!
+//! * `default: foo` overrides the default value,
+//! * `hidden: true` hides the property,
+//! * `max: 10` tightens the maximum,
+//! * `remove: true` (property only) removes the property from the base schema,
+//! * `keyword: null` removes the keyword from the base schema.
+//!
+//! Properties not found in the base schema are reported as errors. The result is a
+//! normal schema with all the references inlined.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::Schema;
+//!
+//! let base: Schema = r#"
+//!   version: 1
+//!   properties:
+//!     - hostname:
+//!         type: hostname
+//!         default: balena
+//!     - port:
+//!         type: port
+//!     - legacy:
+//!         type: string?
+//! "#.parse().unwrap();
+//!
+//! let schema = base.with_overlay(r#"
+//!   properties:
+//!     - hostname:
+//!         default: raspberrypi
+//!     - port:
+//!         max: 1024
+//!     - legacy:
+//!         remove: true
+//! "#).unwrap();
+//!
+//! assert_eq!(schema.properties().len(), 2);
+//! assert_eq!(schema.properties()[0].schema().default(), Some(&"raspberrypi".into()));
+//! assert_eq!(schema.properties()[1].schema().max(), Some(&1024.into()));
+//! ```
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Error,
+    schema::{keywords, parse_value, Schema},
+    validator::path::PathBuf,
+};
+
+const REMOVE_KEYWORD: &str = "remove";
+
+// Keywords which can't be used in the overlay document
+const UNSUPPORTED_KEYWORDS: &[&str] = &["version", "include", "definitions", "$ref"];
+
+// Drops references & definitions, overlay can override keywords of the referenced schemas
+fn inline(schema: &mut Schema) {
+    schema.r#ref = None;
+    schema.unresolved = None;
+    schema.definitions.clear();
+
+    for property in schema.properties.iter_mut() {
+        inline(property.schema_mut());
+    }
+
+    for items in schema.items.iter_mut() {
+        inline(items);
+    }
+
+    if let Some(keys) = schema.keys.as_mut() {
+        inline(keys);
+    }
+
+    if let Some(values) = schema.values.as_mut() {
+        inline(values);
+    }
+}
+
+fn as_object_mut<'a>(value: &'a mut Value, path: &PathBuf) -> Result<&'a mut Map<String, Value>, Error> {
+    if value.is_null() {
+        *value = Value::Object(Map::new());
+    }
+    value
+        .as_object_mut()
+        .ok_or_else(|| Error::at_schema_path(path, "schema is not an object"))
+}
+
+fn apply_properties(schema: &mut Map<String, Value>, overlay: &Value, path: &PathBuf) -> Result<(), Error> {
+    let overlay = overlay
+        .as_array()
+        .ok_or_else(|| Error::at_schema_path(path, "properties must be a sequence"))?;
+
+    for property in overlay {
+        let (name, property_overlay) = property
+            .as_object()
+            .filter(|x| x.len() == 1)
+            .and_then(|x| x.iter().next())
+            .ok_or_else(|| Error::at_schema_path(path, "property must be a mapping with one key"))?;
+
+        let properties = schema
+            .get_mut("properties")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| Error::at_schema_path(path, format!("property '{}' not found in the base schema", name)))?;
+
+        let idx = properties
+            .iter()
+            .position(|x| x.get(name).is_some())
+            .ok_or_else(|| Error::at_schema_path(path, format!("property '{}' not found in the base schema", name)))?;
+
+        let mut property_path = path.clone();
+        property_path.push_index(idx);
+        property_path.push_property(name.as_str());
+
+        if property_overlay.get(REMOVE_KEYWORD) == Some(&Value::Bool(true)) {
+            if property_overlay.as_object().map(Map::len) != Some(1) {
+                return Err(Error::at_schema_path(
+                    &property_path,
+                    "remove can't be combined with other keywords",
+                ));
+            }
+            properties.remove(idx);
+            continue;
+        }
+
+        let property_schema = properties[idx].get_mut(name.as_str()).unwrap();
+        apply(
+            as_object_mut(property_schema, &property_path)?,
+            property_overlay,
+            &property_path,
+        )?;
+    }
+
+    if schema.get("properties").and_then(Value::as_array).map(Vec::is_empty) == Some(true) {
+        schema.remove("properties");
+    }
+
+    Ok(())
+}
+
+fn apply(schema: &mut Map<String, Value>, overlay: &Value, path: &PathBuf) -> Result<(), Error> {
+    let overlay = match overlay {
+        Value::Null => return Ok(()),
+        Value::Object(overlay) => overlay,
+        _ => return Err(Error::at_schema_path(path, "overlay must be a mapping")),
+    };
+
+    for (keyword, value) in overlay {
+        let mut keyword_path = path.clone();
+        keyword_path.push_property(keyword.as_str());
+
+        if UNSUPPORTED_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(Error::at_schema_path(
+                &keyword_path,
+                format!("keyword '{}' is not supported in overlays", keyword),
+            ));
+        }
+
+        if !keywords::is_schema_keyword(keyword) {
+            return Err(Error::at_schema_path(
+                &keyword_path,
+                format!("unknown keyword '{}'", keyword),
+            ));
+        }
+
+        match keyword.as_str() {
+            "properties" => apply_properties(schema, value, &keyword_path)?,
+            "items" if value.is_object() => {
+                let items = match schema.get_mut("items") {
+                    Some(Value::Array(items)) if items.len() == 1 => &mut items[0],
+                    Some(items @ Value::Object(_)) => items,
+                    _ => {
+                        return Err(Error::at_schema_path(
+                            &keyword_path,
+                            "base schema must have exactly one items schema",
+                        ))
+                    }
+                };
+                keyword_path.push_index(0);
+                apply(as_object_mut(items, &keyword_path)?, value, &keyword_path)?;
+            }
+            "keys" | "values" if value.is_object() => {
+                let nested = schema.entry(keyword.as_str()).or_insert(Value::Null);
+                apply(as_object_mut(nested, &keyword_path)?, value, &keyword_path)?;
+            }
+            _ if value.is_null() => {
+                schema.remove(keyword);
+            }
+            _ => {
+                schema.insert(keyword.clone(), value.clone());
+            }
+        };
+    }
+
+    Ok(())
+}
+
+impl Schema {
+    /// Applies the overlay document to the schema
+    ///
+    /// Overlay document mirrors the schema structure (`properties`, `items`, `keys`,
+    /// `values`), any other keyword replaces the schema keyword (`null` removes it).
+    /// Properties are removed with the `remove: true` keyword. Returned schema has all
+    /// the references inlined, this schema is not modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `overlay` - Overlay document source
+    pub fn with_overlay(&self, overlay: &str) -> Result<Schema, Error> {
+        let overlay: Value = serde_yaml::from_str(overlay)?;
+
+        let mut base = self.clone();
+        inline(&mut base);
+
+        let mut value = serde_json::to_value(&base).map_err(|e| Error::message(e.to_string()))?;
+        apply(as_object_mut(&mut value, &PathBuf::new())?, &overlay, &PathBuf::new())?;
+
+        let value = serde_yaml::to_value(&value).map_err(|e| Error::message(e.to_string()))?;
+        parse_value(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{filler::fill_default_values, validator::validate};
+
+    const BASE: &str = r##"
+    version: 1
+    definitions:
+      port:
+        type: port
+    properties:
+      - hostname:
+          type: hostname
+          default: balena
+      - proxy:
+          properties:
+            - host:
+                type: hostname?
+            - port:
+                $ref: "#/definitions/port"
+      - networks:
+          type: array
+          items:
+            properties:
+              - ssid:
+                  type: string
+                  maxLength: 32
+      - legacy:
+          type: string?
+    "##;
+
+    fn base() -> Schema {
+        BASE.parse().unwrap()
+    }
+
+    #[test]
+    fn override_keywords() {
+        let schema = base()
+            .with_overlay(
+                r#"
+                title: Raspberry Pi
+                properties:
+                  - hostname:
+                      default: raspberrypi
+                  - proxy:
+                      hidden: true
+                      properties:
+                        - port:
+                            max: 1024
+                  - networks:
+                      items:
+                        properties:
+                          - ssid:
+                              maxLength: 16
+                  - legacy:
+                      remove: true
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(schema.title(), Some("Raspberry Pi"));
+        assert_eq!(schema.properties().len(), 3);
+        assert!(schema.properties()[1].schema().hidden());
+        assert!(schema.definitions().is_empty());
+
+        let mut data = json!({"proxy": {"port": 2048}, "networks": [{"ssid": "0123456789abcdefgh"}]});
+        fill_default_values(&schema, &mut data, false);
+        assert_eq!(data["hostname"], json!("raspberrypi"));
+
+        let state = validate(&schema, &data);
+        let paths: Vec<_> = state.errors().iter().map(|x| x.data_path().to_string()).collect();
+        assert_eq!(paths, vec!["proxy.port", "networks[0].ssid"]);
+    }
+
+    #[test]
+    fn remove_keyword() {
+        let schema = base()
+            .with_overlay(
+                r#"
+                properties:
+                  - hostname:
+                      default: ~
+                "#,
+            )
+            .unwrap();
+        assert!(schema.properties()[0].schema().default().is_none());
+    }
+
+    #[test]
+    fn base_schema_is_not_modified() {
+        let base = base();
+        base.with_overlay("properties: [{legacy: {remove: true}}]").unwrap();
+        assert_eq!(base.properties().len(), 4);
+    }
+
+    #[test]
+    fn unknown_property() {
+        let error = base()
+            .with_overlay("properties: [{proxy: {properties: [{foo: {hidden: true}}]}}]")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'properties[1].proxy.properties': property 'foo' not found in the base schema"
+        );
+    }
+
+    #[test]
+    fn unknown_keyword() {
+        let error = base().with_overlay("properties: [{port: {maximum: 10}}]").unwrap_err();
+        assert!(error.to_string().contains("not found"));
+
+        let error = base()
+            .with_overlay("properties: [{hostname: {hiden: true}}]")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'properties[0].hostname.hiden': unknown keyword 'hiden'"
+        );
+    }
+
+    #[test]
+    fn remove_with_other_keywords() {
+        let error = base()
+            .with_overlay("properties: [{legacy: {remove: true, hidden: true}}]")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'properties[3].legacy': remove can't be combined with other keywords"
+        );
+    }
+}
@@ -1,16 +1,24 @@
-use std::fmt;
-
-use serde::de;
+use serde::ser::{self, SerializeMap};
 
 use crate::schema::Schema;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Property {
     name: String,
     schema: Schema,
 }
 
 impl Property {
+    pub fn new<S>(name: S, schema: Schema) -> Property
+    where
+        S: Into<String>,
+    {
+        Property {
+            name: name.into(),
+            schema,
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -18,34 +26,19 @@ impl Property {
     pub fn schema(&self) -> &Schema {
         &self.schema
     }
-}
-
-struct PropertyVisitor;
-
-impl<'de> de::Visitor<'de> for PropertyVisitor {
-    type Value = Property;
-
-    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("expected property")
-    }
-
-    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
-    where
-        M: de::MapAccess<'de>,
-    {
-        if let Some((name, schema)) = access.next_entry()? {
-            return Ok(Property { name, schema });
-        }
 
-        Err(de::Error::custom("unable to deserialize property from empty map"))
+    pub(crate) fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.schema
     }
 }
 
-impl<'de> de::Deserialize<'de> for Property {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+impl ser::Serialize for Property {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        D: de::Deserializer<'de>,
+        S: ser::Serializer,
     {
-        deserializer.deserialize_map(PropertyVisitor)
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.name, &self.schema)?;
+        map.end()
     }
 }
@@ -0,0 +1,319 @@
+//! Raw schema deserialization
+//!
+//! Schema is deserialized as written, includes are not expanded, references are not
+//! resolved and keywords are not checked. Used by the parser only, the public
+//! `Deserialize` implementation of the [`Schema`](../struct.Schema.html) goes through
+//! [`Schema::parse`](../struct.Schema.html#method.parse).
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use regex::Regex;
+use serde::de::{self, Deserialize, Deserializer};
+use serde_derive::Deserialize;
+use serde_json::{Number, Value};
+
+use crate::schema::{mapping::Mapping, Annotation, EnumEntry, Migration, Property, Schema, Type, UniqueItems, Version};
+
+#[derive(Deserialize)]
+#[serde(remote = "Schema")]
+struct SchemaDef {
+    #[serde(default)]
+    version: Option<Version>,
+    //
+    // Data migrations
+    //
+    #[serde(default)]
+    revision: Option<u32>,
+    #[serde(default)]
+    migrations: Vec<Migration>,
+    //
+    // Definitions & references
+    //
+    #[serde(default, deserialize_with = "deserialize_string_or_vec")]
+    include: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_definitions")]
+    definitions: BTreeMap<String, Schema>,
+    #[serde(default, rename(deserialize = "$ref"))]
+    r#ref: Option<String>,
+    // Referencing schema as it was written, set when the reference is resolved
+    #[serde(skip)]
+    unresolved: Option<Box<Schema>>,
+    //
+    // Mapping extension
+    //
+    #[serde(default)]
+    mapping: Option<Mapping>,
+    //
+    // Any instance type validation keywords
+    //
+    #[serde(default, rename(deserialize = "type"), deserialize_with = "deserialize_from_str")]
+    r#type: Type,
+    #[serde(default, rename(deserialize = "const"))]
+    r#const: Option<Value>,
+    #[serde(default, rename(deserialize = "default"))]
+    r#default: Option<Value>,
+    #[serde(default, rename(deserialize = "enum"))]
+    r#enum: Vec<EnumEntry>,
+    #[serde(default, deserialize_with = "deserialize_as_optional_string")]
+    formula: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_as_optional_string")]
+    when: Option<String>,
+    #[serde(default, rename(deserialize = "readOnly"))]
+    read_only: bool,
+    #[serde(default, rename(deserialize = "writeOnly"))]
+    write_only: bool,
+    #[serde(default)]
+    placeholder: Option<Annotation>,
+    #[serde(default)]
+    hidden: bool,
+    //
+    // Object validation keywords
+    //
+    #[serde(default, deserialize_with = "deserialize_properties")]
+    properties: Vec<Property>,
+    #[serde(default, deserialize_with = "deserialize_boxed")]
+    keys: Option<Box<Schema>>,
+    #[serde(default, deserialize_with = "deserialize_boxed")]
+    values: Option<Box<Schema>>,
+    #[serde(default, rename(deserialize = "additionalProperties"))]
+    additional_properties: bool,
+    //
+    // StringList keywords
+    //
+    #[serde(default)]
+    separator: Option<String>,
+    //
+    // Annotation keywords
+    //
+    #[serde(default)]
+    title: Option<Annotation>,
+    #[serde(default)]
+    help: Option<Annotation>,
+    #[serde(default)]
+    warning: Option<Annotation>,
+    #[serde(default)]
+    description: Option<Annotation>,
+    #[serde(default)]
+    collapsible: Option<bool>,
+    #[serde(default)]
+    collapsed: Option<bool>,
+    //
+    // Array validation keywords
+    //
+    #[serde(default, deserialize_with = "deserialize_items")]
+    items: Vec<Schema>,
+    #[serde(default)]
+    discriminator: Option<String>,
+    #[serde(default, rename(deserialize = "maxItems"))]
+    max_items: Option<usize>,
+    #[serde(default, rename(deserialize = "minItems"))]
+    min_items: Option<usize>,
+    #[serde(default, rename(deserialize = "uniqueItems"))]
+    unique_items: UniqueItems,
+    #[serde(default)]
+    orderable: Option<bool>,
+    #[serde(default)]
+    addable: Option<bool>,
+    #[serde(default)]
+    removable: Option<bool>,
+    //
+    // Number validation keywords
+    //
+    #[serde(default, rename(deserialize = "multipleOf"))]
+    multiple_of: Option<Number>,
+    #[serde(default)]
+    max: Option<Number>,
+    #[serde(default, rename(deserialize = "exclusiveMax"))]
+    exclusive_max: Option<Number>,
+    #[serde(default)]
+    min: Option<Number>,
+    #[serde(default, rename(deserialize = "exclusiveMin"))]
+    exclusive_min: Option<Number>,
+    //
+    // String based types validation keywords
+    //
+    #[serde(default, rename(deserialize = "maxLength"))]
+    max_length: Option<usize>,
+    #[serde(default, rename(deserialize = "minLength"))]
+    min_length: Option<usize>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pattern: Option<Regex>,
+}
+
+/// Schema deserialized as written
+pub(crate) struct RawSchema(pub(crate) Schema);
+
+impl<'de> Deserialize<'de> for RawSchema {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SchemaDef::deserialize(deserializer).map(RawSchema)
+    }
+}
+
+struct RawProperty(Property);
+
+struct PropertyVisitor;
+
+impl<'de> de::Visitor<'de> for PropertyVisitor {
+    type Value = RawProperty;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("expected property")
+    }
+
+    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: de::MapAccess<'de>,
+    {
+        if let Some((name, schema)) = access.next_entry::<String, RawSchema>()? {
+            return Ok(RawProperty(Property::new(name, schema.0)));
+        }
+
+        Err(de::Error::custom("unable to deserialize property from empty map"))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawProperty {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(PropertyVisitor)
+    }
+}
+
+fn deserialize_properties<'de, D>(deserializer: D) -> Result<Vec<Property>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let properties: Vec<RawProperty> = Deserialize::deserialize(deserializer)?;
+    Ok(properties.into_iter().map(|x| x.0).collect())
+}
+
+fn deserialize_definitions<'de, D>(deserializer: D) -> Result<BTreeMap<String, Schema>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let definitions: BTreeMap<String, RawSchema> = Deserialize::deserialize(deserializer)?;
+    Ok(definitions.into_iter().map(|(name, schema)| (name, schema.0)).collect())
+}
+
+fn deserialize_boxed<'de, D>(deserializer: D) -> Result<Option<Box<Schema>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let schema: RawSchema = Deserialize::deserialize(deserializer)?;
+    Ok(Some(Box::new(schema.0)))
+}
+
+fn deserialize_items<'de, D>(deserializer: D) -> Result<Vec<Schema>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let items: Vec<RawSchema> = deserialize_struct_or_vec(deserializer)?;
+    Ok(items.into_iter().map(|x| x.0).collect())
+}
+
+pub(crate) fn deserialize_from_str<'de, S, D>(deserializer: D) -> Result<S, D::Error>
+where
+    S: FromStr,
+    S::Err: std::fmt::Display,
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+    S::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_as_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = match serde::de::Deserialize::deserialize(deserializer)? {
+        Value::Bool(x) => format!("{}", x),
+        Value::Number(x) => format!("{}", x),
+        Value::String(x) => x.clone(),
+        _ => return Err(serde::de::Error::custom("unable to deserialize as string")),
+    };
+
+    Ok(Some(s))
+}
+
+fn deserialize_option_from_str<'de, S, D>(deserializer: D) -> Result<Option<S>, D::Error>
+where
+    S: FromStr,
+    S::Err: std::fmt::Display,
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+    Ok(Some(S::from_str(&s).map_err(serde::de::Error::custom)?))
+}
+
+fn deserialize_string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct StringOrVec;
+
+    impl<'de> serde::de::Visitor<'de> for StringOrVec {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("string or list of strings")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_seq<S>(self, visitor: S) -> Result<Self::Value, S::Error>
+        where
+            S: serde::de::SeqAccess<'de>,
+        {
+            serde::de::Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(visitor))
+        }
+    }
+
+    deserializer.deserialize_any(StringOrVec)
+}
+
+fn deserialize_struct_or_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: serde::de::Deserialize<'de>,
+    D: serde::de::Deserializer<'de>,
+{
+    struct StructOrVec<T>(PhantomData<T>);
+
+    impl<'de, T> serde::de::Visitor<'de> for StructOrVec<T>
+    where
+        T: serde::de::Deserialize<'de>,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("schema or list of schemas")
+        }
+
+        fn visit_map<M>(self, visitor: M) -> Result<Self::Value, M::Error>
+        where
+            M: serde::de::MapAccess<'de>,
+        {
+            serde::de::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(visitor)).map(|x| vec![x])
+        }
+
+        fn visit_seq<S>(self, visitor: S) -> Result<Self::Value, S::Error>
+        where
+            S: serde::de::SeqAccess<'de>,
+        {
+            serde::de::Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(visitor))
+        }
+    }
+
+    deserializer.deserialize_any(StructOrVec(PhantomData))
+}
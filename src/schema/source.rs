@@ -0,0 +1,239 @@
+//! Schema source positions
+//!
+//! `serde_yaml` doesn't provide positions of deserialized values. Schema source is
+//! parsed into a lightweight tree of nodes where every node knows its position.
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+use crate::{error::Error, validator::path::PathBuf};
+
+/// Position in the schema source (both line & column are 1-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl From<Marker> for Position {
+    fn from(marker: Marker) -> Position {
+        Position::new(marker.line(), marker.col() + 1)
+    }
+}
+
+#[derive(Debug)]
+enum NodeValue {
+    Scalar(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+    Alias,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    value: NodeValue,
+    position: Position,
+}
+
+impl Node {
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            NodeValue::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            NodeValue::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(Node, Node)]> {
+        match &self.value {
+            NodeValue::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Returns mapping value for the given (scalar) key
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+    }
+}
+
+enum Partial {
+    Sequence(Vec<Node>, Position),
+    Mapping(Vec<(Node, Node)>, Option<Node>, Position),
+}
+
+#[derive(Default)]
+struct Builder {
+    stack: Vec<Partial>,
+    root: Option<Node>,
+}
+
+impl Builder {
+    fn push_node(&mut self, node: Node) {
+        match self.stack.last_mut() {
+            Some(Partial::Sequence(items, _)) => items.push(node),
+            Some(Partial::Mapping(entries, key, _)) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None => *key = Some(node),
+            },
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+        };
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        let position = Position::from(marker);
+
+        match event {
+            Event::Scalar(value, ..) => self.push_node(Node {
+                value: NodeValue::Scalar(value),
+                position,
+            }),
+            Event::Alias(_) => self.push_node(Node {
+                value: NodeValue::Alias,
+                position,
+            }),
+            Event::SequenceStart(_) => self.stack.push(Partial::Sequence(vec![], position)),
+            Event::MappingStart(_) => self.stack.push(Partial::Mapping(vec![], None, position)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let node = match self.stack.pop() {
+                    // Block mapping start is marked after the first key, the first key
+                    // position is used in this case
+                    Some(Partial::Sequence(items, position)) => Node {
+                        position: items.first().map_or(position, |x| x.position.min(position)),
+                        value: NodeValue::Sequence(items),
+                    },
+                    Some(Partial::Mapping(entries, _, position)) => Node {
+                        position: entries.first().map_or(position, |(k, _)| k.position.min(position)),
+                        value: NodeValue::Mapping(entries),
+                    },
+                    None => return,
+                };
+                self.push_node(node);
+            }
+            _ => {}
+        };
+    }
+}
+
+/// Parses the schema source into the tree of nodes
+///
+/// Returns `None` for an empty source.
+pub fn parse(source: &str) -> Result<Option<Node>, Error> {
+    let mut builder = Builder::default();
+    Parser::new(source.chars()).load(&mut builder, false).map_err(|e| {
+        let position = Position::from(*e.marker());
+        Error::message(e.to_string()).with_position(position.line(), position.column())
+    })?;
+    Ok(builder.root)
+}
+
+fn collect_path(node: &Node, position: Position, path: &mut PathBuf) {
+    match &node.value {
+        NodeValue::Mapping(entries) => {
+            if let Some((key, value)) = entries.iter().rev().find(|(key, _)| key.position <= position) {
+                path.push_property(key.as_str().unwrap_or_default());
+                if value.position <= position {
+                    collect_path(value, position, path);
+                }
+            }
+        }
+        NodeValue::Sequence(items) => {
+            if let Some((idx, item)) = items
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, item)| item.position <= position)
+            {
+                path.push_index(idx);
+                collect_path(item, position, path);
+            }
+        }
+        NodeValue::Scalar(_) | NodeValue::Alias => {}
+    };
+}
+
+/// Returns the logical path (`properties[3].network.items`) of the node at the given position
+pub fn path_at(root: &Node, position: Position) -> PathBuf {
+    let mut path = PathBuf::new();
+    collect_path(root, position, &mut path);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let source = "version: 1\nproperties:\n  - name:\n      type: string\n";
+        let root = parse(source).unwrap().unwrap();
+
+        let name = &root.get("properties").unwrap().as_sequence().unwrap()[0];
+        let (key, schema) = &name.as_mapping().unwrap()[0];
+        assert_eq!(key.as_str(), Some("name"));
+        assert_eq!(key.position(), Position::new(3, 5));
+
+        let (key, value) = &schema.as_mapping().unwrap()[0];
+        assert_eq!(key.position(), Position::new(4, 7));
+        assert_eq!(value.as_str(), Some("string"));
+    }
+
+    #[test]
+    fn path_at_position() {
+        let source = "version: 1\nproperties:\n  - network:\n      items:\n        type: foo\n";
+        let root = parse(source).unwrap().unwrap();
+
+        assert_eq!(path_at(&root, Position::new(1, 10)).to_string(), "version");
+        assert_eq!(
+            path_at(&root, Position::new(5, 15)).to_string(),
+            "properties[0].network.items.type"
+        );
+        assert_eq!(path_at(&root, Position::new(3, 5)).to_string(), "properties[0].network");
+    }
+
+    #[test]
+    fn error_position() {
+        let error = parse("properties:\n  - name: [\n").unwrap_err();
+        assert!(error.line().is_some());
+        assert!(error.column().is_some());
+    }
+
+    #[test]
+    fn empty_source() {
+        assert!(parse("").unwrap().is_none());
+    }
+}
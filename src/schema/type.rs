@@ -1,5 +1,7 @@
 use std::{fmt, str::FromStr};
 
+use serde::ser;
+
 use crate::error::Error;
 
 const OBJECT_KEYWORD: &str = "object";
@@ -10,20 +12,24 @@ const HOSTNAME_KEYWORD: &str = "hostname";
 const INTEGER_KEYWORD: &str = "integer";
 const ARRAY_KEYWORD: &str = "array";
 const NUMBER_KEYWORD: &str = "number";
-const DATE_TIME_KEYWORD: &str = "datetime";
+const DATE_TIME_KEYWORD: &str = "date-time";
 const DATE_KEYWORD: &str = "date";
 const TIME_KEYWORD: &str = "time";
 const EMAIL_KEYWORD: &str = "email";
 const IPV4_KEYWORD: &str = "ipv4";
 const IPV6_KEYWORD: &str = "ipv6";
 const URI_KEYWORD: &str = "uri";
-const FILE_KEYWORD: &str = "file"; // TODO: Update spec
+const FILE_KEYWORD: &str = "file";
 const PORT_KEYWORD: &str = "port";
-const TEXT_KEYWORD: &str = "text"; // TODO: Update spec
-const STRINGLIST_KEYWORD: &str = "stringlist"; // TODO: Update spec
-const DNSMASQ_ADDRESS_KEYWORD: &str = "dnsmasq-address"; // TODO: Update spec
-const CHRONY_ADDRESS_KEYWORD: &str = "chrony-address"; // TODO: Update spec
-const IPTABLES_ADDRESS_KEYWORD: &str = "iptables-address"; // TODO: Update spec
+const TEXT_KEYWORD: &str = "text";
+const STRING_LIST_KEYWORD: &str = "string-list";
+const DNSMASQ_ADDRESS_KEYWORD: &str = "dnsmasq-address";
+const CHRONY_ADDRESS_KEYWORD: &str = "chrony-address";
+const IPTABLES_ADDRESS_KEYWORD: &str = "iptables-address";
+
+// Version 1 type names, renamed in the version 2 to be consistent with other types
+const V1_DATE_TIME_KEYWORD: &str = "datetime";
+const V1_STRINGLIST_KEYWORD: &str = "stringlist";
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PrimitiveType {
@@ -72,7 +78,7 @@ impl AsRef<str> for PrimitiveType {
             PrimitiveType::File => FILE_KEYWORD,
             PrimitiveType::Port => PORT_KEYWORD,
             PrimitiveType::Text => TEXT_KEYWORD,
-            PrimitiveType::StringList => STRINGLIST_KEYWORD,
+            PrimitiveType::StringList => STRING_LIST_KEYWORD,
             PrimitiveType::DNSMasqAddress => DNSMASQ_ADDRESS_KEYWORD,
             PrimitiveType::ChronyAddress => CHRONY_ADDRESS_KEYWORD,
             PrimitiveType::IPTablesAddress => IPTABLES_ADDRESS_KEYWORD,
@@ -80,6 +86,26 @@ impl AsRef<str> for PrimitiveType {
     }
 }
 
+impl ser::Serialize for PrimitiveType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+impl PrimitiveType {
+    /// Returns primitive type for the version 1 type name renamed in the version 2
+    pub(crate) fn from_v1_name(s: &str) -> Option<PrimitiveType> {
+        match s {
+            V1_DATE_TIME_KEYWORD => Some(PrimitiveType::DateTime),
+            V1_STRINGLIST_KEYWORD => Some(PrimitiveType::StringList),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for PrimitiveType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.as_ref())
@@ -109,16 +135,18 @@ impl FromStr for PrimitiveType {
             FILE_KEYWORD => Ok(PrimitiveType::File),
             PORT_KEYWORD => Ok(PrimitiveType::Port),
             TEXT_KEYWORD => Ok(PrimitiveType::Text),
-            STRINGLIST_KEYWORD => Ok(PrimitiveType::StringList),
+            STRING_LIST_KEYWORD => Ok(PrimitiveType::StringList),
             DNSMASQ_ADDRESS_KEYWORD => Ok(PrimitiveType::DNSMasqAddress),
             CHRONY_ADDRESS_KEYWORD => Ok(PrimitiveType::ChronyAddress),
             IPTABLES_ADDRESS_KEYWORD => Ok(PrimitiveType::IPTablesAddress),
-            _ => Err(Error::message(format!("invalid primitive type: \"{}\"", s))),
+            // Version 1 names are still accepted, version 2 schemas are checked during parsing
+            _ => PrimitiveType::from_v1_name(s)
+                .ok_or_else(|| Error::message(format!("invalid primitive type: \"{}\"", s))),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Type {
     primitive_type: PrimitiveType,
     optional: bool,
@@ -165,10 +193,9 @@ impl FromStr for Type {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (optional, s) = if s.ends_with('?') {
-            (true, &s[..s.len() - 1])
-        } else {
-            (false, s)
+        let (optional, s) = match s.strip_suffix('?') {
+            Some(s) => (true, s),
+            None => (false, s),
         };
         let primitive_type = s.parse::<PrimitiveType>()?;
         Ok(Type::new(primitive_type, optional))
@@ -191,6 +218,15 @@ impl fmt::Display for Type {
     }
 }
 
+impl ser::Serialize for Type {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,7 +241,7 @@ mod tests {
         assert_eq!("password".parse::<PrimitiveType>().unwrap(), PrimitiveType::Password);
         assert_eq!("hostname".parse::<PrimitiveType>().unwrap(), PrimitiveType::Hostname);
         assert_eq!("number".parse::<PrimitiveType>().unwrap(), PrimitiveType::Number);
-        assert_eq!("datetime".parse::<PrimitiveType>().unwrap(), PrimitiveType::DateTime);
+        assert_eq!("date-time".parse::<PrimitiveType>().unwrap(), PrimitiveType::DateTime);
         assert_eq!("date".parse::<PrimitiveType>().unwrap(), PrimitiveType::Date);
         assert_eq!("time".parse::<PrimitiveType>().unwrap(), PrimitiveType::Time);
         assert_eq!("email".parse::<PrimitiveType>().unwrap(), PrimitiveType::Email);
@@ -216,7 +252,7 @@ mod tests {
         assert_eq!("port".parse::<PrimitiveType>().unwrap(), PrimitiveType::Port);
         assert_eq!("text".parse::<PrimitiveType>().unwrap(), PrimitiveType::Text);
         assert_eq!(
-            "stringlist".parse::<PrimitiveType>().unwrap(),
+            "string-list".parse::<PrimitiveType>().unwrap(),
             PrimitiveType::StringList
         );
         assert_eq!(
@@ -233,6 +269,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn version_one_primitive_types() {
+        assert_eq!("datetime".parse::<PrimitiveType>().unwrap(), PrimitiveType::DateTime);
+        assert_eq!(
+            "stringlist".parse::<PrimitiveType>().unwrap(),
+            PrimitiveType::StringList
+        );
+        assert_eq!(PrimitiveType::StringList.to_string(), "string-list");
+        assert!(PrimitiveType::from_v1_name("string").is_none());
+    }
+
     #[test]
     fn required_object_type() {
         assert!(!"string".parse::<Type>().unwrap().optional);
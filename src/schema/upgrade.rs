@@ -0,0 +1,147 @@
+//! Schema upgrade
+//!
+//! Version 2 schemas differ from the version 1 ones in:
+//!
+//! * unknown keywords are errors (strict parse mode by default),
+//! * `datetime` type was renamed to `date-time`,
+//! * `stringlist` type was renamed to `string-list`.
+//!
+//! Version 1 schemas are still supported and they can be converted to the version 2
+//! with the [`upgrade`](fn.upgrade.html) function. Every document is upgraded on its
+//! own, includes and references are kept as they are.
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::schema::{upgrade, Schema};
+//!
+//! let (source, unknown) = upgrade(r#"
+//!   version: 1
+//!   properties:
+//!     - servers:
+//!         type: stringlist
+//!         colapsed: true
+//! "#).unwrap();
+//!
+//! assert_eq!(unknown[0].keyword(), "colapsed");
+//!
+//! let schema: Schema = source.parse().unwrap();
+//! assert_eq!(schema.version(), Some(2));
+//! assert_eq!(schema.properties()[0].schema().r#type().to_string(), "string-list");
+//! ```
+use crate::{
+    error::Error,
+    schema::{ParseMode, Schema, UnknownKeyword, Version},
+};
+
+/// Upgrades the schema document to the latest version
+///
+/// Returns the upgraded schema source and the dropped unknown keywords. Comments and
+/// the original keyword order are not preserved, the schema is serialized in the
+/// canonical form. Already upgraded schema source is returned as it is.
+///
+/// # Arguments
+///
+/// * `source` - Schema document source
+pub fn upgrade(source: &str) -> Result<(String, Vec<UnknownKeyword>), Error> {
+    let (mut schema, unknown) = Schema::parse_document(source, Some(ParseMode::Lenient))?;
+
+    let latest = Version::latest();
+
+    if schema.version() == Some(latest.value()) {
+        if let Some(keyword) = unknown.first() {
+            return Err(Error::message(keyword.to_string())
+                .with_position(keyword.line(), keyword.column())
+                .with_schema_path(keyword.schema_path()));
+        }
+        return Ok((source.to_string(), unknown));
+    }
+
+    schema.version = Some(latest);
+    let source = serde_yaml::to_string(&schema)?;
+    Ok((source, unknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade_version_one() {
+        let (source, unknown) = upgrade(
+            r##"
+            properties:
+              - started:
+                  type: datetime?
+              - ntp:
+                  $ref: "#/definitions/servers"
+                  title: NTP servers
+            definitions:
+              servers:
+                type: stringlist
+            "##,
+        )
+        .unwrap();
+
+        assert!(unknown.is_empty());
+        assert_eq!(
+            source,
+            r##"---
+version: 2
+properties:
+  - started:
+      type: date-time?
+  - ntp:
+      $ref: "#/definitions/servers"
+      title: NTP servers
+definitions:
+  servers:
+    type: string-list
+"##
+        );
+
+        let schema: Schema = source.parse().unwrap();
+        assert_eq!(schema.properties()[1].schema().title(), Some("NTP servers"));
+    }
+
+    #[test]
+    fn drop_unknown_keywords() {
+        let (source, unknown) = upgrade("version: 1\ntitle: Foo\ntitel: Foo\n").unwrap();
+        assert_eq!(source, "---\nversion: 2\ntitle: Foo\n");
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].schema_path(), "titel");
+    }
+
+    #[test]
+    fn keep_latest_version() {
+        let source = "version: 2\n# Comment\ntitle: Foo\n";
+        assert_eq!(upgrade(source).unwrap(), (source.to_string(), vec![]));
+    }
+
+    #[test]
+    fn fail_on_unknown_keyword_in_latest_version() {
+        let error = upgrade("version: 2\ntitel: Foo\n").unwrap_err();
+        assert_eq!(error.schema_path(), Some("titel"));
+    }
+
+    #[test]
+    fn fail_on_renamed_type_in_version_two() {
+        let error = Schema::parse("version: 2\nproperties:\n  - ntp:\n      type: stringlist?\n").unwrap_err();
+        assert_eq!(error.schema_path(), Some("properties[0].ntp.type"));
+        assert_eq!(error.line(), Some(4));
+        assert!(error
+            .to_string()
+            .contains("type 'stringlist' was renamed to 'string-list' in version 2"));
+    }
+
+    #[test]
+    fn version_one_accepts_both_names() {
+        let schema: Schema = "properties:\n  - a:\n      type: stringlist\n  - b:\n      type: string-list\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            schema.properties()[0].schema().r#type(),
+            schema.properties()[1].schema().r#type()
+        );
+    }
+}
@@ -1,12 +1,32 @@
 use serde::{de, ser};
 use serde_json::Number;
 
-#[derive(Debug, PartialEq)]
+use crate::error::Error;
+
+const SUPPORTED_VERSIONS: &[u8] = &[1, 2];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Version {
     value: u8,
 }
 
 impl Version {
+    /// Creates a new version, fails if the version is not supported
+    pub fn new(value: u8) -> Result<Version, Error> {
+        if SUPPORTED_VERSIONS.contains(&value) {
+            Ok(Version { value })
+        } else {
+            Err(Error::message("unsuppored version number"))
+        }
+    }
+
+    /// Returns the latest supported version
+    pub fn latest() -> Version {
+        Version {
+            value: *SUPPORTED_VERSIONS.last().unwrap(),
+        }
+    }
+
     /// Returns schema version value
     pub fn value(&self) -> u8 {
         self.value
@@ -26,11 +46,10 @@ impl<'de> de::Deserialize<'de> for Version {
     {
         let v = Number::deserialize(deserializer)?;
 
-        if v.as_u64() != Some(1) {
-            Err(de::Error::custom("unsuppored version number"))
-        } else {
-            Ok(Version { value: 1 })
-        }
+        v.as_u64()
+            .filter(|v| *v <= u64::from(u8::MAX))
+            .ok_or_else(|| de::Error::custom("unsuppored version number"))
+            .and_then(|v| Version::new(v as u8).map_err(de::Error::custom))
     }
 }
 
@@ -58,9 +77,15 @@ mod tests {
         assert_eq!(v.value(), 1);
     }
 
+    #[test]
+    fn two() {
+        let v: Version = serde_yaml::from_str("2").unwrap();
+        assert_eq!(v.value(), 2);
+    }
+
     #[test]
     fn fail_on_unsupported_version() {
-        let v: Result<Version, _> = serde_yaml::from_str("2");
+        let v: Result<Version, _> = serde_yaml::from_str("3");
         assert!(v.is_err());
         let v: Result<Version, _> = serde_yaml::from_str("0");
         assert!(v.is_err());
@@ -1,48 +1,304 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use serde_derive::Serialize;
+use serde_json::Value;
 use std::fmt;
 
-#[derive(Debug, Serialize)]
+use crate::validator::path::{PathBuf, PathFormat};
+
+/// Stable validation error code
+///
+/// Codes are serialized in the kebab case (`min-length`, ...) and they do not change
+/// between releases, messages do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// Data can't be deserialized
+    InvalidData,
+    /// Value is missing, but the type is not optional
+    Required,
+    /// Value type does not match the schema type
+    Type,
+    /// Value type matches, but the value is not in the type format (`hostname`, `date`, ...)
+    Format,
+    Const,
+    /// Value does not match any `enum` value
+    Enum,
+    /// Value matches multiple `enum` values
+    EnumAmbiguous,
+    Min,
+    ExclusiveMin,
+    Max,
+    ExclusiveMax,
+    MultipleOf,
+    MinLength,
+    MaxLength,
+    Pattern,
+    MinItems,
+    MaxItems,
+    UniqueItems,
+    /// Array item is valid against multiple `items` schemas
+    ItemsAmbiguous,
+    /// Discriminator value does not select any `items` schema
+    Discriminator,
+    AdditionalProperties,
+    /// `when` condition can't be evaluated
+    When,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidData => "invalid-data",
+            ErrorCode::Required => "required",
+            ErrorCode::Type => "type",
+            ErrorCode::Format => "format",
+            ErrorCode::Const => "const",
+            ErrorCode::Enum => "enum",
+            ErrorCode::EnumAmbiguous => "enum-ambiguous",
+            ErrorCode::Min => "min",
+            ErrorCode::ExclusiveMin => "exclusive-min",
+            ErrorCode::Max => "max",
+            ErrorCode::ExclusiveMax => "exclusive-max",
+            ErrorCode::MultipleOf => "multiple-of",
+            ErrorCode::MinLength => "min-length",
+            ErrorCode::MaxLength => "max-length",
+            ErrorCode::Pattern => "pattern",
+            ErrorCode::MinItems => "min-items",
+            ErrorCode::MaxItems => "max-items",
+            ErrorCode::UniqueItems => "unique-items",
+            ErrorCode::ItemsAmbiguous => "items-ambiguous",
+            ErrorCode::Discriminator => "discriminator",
+            ErrorCode::AdditionalProperties => "additional-properties",
+            ErrorCode::When => "when",
+        }
+    }
+
+    /// Schema keyword the error belongs to
+    pub fn keyword(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidData => "",
+            ErrorCode::Required | ErrorCode::Type | ErrorCode::Format => "type",
+            ErrorCode::Const => "const",
+            ErrorCode::Enum | ErrorCode::EnumAmbiguous => "enum",
+            ErrorCode::Min => "min",
+            ErrorCode::ExclusiveMin => "exclusiveMin",
+            ErrorCode::Max => "max",
+            ErrorCode::ExclusiveMax => "exclusiveMax",
+            ErrorCode::MultipleOf => "multipleOf",
+            ErrorCode::MinLength => "minLength",
+            ErrorCode::MaxLength => "maxLength",
+            ErrorCode::Pattern => "pattern",
+            ErrorCode::MinItems => "minItems",
+            ErrorCode::MaxItems => "maxItems",
+            ErrorCode::UniqueItems => "uniqueItems",
+            ErrorCode::ItemsAmbiguous => "items",
+            ErrorCode::Discriminator => "discriminator",
+            ErrorCode::AdditionalProperties => "additionalProperties",
+            ErrorCode::When => "when",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Validation error parameters
+///
+/// * `limit` - schema limit (`min`, `maxLength`, `multipleOf`, ...)
+/// * `actual` - validated value, length or count for the `*Length` & `*Items` errors
+/// * `expected` - expected value (`const`)
+/// * `expectedType` - schema type
+/// * `pattern` - regular expression or `uniqueItems` path
+/// * `property` - property name (`additionalProperties`, `discriminator`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<Value>,
+    #[serde(rename = "expectedType", skip_serializing_if = "Option::is_none")]
+    expected_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    property: Option<String>,
+}
+
+impl ErrorParams {
+    pub fn limit(&self) -> Option<&Value> {
+        self.limit.as_ref()
+    }
+
+    pub fn actual(&self) -> Option<&Value> {
+        self.actual.as_ref()
+    }
+
+    pub fn expected(&self) -> Option<&Value> {
+        self.expected.as_ref()
+    }
+
+    pub fn expected_type(&self) -> Option<&str> {
+        self.expected_type.as_deref()
+    }
+
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    pub fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &ErrorParams::default()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ValidationError {
-    keyword: String,
-    #[serde(rename = "schemaPath")]
-    schema_path: String,
-    #[serde(rename = "dataPath")]
-    data_path: String,
+    code: ErrorCode,
+    schema_path: PathBuf,
+    data_path: PathBuf,
     message: String,
+    params: ErrorParams,
+    path_format: PathFormat,
 }
 
 impl ValidationError {
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
     pub fn keyword(&self) -> &str {
-        &self.keyword
+        self.code.keyword()
     }
 
-    pub fn schema_path(&self) -> &str {
-        &self.schema_path
+    pub fn params(&self) -> &ErrorParams {
+        &self.params
     }
 
-    pub fn data_path(&self) -> &str {
-        &self.data_path
+    /// Schema path rendered in the error path format
+    pub fn schema_path(&self) -> String {
+        self.schema_path.format(self.path_format)
+    }
+
+    /// Data path rendered in the error path format
+    pub fn data_path(&self) -> String {
+        self.data_path.format(self.path_format)
     }
 
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    /// Schema path components
+    pub fn schema_path_buf(&self) -> &PathBuf {
+        &self.schema_path
+    }
+
+    /// Data path components
+    pub fn data_path_buf(&self) -> &PathBuf {
+        &self.data_path
+    }
+
+    pub fn path_format(&self) -> PathFormat {
+        self.path_format
+    }
 }
 
 impl ValidationError {
-    pub fn new<S1, S2, S3, S4>(keyword: S1, schema_path: S2, data_path: S3, message: S4) -> ValidationError
+    pub fn new<S>(code: ErrorCode, schema_path: PathBuf, data_path: PathBuf, message: S) -> ValidationError
     where
-        S1: Into<String>,
-        S2: Into<String>,
-        S3: Into<String>,
-        S4: Into<String>,
+        S: Into<String>,
     {
         ValidationError {
-            keyword: keyword.into(),
-            schema_path: schema_path.into(),
-            data_path: data_path.into(),
+            code,
+            schema_path,
+            data_path,
+            message: message.into(),
+            params: ErrorParams::default(),
+            path_format: PathFormat::default(),
+        }
+    }
+
+    /// Replaces the error message (localized message, ...)
+    pub fn with_message<S: Into<String>>(self, message: S) -> ValidationError {
+        ValidationError {
             message: message.into(),
+            ..self
+        }
+    }
+
+    pub fn with_limit<V: Into<Value>>(mut self, limit: V) -> ValidationError {
+        self.params.limit = Some(limit.into());
+        self
+    }
+
+    pub fn with_actual<V: Into<Value>>(mut self, actual: V) -> ValidationError {
+        self.params.actual = Some(actual.into());
+        self
+    }
+
+    pub fn with_expected<V: Into<Value>>(mut self, expected: V) -> ValidationError {
+        self.params.expected = Some(expected.into());
+        self
+    }
+
+    pub fn with_expected_type<S: Into<String>>(mut self, expected_type: S) -> ValidationError {
+        self.params.expected_type = Some(expected_type.into());
+        self
+    }
+
+    pub fn with_pattern<S: Into<String>>(mut self, pattern: S) -> ValidationError {
+        self.params.pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn with_property<S: Into<String>>(mut self, property: S) -> ValidationError {
+        self.params.property = Some(property.into());
+        self
+    }
+
+    /// Changes the format of the rendered & serialized paths
+    ///
+    /// # Arguments
+    ///
+    /// * `path_format` - Path format
+    pub fn with_path_format(self, path_format: PathFormat) -> ValidationError {
+        ValidationError { path_format, ..self }
+    }
+}
+
+impl Serialize for ValidationError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("ValidationError", 6)?;
+        state.serialize_field("code", &self.code)?;
+        state.serialize_field("keyword", self.keyword())?;
+        state.serialize_field("schemaPath", &self.schema_path())?;
+        state.serialize_field("dataPath", &self.data_path())?;
+        state.serialize_field("message", &self.message)?;
+        if self.params.is_empty() {
+            state.skip_field("params")?;
+        } else {
+            state.serialize_field("params", &self.params)?;
         }
+        state.end()
     }
 }
 
@@ -51,9 +307,68 @@ impl fmt::Display for ValidationError {
         write!(
             f,
             "schema path: '{}', data path: '{}', keyword: '{}', message: '{}'",
-            self.schema_path, self.data_path, self.keyword, self.message
+            self.schema_path(),
+            self.data_path(),
+            self.keyword(),
+            self.message
         )
     }
 }
 
 impl std::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn error() -> ValidationError {
+        let mut schema_path = PathBuf::new();
+        schema_path.push_property("properties");
+        schema_path.push_index(0);
+        schema_path.push_property("a.b/c");
+        schema_path.push_property("type");
+
+        let mut data_path = PathBuf::new();
+        data_path.push_property("a.b/c");
+
+        ValidationError::new(ErrorCode::Type, schema_path, data_path, "expected 'integer'")
+            .with_expected_type("integer")
+            .with_actual("foo")
+    }
+
+    #[test]
+    fn dotted_paths() {
+        let error = error();
+        assert_eq!(error.schema_path(), "properties[0].a.b/c.type");
+        assert_eq!(error.data_path(), "a.b/c");
+    }
+
+    #[test]
+    fn serialize_without_params() {
+        let error = ValidationError::new(ErrorCode::Const, PathBuf::new(), PathBuf::new(), "value does not match");
+        assert_eq!(serde_json::to_value(&error).unwrap().get("params"), None);
+        assert_eq!(error.keyword(), "const");
+    }
+
+    #[test]
+    fn pointer_paths() {
+        let error = error().with_path_format(PathFormat::Pointer);
+        assert_eq!(error.schema_path(), "/properties/0/a.b~1c/type");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "type",
+                "keyword": "type",
+                "schemaPath": "/properties/0/a.b~1c/type",
+                "dataPath": "/a.b~1c",
+                "message": "expected 'integer'",
+                "params": {
+                    "expectedType": "integer",
+                    "actual": "foo"
+                }
+            })
+        );
+    }
+}
@@ -2,12 +2,12 @@ use serde_json::{self, Value};
 
 use crate::schema::{PrimitiveType, Schema};
 
-pub use error::ValidationError;
+pub use error::{ErrorCode, ErrorParams, ValidationError};
 use scope::ScopedSchema;
 pub use state::ValidationState;
 
 mod error;
-mod path;
+pub mod path;
 mod scope;
 mod state;
 mod types;
@@ -26,18 +26,15 @@ macro_rules! bail_if_invalid {
 }
 
 fn validate_optional(scope: &ScopedSchema, data: Option<&Value>) -> ValidationState {
-    let value_exists = match data {
-        Some(Value::Null) => false,
-        None => false,
-        _ => true,
-    };
+    let value_exists = !matches!(data, Some(Value::Null) | None);
 
     if !value_exists && scope.schema().r#type().is_required() {
         return scope
             .error(
-                "type",
-                format!("'{}' is not an optional type", scope.schema().r#type().to_string()),
+                ErrorCode::Required,
+                format!("'{}' is not an optional type", scope.schema().r#type()),
             )
+            .with_expected_type(scope.schema().r#type().primitive_type().as_ref())
             .into();
     }
 
@@ -46,7 +43,11 @@ fn validate_optional(scope: &ScopedSchema, data: Option<&Value>) -> ValidationSt
 
 fn validate_const(scope: &ScopedSchema, data: &Value) -> ValidationState {
     match scope.schema().r#const() {
-        Some(constant) if constant != data => scope.error("const", "value does not match").into(),
+        Some(constant) if constant != data => scope
+            .error(ErrorCode::Const, "value does not match")
+            .with_expected(constant.clone())
+            .with_actual(data.clone())
+            .into(),
         _ => ValidationState::new(),
     }
 }
@@ -64,8 +65,14 @@ fn validate_enum(scope: &ScopedSchema, data: &Value) -> ValidationState {
 
     match valid_count {
         1 => ValidationState::new(),
-        0 => scope.error("enum", "does not match any value").into(),
-        _ => scope.error("enum", "matches multiple values").into(),
+        0 => scope
+            .error(ErrorCode::Enum, "does not match any value")
+            .with_actual(data.clone())
+            .into(),
+        _ => scope
+            .error(ErrorCode::EnumAmbiguous, "matches multiple values")
+            .with_actual(data.clone())
+            .into(),
     }
 }
 
@@ -110,7 +117,7 @@ impl<'a> Validator for ScopedSchema<'a> {
 
 impl Validator for Schema {
     fn validate(&self, data: Option<&Value>) -> ValidationState {
-        ScopedSchema::new(self).validate(data)
+        ScopedSchema::new(self, data).validate(data)
     }
 }
 
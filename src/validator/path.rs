@@ -1,12 +1,47 @@
+//! Data & schema paths
+//!
+//! Paths are rendered in the dotted format (`networks[0].ssid`) by default. The dotted
+//! format is ambiguous for property names containing dots or brackets, use the
+//! [JSON Pointer](https://tools.ietf.org/html/rfc6901) format (`/networks/0/ssid`) if
+//! paths are consumed by other tools (ajv, rjsf, ...).
+//!
+//! # Examples
+//!
+//! ```
+//! use jellyschema::validator::path::{PathBuf, PathFormat};
+//!
+//! let mut path = PathBuf::new();
+//! path.push_property("networks");
+//! path.push_index(0);
+//! path.push_property("wifi.ssid");
+//!
+//! assert_eq!(path.to_string(), "networks[0].wifi.ssid");
+//! assert_eq!(path.format(PathFormat::Pointer), "/networks/0/wifi.ssid");
+//! assert_eq!(PathBuf::from_pointer("/networks/0/wifi.ssid").unwrap(), path);
+//! ```
 use std::fmt;
 
-#[derive(Debug, Clone)]
+use crate::error::Error;
+
+/// Path component
+#[derive(Debug, Clone, PartialEq)]
 pub enum Component {
     Property(String),
     Index(usize),
 }
 
-#[derive(Debug, Clone)]
+/// Path rendering format
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PathFormat {
+    /// Dotted format (`networks[0].ssid`)
+    #[default]
+    Dotted,
+    /// JSON Pointer format (`/networks/0/ssid`)
+    Pointer,
+}
+
+/// Data or schema path
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PathBuf {
     components: Vec<Component>,
 }
@@ -26,6 +61,103 @@ impl PathBuf {
     {
         self.components.push(Component::Property(property.into()))
     }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Renders the path in the given format
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Path format
+    pub fn format(&self, format: PathFormat) -> String {
+        match format {
+            PathFormat::Dotted => self.to_string(),
+            PathFormat::Pointer => self.to_pointer(),
+        }
+    }
+
+    /// Renders the path as the JSON Pointer
+    ///
+    /// `~` is escaped as `~0` and `/` as `~1`. Empty path is rendered as an empty string.
+    pub fn to_pointer(&self) -> String {
+        self.components
+            .iter()
+            .map(|component| match component {
+                Component::Property(s) => format!("/{}", s.replace('~', "~0").replace('/', "~1")),
+                Component::Index(idx) => format!("/{}", idx),
+            })
+            .collect()
+    }
+
+    /// Parses the JSON Pointer
+    ///
+    /// JSON Pointer doesn't distinguish array indexes and property names. Reference tokens
+    /// in the array index format (`0`, `12`, no leading zeros) are parsed as indexes.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer (`/networks/0/ssid`)
+    pub fn from_pointer(pointer: &str) -> Result<PathBuf, Error> {
+        let mut path = PathBuf::new();
+
+        if pointer.is_empty() {
+            return Ok(path);
+        }
+
+        if !pointer.starts_with('/') {
+            return Err(Error::message(format!(
+                "invalid JSON Pointer '{}': must start with '/'",
+                pointer
+            )));
+        }
+
+        for token in pointer[1..].split('/') {
+            if is_array_index(token) {
+                if let Ok(index) = token.parse() {
+                    path.push_index(index);
+                    continue;
+                }
+            }
+
+            path.push_property(unescape(token).ok_or_else(|| {
+                Error::message(format!(
+                    "invalid JSON Pointer '{}': invalid escape sequence in '{}'",
+                    pointer, token
+                ))
+            })?);
+        }
+
+        Ok(path)
+    }
+}
+
+fn is_array_index(token: &str) -> bool {
+    token == "0" || (!token.is_empty() && !token.starts_with('0') && token.chars().all(|c| c.is_ascii_digit()))
+}
+
+// `~0` -> `~`, `~1` -> `/`, any other `~` sequence is invalid
+fn unescape(token: &str) -> Option<String> {
+    let mut result = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => result.push('~'),
+                Some('1') => result.push('/'),
+                _ => return None,
+            },
+            c => result.push(c),
+        };
+    }
+
+    Some(result)
 }
 
 impl fmt::Display for PathBuf {
@@ -48,5 +180,38 @@ mod tests {
     #[test]
     fn empty_buf() {
         assert_eq!(&PathBuf::new().to_string(), "");
+        assert_eq!(&PathBuf::new().to_pointer(), "");
+    }
+
+    #[test]
+    fn escape_pointer() {
+        let mut path = PathBuf::new();
+        path.push_property("a/b");
+        path.push_property("m~n");
+        path.push_index(10);
+        path.push_property("");
+        assert_eq!(path.to_pointer(), "/a~1b/m~0n/10/");
+        assert_eq!(PathBuf::from_pointer(&path.to_pointer()).unwrap(), path);
+    }
+
+    #[test]
+    fn parse_pointer() {
+        let path = PathBuf::from_pointer("/foo/0/01/~01").unwrap();
+        assert_eq!(
+            path.components(),
+            &[
+                Component::Property("foo".to_string()),
+                Component::Index(0),
+                Component::Property("01".to_string()),
+                Component::Property("~1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_pointer() {
+        assert!(PathBuf::from_pointer("foo").is_err());
+        assert!(PathBuf::from_pointer("/foo~2").is_err());
+        assert!(PathBuf::from_pointer("/foo~").is_err());
     }
 }
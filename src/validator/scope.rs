@@ -1,6 +1,13 @@
+use serde_json::Value;
+
 use crate::{
+    error::Error,
+    evaluator,
     schema::{Property, Schema},
-    validator::{error::ValidationError, path::PathBuf},
+    validator::{
+        error::{ErrorCode, ValidationError},
+        path::PathBuf,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -8,19 +15,22 @@ pub struct ScopedSchema<'a> {
     schema: &'a Schema,
     schema_path: PathBuf,
     data_path: PathBuf,
+    // Validated data, `when` conditions are evaluated against it
+    root: Option<&'a Value>,
 }
 
 impl<'a> ScopedSchema<'a> {
-    pub fn new(schema: &Schema) -> ScopedSchema {
+    pub fn new(schema: &'a Schema, root: Option<&'a Value>) -> ScopedSchema<'a> {
         ScopedSchema {
             schema,
             schema_path: PathBuf::new(),
             data_path: PathBuf::new(),
+            root,
         }
     }
 
     pub fn schema(&self) -> &Schema {
-        &self.schema
+        self.schema
     }
 
     pub fn schema_path(&self) -> &PathBuf {
@@ -30,10 +40,14 @@ impl<'a> ScopedSchema<'a> {
     pub fn data_path(&self) -> &PathBuf {
         &self.data_path
     }
+
+    pub fn is_active(&self) -> Result<bool, Error> {
+        evaluator::is_active(self.schema, &self.data_path, self.root.unwrap_or(&Value::Null))
+    }
 }
 
 impl<'a> ScopedSchema<'a> {
-    pub fn scope_with_data_index(&self, index: usize) -> ScopedSchema {
+    pub fn scope_with_data_index(&self, index: usize) -> ScopedSchema<'_> {
         let mut data_path = self.data_path.clone();
         data_path.push_index(index);
 
@@ -41,10 +55,14 @@ impl<'a> ScopedSchema<'a> {
             schema: self.schema,
             schema_path: self.schema_path.clone(),
             data_path,
+            root: self.root,
         }
     }
 
-    pub fn scope_with_property<'b>(&self, index: usize, property: &'b Property) -> ScopedSchema<'b> {
+    pub fn scope_with_property<'b>(&self, index: usize, property: &'b Property) -> ScopedSchema<'b>
+    where
+        'a: 'b,
+    {
         let mut data_path = self.data_path.clone();
         data_path.push_property(property.name());
 
@@ -57,10 +75,14 @@ impl<'a> ScopedSchema<'a> {
             schema: property.schema(),
             schema_path,
             data_path,
+            root: self.root,
         }
     }
 
-    pub fn scope_with_schema_index<'b>(&self, index: usize, schema: &'b Schema) -> ScopedSchema<'b> {
+    pub fn scope_with_schema_index<'b>(&self, index: usize, schema: &'b Schema) -> ScopedSchema<'b>
+    where
+        'a: 'b,
+    {
         let mut schema_path = self.schema_path.clone();
         schema_path.push_index(index);
 
@@ -68,10 +90,11 @@ impl<'a> ScopedSchema<'a> {
             schema,
             schema_path,
             data_path: self.data_path.clone(),
+            root: self.root,
         }
     }
 
-    pub fn scope_with_schema_keyword<S: Into<String>>(&self, keyword: S) -> ScopedSchema {
+    pub fn scope_with_schema_keyword<S: Into<String>>(&self, keyword: S) -> ScopedSchema<'_> {
         let mut schema_path = self.schema_path.clone();
         schema_path.push_property(keyword);
 
@@ -79,21 +102,29 @@ impl<'a> ScopedSchema<'a> {
             schema: self.schema,
             schema_path,
             data_path: self.data_path.clone(),
+            root: self.root,
         }
     }
 }
 
 impl<'a> ScopedSchema<'a> {
-    pub fn error<S1, S2>(&self, keyword: S1, message: S2) -> ValidationError
+    pub fn error<S>(&self, code: ErrorCode, message: S) -> ValidationError
     where
-        S1: Into<String>,
-        S2: Into<String>,
+        S: Into<String>,
     {
-        let keyword = keyword.into();
-
         let mut schema_path = self.schema_path().clone();
-        schema_path.push_property(keyword.clone());
+        schema_path.push_property(code.keyword());
+
+        ValidationError::new(code, schema_path, self.data_path().clone(), message)
+    }
 
-        ValidationError::new(keyword, schema_path.to_string(), self.data_path().to_string(), message)
+    /// Type error with the expected type & actual value parameters
+    pub fn type_error<S>(&self, code: ErrorCode, message: S, data: &Value) -> ValidationError
+    where
+        S: Into<String>,
+    {
+        self.error(code, message)
+            .with_expected_type(self.schema.r#type().primitive_type().as_ref())
+            .with_actual(data.clone())
     }
 }
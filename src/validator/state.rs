@@ -1,4 +1,4 @@
-use crate::validator::error::ValidationError;
+use crate::validator::{error::ValidationError, path::PathFormat};
 
 #[derive(Debug, Default)]
 pub struct ValidationState {
@@ -38,6 +38,21 @@ impl ValidationState {
     pub fn errors(&self) -> &Vec<ValidationError> {
         &self.errors
     }
+
+    /// Changes the path format of all errors
+    ///
+    /// # Arguments
+    ///
+    /// * `path_format` - Path format
+    pub fn with_path_format(self, path_format: PathFormat) -> ValidationState {
+        ValidationState {
+            errors: self
+                .errors
+                .into_iter()
+                .map(|x| x.with_path_format(path_format))
+                .collect(),
+        }
+    }
 }
 
 impl From<ValidationError> for ValidationState {
@@ -3,7 +3,7 @@ use serde_json::Value;
 
 use crate::{
     schema::UniqueItems,
-    validator::{scope::ScopedSchema, state::ValidationState, Validator},
+    validator::{scope::ScopedSchema, state::ValidationState, ErrorCode, Validator},
 };
 
 enum ValueWrapper<'a> {
@@ -50,13 +50,13 @@ fn validate_items_uniqueness_by(scope: &ScopedSchema, items: &[Value], path: &st
         match engine.eval(path, &position, item, &mut ctx).map(ValueWrapper::from) {
             Ok(wrapped) => {
                 if disctinct.contains(&wrapped) {
-                    state.push_error(scope.error("uniqueItems", path));
+                    state.push_error(scope.error(ErrorCode::UniqueItems, path).with_pattern(path));
                 } else {
                     disctinct.push(wrapped);
                 }
             }
             Err(_) => {
-                state.push_error(scope.error("uniqueItems", path));
+                state.push_error(scope.error(ErrorCode::UniqueItems, path).with_pattern(path));
             }
         }
     }
@@ -71,7 +71,11 @@ fn validate_items_uniqueness(scope: &ScopedSchema, items: &[Value]) -> Validatio
 
     for wrapped in items.iter().map(ValueWrapper::from) {
         if distinct.contains(&wrapped) {
-            state.push_error(scope.error("uniqueItems", "expected unique items"));
+            state.push_error(
+                scope
+                    .error(ErrorCode::UniqueItems, "expected unique items")
+                    .with_actual(wrapped.value_ref().clone()),
+            );
         } else {
             distinct.push(wrapped);
         }
@@ -100,7 +104,7 @@ pub fn validate_as_array(scope: &ScopedSchema, data: &Value) -> ValidationState
     // Validate type
     let data_array = match data.as_array() {
         Some(x) => x,
-        None => return scope.error("type", "expected 'array'").into(),
+        None => return scope.type_error(ErrorCode::Type, "expected 'array'", data).into(),
     };
 
     let schema = scope.schema();
@@ -110,13 +114,23 @@ pub fn validate_as_array(scope: &ScopedSchema, data: &Value) -> ValidationState
     // Validate array keywords (min/max items)
     if let Some(min) = schema.min_items() {
         if data_array.len() < min {
-            state.push_error(scope.error("minItems", format!("should contain at least '{}' items", min)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::MinItems, format!("should contain at least '{}' items", min))
+                    .with_limit(min)
+                    .with_actual(data_array.len()),
+            );
         }
     }
 
     if let Some(max) = schema.max_items() {
         if data_array.len() > max {
-            state.push_error(scope.error("maxItems", format!("should contain up to '{}' items", max)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::MaxItems, format!("should contain up to '{}' items", max))
+                    .with_limit(max)
+                    .with_actual(data_array.len()),
+            );
         }
     }
 
@@ -124,13 +138,36 @@ pub fn validate_as_array(scope: &ScopedSchema, data: &Value) -> ValidationState
     state.extend(validate_unique_items(scope, data_array));
 
     // Validate items keyword
+    let array_scope = scope;
     let scope = scope.scope_with_schema_keyword("items");
 
     for (idx, item) in data_array.iter().enumerate() {
-        let mut valid_count = 0;
-
         let data_scope = scope.scope_with_data_index(idx);
 
+        // Discriminated items are validated against the selected schema only
+        if let Some(discriminator) = schema.discriminator() {
+            match schema.discriminated_items(item) {
+                Some((idx, array_schema)) => state.extend(
+                    data_scope
+                        .scope_with_schema_index(idx, array_schema)
+                        .validate(Some(item)),
+                ),
+                None => state.push_error(
+                    array_scope
+                        .scope_with_data_index(idx)
+                        .error(
+                            ErrorCode::Discriminator,
+                            format!("'{}' does not match any items schema", discriminator),
+                        )
+                        .with_property(discriminator)
+                        .with_actual(item.get(discriminator).cloned().unwrap_or(Value::Null)),
+                ),
+            };
+            continue;
+        }
+
+        let mut valid_count = 0;
+
         let mut data_item_state = ValidationState::new();
 
         for (idx, array_schema) in scope.schema().items().iter().enumerate() {
@@ -148,7 +185,7 @@ pub fn validate_as_array(scope: &ScopedSchema, data: &Value) -> ValidationState
         match valid_count {
             0 => state.extend(data_item_state),
             1 => {}
-            _ => state.push_error(data_scope.error("items", "valid against multiple schemas")),
+            _ => state.push_error(data_scope.error(ErrorCode::ItemsAmbiguous, "valid against multiple schemas")),
         };
     }
 
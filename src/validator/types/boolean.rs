@@ -1,10 +1,10 @@
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, ErrorCode};
 
 pub fn validate_as_boolean(scope: &ScopedSchema, data: &Value) -> ValidationState {
     if !data.is_boolean() {
-        ValidationState::new_with_error(scope.error("type", "expected 'boolean'"))
+        ValidationState::new_with_error(scope.type_error(ErrorCode::Type, "expected 'boolean'", data))
     } else {
         ValidationState::new()
     }
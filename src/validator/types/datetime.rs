@@ -3,7 +3,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string, ErrorCode};
 
 lazy_static! {
     // ajv v6.7.0 compatible
@@ -23,7 +23,7 @@ pub fn validate_as_datetime(scope: &ScopedSchema, data: &Value) -> ValidationSta
     if state.is_valid()
         && chrono::DateTime::parse_from_rfc3339(data.as_str().expect("invalid validate_as_string")).is_err()
     {
-        state.push_error(scope.error("type", "unable to parse as 'datetime'"));
+        state.push_error(scope.type_error(ErrorCode::Format, "unable to parse as 'datetime'", data));
     }
 
     state
@@ -31,7 +31,7 @@ pub fn validate_as_datetime(scope: &ScopedSchema, data: &Value) -> ValidationSta
 
 fn is_leap_year(year: usize) -> bool {
     // https://tools.ietf.org/html/rfc3339#appendix-C
-    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
 }
 
 fn days(year: usize, month: usize) -> usize {
@@ -47,43 +47,43 @@ fn days(year: usize, month: usize) -> usize {
 pub fn validate_as_date(scope: &ScopedSchema, data: &Value) -> ValidationState {
     let s = match data.as_str() {
         Some(x) => x,
-        None => return scope.error("type", "expected `date`").into(),
+        None => return scope.type_error(ErrorCode::Type, "expected `date`", data).into(),
     };
 
     let captures = match DATE_REGEX.captures(s) {
         Some(x) => x,
-        _ => return scope.error("type", "expected `date`").into(),
+        _ => return scope.type_error(ErrorCode::Format, "expected `date`", data).into(),
     };
 
-    let year: usize = (&captures[1]).parse().expect("invalid regex");
-    let month: usize = (&captures[2]).parse().expect("invalid regex");
-    let day: usize = (&captures[3]).parse().expect("invalid regex");
+    let year: usize = captures[1].parse().expect("invalid regex");
+    let month: usize = captures[2].parse().expect("invalid regex");
+    let day: usize = captures[3].parse().expect("invalid regex");
 
-    if month >= 1 && month <= 12 && day >= 1 && day <= days(year, month) {
+    if (1..=12).contains(&month) && day >= 1 && day <= days(year, month) {
         ValidationState::new()
     } else {
-        scope.error("type", "invalid `date` range").into()
+        scope.type_error(ErrorCode::Format, "invalid `date` range", data).into()
     }
 }
 
 pub fn validate_as_time(scope: &ScopedSchema, data: &Value) -> ValidationState {
     let s = match data.as_str() {
         Some(x) => x,
-        None => return scope.error("type", "expected `time`").into(),
+        None => return scope.type_error(ErrorCode::Type, "expected `time`", data).into(),
     };
 
     let captures = match TIME_REGEX.captures(s) {
         Some(x) => x,
-        _ => return scope.error("type", "expected `time`").into(),
+        _ => return scope.type_error(ErrorCode::Format, "expected `time`", data).into(),
     };
 
-    let hour: usize = (&captures[1]).parse().expect("invalid regex");
-    let min: usize = (&captures[2]).parse().expect("invalid regex");
-    let sec: usize = (&captures[3]).parse().expect("invalid regex");
+    let hour: usize = captures[1].parse().expect("invalid regex");
+    let min: usize = captures[2].parse().expect("invalid regex");
+    let sec: usize = captures[3].parse().expect("invalid regex");
 
     if (hour <= 23 && min <= 59 && sec <= 59) || (hour == 23 && min == 59 && sec == 60) {
         ValidationState::new()
     } else {
-        scope.error("type", "invalid `time` range").into()
+        scope.type_error(ErrorCode::Format, "invalid `time` range", data).into()
     }
 }
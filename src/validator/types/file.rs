@@ -1,8 +1,9 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, ErrorCode};
 
 lazy_static! {
     // data:text/plain;name=test.txt;base64,aGV...
@@ -13,24 +14,28 @@ lazy_static! {
 pub fn validate_as_file(scope: &ScopedSchema, data: &Value) -> ValidationState {
     let s = match data.as_str() {
         Some(x) => x,
-        None => return scope.error("type", "expected `file`").into(),
+        None => return scope.type_error(ErrorCode::Type, "expected `file`", data).into(),
     };
 
     let captures = match FILE_REGEX.captures(s) {
         Some(x) => x,
-        _ => return scope.error("type", "expected `file`").into(),
+        _ => return scope.type_error(ErrorCode::Format, "expected `file`", data).into(),
     };
 
-    if (&captures[1]).is_empty() {
-        return scope.error("type", "file name is missing").into();
+    if captures[1].is_empty() {
+        return scope.type_error(ErrorCode::Format, "file name is missing", data).into();
     }
 
     if &captures[2] != "base64" {
-        return scope.error("type", "only base64 is supported").into();
+        return scope
+            .type_error(ErrorCode::Format, "only base64 is supported", data)
+            .into();
     }
 
-    match base64::decode(&captures[3]) {
+    match STANDARD.decode(&captures[3]) {
         Ok(_) => ValidationState::new(),
-        Err(_) => scope.error("type", "unable to decode file data").into(),
+        Err(_) => scope
+            .type_error(ErrorCode::Format, "unable to decode file data", data)
+            .into(),
     }
 }
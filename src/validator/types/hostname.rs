@@ -2,7 +2,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string_with_regex};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string_with_regex, ErrorCode};
 
 lazy_static! {
     // ajv v6.7.0 compatible
@@ -22,7 +22,15 @@ pub fn validate_as_hostname(scope: &ScopedSchema, data: &Value) -> ValidationSta
             .count();
 
         if len > 255 {
-            state.push_error(scope.error("type", "'hostname' must not be longer than 255 characters"));
+            state.push_error(
+                scope
+                    .type_error(
+                        ErrorCode::Format,
+                        "'hostname' must not be longer than 255 characters",
+                        data,
+                    )
+                    .with_limit(255),
+            );
         }
     }
 
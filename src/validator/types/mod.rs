@@ -43,15 +43,16 @@ mod uri;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, ValidationState};
+use crate::validator::{scope::ScopedSchema, ErrorCode, ValidationState};
 
 pub(crate) fn validate_as_string_with_regex(scope: &ScopedSchema, data: &Value, regex: &Regex) -> ValidationState {
     let mut state = validate_as_string(scope, data);
 
     if state.is_valid() && !regex.is_match(data.as_str().expect("invalid validate_as_string")) {
-        state.push_error(scope.error(
-            "type",
+        state.push_error(scope.type_error(
+            ErrorCode::Format,
             format!("expected '{}'", scope.schema().r#type().primitive_type().as_ref()),
+            data,
         ));
     }
 
@@ -1,7 +1,7 @@
 use serde_json::Number;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, ErrorCode};
 
 fn validate_as<T, F1, F2>(
     scope: &ScopedSchema,
@@ -17,9 +17,10 @@ where
     let value = match data_value(data) {
         Some(x) => x,
         None => {
-            return ValidationState::new_with_error(scope.error(
-                "type",
+            return ValidationState::new_with_error(scope.type_error(
+                ErrorCode::Type,
                 format!("expected '{}'", scope.schema().r#type().primitive_type().as_ref()),
+                data,
             ));
         }
     };
@@ -29,35 +30,60 @@ where
 
     if let Some(min) = schema.min().and_then(schema_number_value) {
         if value < min {
-            state.push_error(scope.error("min", format!("expected '>= {}", min)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::Min, format!("expected '>= {}", min))
+                    .with_limit(schema.min().cloned().unwrap())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
     if let Some(exclusive_min) = schema.exclusive_min().and_then(schema_number_value) {
         if value <= exclusive_min {
-            state.push_error(scope.error("exclusiveMin", format!("expected '> {}", exclusive_min)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::ExclusiveMin, format!("expected '> {}", exclusive_min))
+                    .with_limit(schema.exclusive_min().cloned().unwrap())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
     if let Some(max) = schema.max().and_then(schema_number_value) {
         if value > max {
-            state.push_error(scope.error("max", format!("expected '<= {}", max)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::Max, format!("expected '<= {}", max))
+                    .with_limit(schema.max().cloned().unwrap())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
     if let Some(exclusive_max) = schema.exclusive_max().and_then(schema_number_value) {
         if value >= exclusive_max {
-            state.push_error(scope.error("exclusiveMax", format!("expected '< {}", exclusive_max)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::ExclusiveMax, format!("expected '< {}", exclusive_max))
+                    .with_limit(schema.exclusive_max().cloned().unwrap())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
     let zero = schema_number_value(&Number::from(0)).unwrap();
     if let Some(multiple_of) = schema.multiple_of().and_then(schema_number_value) {
         if multiple_of > zero && value % multiple_of != zero {
-            state.push_error(scope.error(
-                "multipleOf",
-                format!("expected '{} % {} == {}'", value, multiple_of, zero),
-            ));
+            state.push_error(
+                scope
+                    .error(
+                        ErrorCode::MultipleOf,
+                        format!("expected '{} % {} == {}'", value, multiple_of, zero),
+                    )
+                    .with_limit(schema.multiple_of().cloned().unwrap())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
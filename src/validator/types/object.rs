@@ -2,12 +2,12 @@ use std::collections::HashSet;
 
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState, Validator};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, ErrorCode, Validator};
 
 pub fn validate_as_object(scope: &ScopedSchema, data: &Value) -> ValidationState {
     let object = match data.as_object() {
         Some(x) => x,
-        None => return ValidationState::new_with_error(scope.error("type", "expected 'object'")),
+        None => return ValidationState::new_with_error(scope.type_error(ErrorCode::Type, "expected 'object'", data)),
     };
 
     let mut state = ValidationState::new();
@@ -17,9 +17,14 @@ pub fn validate_as_object(scope: &ScopedSchema, data: &Value) -> ValidationState
     // Validate .properties first
     for (index, property) in scope.schema().properties().iter().enumerate() {
         let nested_scope = scope.scope_with_property(index, property);
-        let nested_state = nested_scope.validate(object.get(property.name()));
-        state.extend(nested_state);
         remaining_keys.remove(property.name());
+
+        // Inactive property (`when` condition) is neither required nor validated
+        match nested_scope.is_active() {
+            Ok(true) => state.extend(nested_scope.validate(object.get(property.name()))),
+            Ok(false) => {}
+            Err(e) => state.push_error(nested_scope.error(ErrorCode::When, e.to_string())),
+        };
     }
 
     match (scope.schema().keys(), scope.schema().values()) {
@@ -33,8 +38,15 @@ pub fn validate_as_object(scope: &ScopedSchema, data: &Value) -> ValidationState
         }
         // Schema doesn't contain keys & values, just check for additional properties
         _ => {
-            if !remaining_keys.is_empty() && !scope.schema().additional_properties() {
-                state.push_error(scope.error("additionalProperties", "not allowed"));
+            if !scope.schema().additional_properties() {
+                // The first one in the alphabetical order, HashSet order is random
+                if let Some(key) = remaining_keys.iter().min() {
+                    state.push_error(
+                        scope
+                            .error(ErrorCode::AdditionalProperties, "not allowed")
+                            .with_property(*key),
+                    );
+                }
             }
         }
     }
@@ -1,14 +1,15 @@
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, ErrorCode};
 
 pub fn validate_as_string(scope: &ScopedSchema, data: &Value) -> ValidationState {
     let string = match data.as_str() {
         Some(x) => x,
         None => {
-            return ValidationState::new_with_error(scope.error(
-                "type",
+            return ValidationState::new_with_error(scope.type_error(
+                ErrorCode::Type,
                 format!("expected '{}'", scope.schema().r#type().primitive_type().as_ref()),
+                data,
             ));
         }
     };
@@ -19,19 +20,34 @@ pub fn validate_as_string(scope: &ScopedSchema, data: &Value) -> ValidationState
 
     if let Some(min) = schema.min_length() {
         if len < min {
-            state.push_error(scope.error("minLength", format!("expected '>= {}'", min)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::MinLength, format!("expected '>= {}'", min))
+                    .with_limit(min)
+                    .with_actual(len),
+            );
         }
     }
 
     if let Some(max) = schema.max_length() {
         if len > max {
-            state.push_error(scope.error("maxLength", format!("expected '<= {}'", max)));
+            state.push_error(
+                scope
+                    .error(ErrorCode::MaxLength, format!("expected '<= {}'", max))
+                    .with_limit(max)
+                    .with_actual(len),
+            );
         }
     }
 
     if let Some(regex) = schema.pattern() {
         if !regex.is_match(string) {
-            state.push_error(scope.error("pattern", "does not match"));
+            state.push_error(
+                scope
+                    .error(ErrorCode::Pattern, "does not match")
+                    .with_pattern(regex.as_str())
+                    .with_actual(data.clone()),
+            );
         }
     }
 
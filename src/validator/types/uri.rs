@@ -5,7 +5,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string_with_regex};
+use crate::validator::{scope::ScopedSchema, state::ValidationState, types::validate_as_string_with_regex, ErrorCode};
 
 lazy_static! {
     // ajv v6.7.0 compatible
@@ -23,7 +23,7 @@ pub fn validate_as_uri(scope: &ScopedSchema, data: &Value) -> ValidationState {
         let string = data.as_str().expect("invalid validate_as_string_with_regex");
 
         if !NOT_URI_FRAGMENT_REGEX.is_match(string) {
-            state.push_error(scope.error("type", "unable to parse as 'uri'"));
+            state.push_error(scope.type_error(ErrorCode::Format, "unable to parse as 'uri'", data));
         }
     }
 
@@ -1,20 +1,26 @@
 use console_error_panic_hook::set_once as set_panic_hook_once;
 use serde_json::{json, Value};
-use std::str::FromStr;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 use crate::{
     filler::fill_default_values,
     generator::generate_json_ui_schema,
-    schema::Schema,
-    validator::{ValidationError, ValidationState, Validator},
+    locale::{Catalogs, DEFAULT_LOCALE},
+    schema::{InMemoryResolver, Schema},
+    validator::{
+        path::{PathBuf, PathFormat},
+        ErrorCode, ValidationError, ValidationState, Validator,
+    },
 };
 
 #[wasm_bindgen]
 pub struct JellySchema {
     schema: Schema,
-    json_ui_schema: Option<(Value, Value)>,
+    // Generated schemas keyed by the annotations locale
+    json_ui_schemas: HashMap<String, (Value, Value)>,
     last_validation_state: ValidationState,
+    catalogs: Catalogs,
 }
 
 #[wasm_bindgen]
@@ -25,24 +31,43 @@ impl JellySchema {
     /// # Arguments
     ///
     /// * `schema` - JellySchema as a string or an object
+    /// * `includes` - Optional object with documents for the `include` keyword (name -> source)
     ///
     /// # Throws
     ///
-    /// Constructor throws in case of invalid `schema` argument value.
+    /// Constructor throws in case of invalid `schema` argument value. Thrown error is an
+    /// object with the `message` property. `line`, `column` and `schemaPath` properties
+    /// are present if the error location is known, `file` property is present if the
+    /// error comes from an included document.
     #[wasm_bindgen(constructor)]
-    pub fn constructor(schema: &JsValue) -> Result<JellySchema, JsValue> {
+    pub fn constructor(schema: &JsValue, includes: &JsValue) -> Result<JellySchema, JsValue> {
         set_panic_hook_once();
 
-        let schema: Schema = if schema.is_string() {
-            Schema::from_str(&schema.as_string().unwrap()).map_err(|e| JsValue::from(format!("{}", e)))?
+        let mut resolver = InMemoryResolver::new();
+        if !includes.is_undefined() && !includes.is_null() {
+            let documents: HashMap<String, String> =
+                includes.into_serde().map_err(|e| JsValue::from(format!("{}", e)))?;
+            for (name, source) in documents {
+                resolver.insert(name, source);
+            }
+        }
+
+        // JSON is a subset of YAML, objects are parsed in the same way as strings
+        let source = if schema.is_string() {
+            schema.as_string().unwrap()
         } else {
-            schema.into_serde().map_err(|e| JsValue::from(format!("{}", e)))?
+            let value: Value = schema.into_serde().map_err(|e| JsValue::from(format!("{}", e)))?;
+            value.to_string()
         };
 
+        let (schema, _) = Schema::parse_with_resolver(&source, None, &resolver)
+            .map_err(|e| JsValue::from_serde(&e).unwrap_or_else(|_| JsValue::from(format!("{}", e))))?;
+
         Ok(JellySchema {
             schema,
-            json_ui_schema: None,
+            json_ui_schemas: HashMap::new(),
             last_validation_state: ValidationState::new(),
+            catalogs: Catalogs::new(),
         })
     }
 
@@ -71,8 +96,13 @@ impl JellySchema {
                 Ok(self.last_validation_state.is_valid())
             }
             Err(e) => {
-                self.last_validation_state =
-                    ValidationError::new("", "", "", format!("unable to deserialize given data: {}", e)).into();
+                self.last_validation_state = ValidationError::new(
+                    ErrorCode::InvalidData,
+                    PathBuf::new(),
+                    PathBuf::new(),
+                    format!("unable to deserialize given data: {}", e),
+                )
+                .into();
                 Ok(false)
             }
         }
@@ -87,15 +117,20 @@ impl JellySchema {
     /// }
     /// ```
     ///
+    /// # Arguments
+    ///
+    /// * `locale` - Annotations locale (`de`, `pt-BR`, ...), English if not provided
+    ///
     /// # Throws
     ///
     /// In case of internal error only (serialization).
-    pub fn jsonAndUiSchema(&mut self) -> Result<JsValue, JsValue> {
-        if self.json_ui_schema.is_none() {
-            self.json_ui_schema = Some(generate_json_ui_schema(&self.schema));
-        }
-
-        let schemas = self.json_ui_schema.as_ref().unwrap();
+    pub fn jsonAndUiSchema(&mut self, locale: Option<String>) -> Result<JsValue, JsValue> {
+        let locale = locale.unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+        let schema = &self.schema;
+        let schemas = self
+            .json_ui_schemas
+            .entry(locale)
+            .or_insert_with_key(|locale| generate_json_ui_schema(schema, Some(locale)));
 
         JsValue::from_serde(&json!({
             "jsonSchema": schemas.0,
@@ -113,6 +148,71 @@ impl JellySchema {
         JsValue::from_serde(&json!(self.last_validation_state.errors()))
             .map_err(|e| JsValue::from_str(&format!("{}", e)))
     }
+
+    /// Returns last validation errors with paths in the JSON Pointer format
+    ///
+    /// # Throws
+    ///
+    /// In case of internal error only (serialization).
+    pub fn errorsWithPointers(&self) -> Result<JsValue, JsValue> {
+        let errors: Vec<ValidationError> = self
+            .last_validation_state
+            .errors()
+            .iter()
+            .cloned()
+            .map(|x| x.with_path_format(PathFormat::Pointer))
+            .collect();
+
+        JsValue::from_serde(&json!(errors)).map_err(|e| JsValue::from_str(&format!("{}", e)))
+    }
+
+    /// Adds the validation messages catalog
+    ///
+    /// Messages are merged into the existing locale catalog. English catalog is built in.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    /// * `catalog` - Message catalog as a string (YAML, JSON) or an object (code -> message)
+    ///
+    /// # Throws
+    ///
+    /// If the catalog is invalid.
+    pub fn addMessageCatalog(&mut self, locale: &str, catalog: &JsValue) -> Result<(), JsValue> {
+        // JSON is a subset of YAML, objects are parsed in the same way as strings
+        let source = if catalog.is_string() {
+            catalog.as_string().unwrap()
+        } else {
+            let value: Value = catalog.into_serde().map_err(|e| JsValue::from(format!("{}", e)))?;
+            value.to_string()
+        };
+
+        self.catalogs
+            .load(locale, &source)
+            .map_err(|e| JsValue::from_str(&format!("{}", e)))
+    }
+
+    /// Returns last validation errors with messages in the requested locale
+    ///
+    /// Missing messages fall back to the locale language (`pt` for `pt-BR`) and English.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale (`de`, `pt-BR`, ...)
+    ///
+    /// # Throws
+    ///
+    /// In case of internal error only (serialization).
+    pub fn localizedErrors(&self, locale: &str) -> Result<JsValue, JsValue> {
+        let errors: Vec<ValidationError> = self
+            .last_validation_state
+            .errors()
+            .iter()
+            .map(|x| self.catalogs.localize(x, locale))
+            .collect();
+
+        JsValue::from_serde(&json!(errors)).map_err(|e| JsValue::from_str(&format!("{}", e)))
+    }
 }
 
 /// Generates JSON and UI schema object
@@ -137,11 +237,11 @@ pub fn generateJsonAndUiSchema(schema: &JsValue) -> Result<JsValue, JsValue> {
     // This is okay for now, but in the future, when the `constructor`
     // will be much more expensive (doing lot of other things), we will have to
     // replace it with direct calls to `generate_json_ui_schema`, etc.
-    JellySchema::constructor(schema)?.jsonAndUiSchema()
+    JellySchema::constructor(schema, &JsValue::UNDEFINED)?.jsonAndUiSchema(None)
 }
 
 #[wasm_bindgen]
 #[allow(non_snake_case)]
 pub fn fillDefaultValues(schema: &JsValue, data: &JsValue, include_optional: bool) -> Result<JsValue, JsValue> {
-    JellySchema::constructor(schema)?.fillDefaultValues(data, include_optional)
+    JellySchema::constructor(schema, &JsValue::UNDEFINED)?.fillDefaultValues(data, include_optional)
 }